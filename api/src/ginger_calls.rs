@@ -1,9 +1,11 @@
 use algebra::{
     fields::{
-        mnt4753::{Fr as MNT4Fr, Fq as MNT4Fq}, PrimeField
+        mnt4753::{Fr as MNT4Fr, Fq as MNT4Fq, Fq2 as MNT4Fq2}, Field, PrimeField, FpParameters
     },
     curves::{
-        mnt4753::MNT4,
+        mnt4753::{
+            MNT4, G1Affine as MNT4G1Affine, G2Affine as MNT4G2Affine,
+        },
         mnt6753::{
             G1Projective as MNT6G1Projective, G1Affine as MNT6G1Affine
         },
@@ -35,16 +37,29 @@ use proof_systems::groth16::{
     Proof, create_random_proof,
     prepare_verifying_key, verify_proof,
 };
+use ouroboros::vrf::{
+    LeaderProof, NonceProof, LeaderOutput, NonceOutput,
+    ouroboros_create_proof, ouroboros_check_proof,
+};
 use demo_circuit::{
     constants::{
         VRFParams, VRFWindow,
     },
+    domain_separation::*,
     naive_threshold_sig::*
 };
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, RngCore, SeedableRng};
+use rand_chacha::ChaChaRng;
+use scrypt::{scrypt, ScryptParams};
+use chacha20poly1305::{
+    ChaCha20Poly1305, aead::{Aead, NewAead, Payload, generic_array::GenericArray}
+};
+use sha2::{Sha256, Digest};
+use rayon::prelude::*;
 
 use std::{
-    fs::File, io::Result as IoResult
+    fs::File, io::{Result as IoResult, Error as IoError, ErrorKind, Read, Write},
+    collections::HashMap, fmt, ops::Deref, ptr, sync::atomic,
 };
 use lazy_static::*;
 
@@ -63,6 +78,14 @@ pub const VRF_SK_SIZE: usize = SCALAR_FIELD_SIZE; // 96
 pub const VRF_PROOF_SIZE: usize = G1_SIZE + 2 * FIELD_SIZE; // 192
 
 pub const ZK_PROOF_SIZE: usize = 2 * G1_SIZE + G2_SIZE;  // 771
+
+// Compressed encodings: points are written as their x coordinate only
+pub const G1_COMPRESSED_SIZE: usize = FIELD_SIZE; // 96
+pub const G2_COMPRESSED_SIZE: usize = 2 * FIELD_SIZE; // 192
+pub const SCHNORR_PK_COMPRESSED_SIZE: usize = G1_COMPRESSED_SIZE; // 96
+pub const VRF_PK_COMPRESSED_SIZE: usize = G1_COMPRESSED_SIZE; // 96
+pub const VRF_PROOF_COMPRESSED_SIZE: usize = G1_COMPRESSED_SIZE + 2 * FIELD_SIZE; // 288
+pub const ZK_PROOF_COMPRESSED_SIZE: usize = 2 * G1_COMPRESSED_SIZE + G2_COMPRESSED_SIZE; // 384
 pub type Error = Box<dyn std::error::Error>;
 
 //*******************************Generic I/O functions**********************************************
@@ -87,319 +110,2688 @@ pub fn get_random_field_element() -> FieldElement {
     FieldElement::rand(&mut rng)
 }
 
-//***************************Schnorr types and functions********************************************
-
-pub type SchnorrSigScheme = FieldBasedSchnorrSignatureScheme<MNT4Fr, MNT6G1Projective, MNT4PoseidonHash>;
-pub type SchnorrSig = FieldBasedSchnorrSignature<MNT4Fr>;
-pub type SchnorrPk = MNT6G1Affine;
-pub type SchnorrSk = MNT4Fq;
+//*******************************Secret key functions***********************************************
 
-pub fn schnorr_generate_key() -> (SchnorrPk, SchnorrSk) {
-    let mut rng = OsRng;
-    let (pk, sk) = SchnorrSigScheme::keygen(&mut rng);
-    (pk.into_affine(), sk)
+// Overwrites `values` with their default, i.e. zero, value in a way the compiler can't optimize
+// away. Used to clear temporary buffers holding secret data before they're released.
+pub fn wipe<T: Copy + Default>(values: &mut [T]) {
+    for value in values.iter_mut() {
+        unsafe { ptr::write_volatile(value, T::default()) };
+    }
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
 }
 
-pub fn schnorr_get_public_key(sk: &SchnorrSk) -> SchnorrPk {
-    SchnorrSigScheme::get_public_key(sk).into_affine()
-}
+// A secret scalar, i.e. a Schnorr or VRF secret key, which is wiped from memory when dropped.
+// It dereferences to the scalar, so it can be passed as is to the signature and VRF schemes.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretScalar(MNT4Fq);
 
-pub fn schnorr_verify_public_key(pk: &SchnorrPk) -> bool {
-    SchnorrSigScheme::keyverify(&pk.into_projective())
-}
+impl SecretScalar {
+    pub fn new(scalar: MNT4Fq) -> Self {
+        SecretScalar(scalar)
+    }
 
-pub fn schnorr_sign(msg: &FieldElement, sk: &SchnorrSk, pk: &SchnorrPk) -> Result<SchnorrSig, Error> {
-    let mut rng = OsRng;
-    SchnorrSigScheme::sign(&mut rng, &pk.into_projective(), sk, &[*msg])
-}
+    // Moves the scalar out of `scalar`, wiping the original.
+    pub fn take(scalar: &mut MNT4Fq) -> Self {
+        let secret = SecretScalar(*scalar);
+        wipe(std::slice::from_mut(scalar));
+        secret
+    }
 
-pub fn schnorr_verify_signature(msg: &FieldElement, pk: &SchnorrPk, signature: &SchnorrSig) -> Result<bool, Error> {
-    SchnorrSigScheme::verify(&pk.into_projective(), &[*msg], signature)
+    pub fn zeroize(&mut self) {
+        wipe(std::slice::from_mut(&mut self.0));
+    }
 }
 
-//************************************Poseidon Hash function****************************************
+impl Deref for SecretScalar {
+    type Target = MNT4Fq;
 
-pub fn compute_poseidon_hash(input: &[FieldElement]) -> Result<FieldElement, Error> {
-    MNT4PoseidonHash::evaluate(input)
+    fn deref(&self) -> &MNT4Fq {
+        &self.0
+    }
 }
 
-//*****************************Naive threshold sig circuit related functions************************
-
-pub type SCProof = Proof<MNT4>;
+impl Drop for SecretScalar {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
 
-#[derive(Clone, Default)]
-pub struct BackwardTransfer {
-    pub pk_dest:    [u8; 20],
-    pub amount:     u64,
+// Secret keys are never printed, not even in debug output
+impl fmt::Debug for SecretScalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretScalar(<redacted>)")
+    }
 }
 
-impl BackwardTransfer {
-    pub fn new(pk_dest: [u8; 20], amount: u64) -> Self
-    {
-        Self{ pk_dest, amount }
+impl ToBytes for SecretScalar {
+    fn write<W: Write>(&self, writer: W) -> IoResult<()> {
+        self.0.write(writer)
     }
+}
 
-    pub fn to_field_element(&self) -> IoResult<FieldElement>
-    {
-        let mut buffer = vec![];
-        self.pk_dest.write(&mut buffer)?;
-        self.amount.write(&mut buffer)?;
-        read_field_element_from_buffer_with_padding(buffer.as_slice())
+impl FromBytes for SecretScalar {
+    fn read<R: Read>(reader: R) -> IoResult<Self> {
+        let mut scalar = MNT4Fq::read(reader)?;
+        Ok(SecretScalar::take(&mut scalar))
     }
 }
 
-//Will return error if buffer.len > FIELD_SIZE. If buffer.len < FIELD_SIZE, padding 0s will be added
-pub fn read_field_element_from_buffer_with_padding(buffer: &[u8]) -> IoResult<FieldElement>
+// Serializes `sk` into a temporary buffer, which is wiped after `f` is called on it.
+pub fn with_serialized_secret_key<F, R>(sk: &SecretScalar, f: F) -> IoResult<R>
+    where F: FnOnce(&[u8]) -> R
 {
-    let buff_len = buffer.len();
+    let mut sk_bytes = [0u8; SCALAR_FIELD_SIZE];
+    let result = sk.write(&mut sk_bytes[..]).map(|_| f(&sk_bytes[..]));
+    wipe(&mut sk_bytes[..]);
+    result
+}
 
-    //Pad to reach field element size
-    let mut new_buffer = vec![];
-    new_buffer.extend_from_slice(buffer);
-    for _ in buff_len..FIELD_SIZE { new_buffer.push(0u8) } //Add padding zeros to reach field size
+//*******************************Compressed point serialization*************************************
 
-    FieldElement::read(&new_buffer[..])
-}
+// A point is compressed to its x coordinate, serialized as usual, with two flags in the most
+// significant bits of the last byte, which are always unused by the serialization of the
+// coordinate: one for the point at infinity (whose x is then all zeros), one telling if y is the
+// larger of the two roots. Decoding rejects points not on the curve or not in the prime order
+// subgroup, and non canonical encodings, with a DeserializationError.
+pub const COMPRESSED_INFINITY_FLAG: u8 = 1 << 7;
+pub const COMPRESSED_GREATEST_Y_FLAG: u8 = 1 << 6;
 
-pub fn read_field_element_from_u64(num: u64) -> FieldElement {
-    FieldElement::from_repr(BigInteger768::from(num))
+pub trait CompressedPoint: Sized {
+    const COMPRESSED_SIZE: usize;
+
+    fn to_compressed_bytes(&self) -> Result<Vec<u8>, Error>;
+
+    fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, Error>;
 }
 
-// Computes H(H(pks), threshold): used to generate the constant value needed to be declared
-// in MC during SC creation.
-pub fn compute_pks_threshold_hash(pks: &[SchnorrPk], threshold: u64) -> Result<FieldElement, Error> {
-    let threshold_field = read_field_element_from_u64(threshold);
-    let pks_x = pks.iter().map(|pk| pk.x).collect::<Vec<_>>();
-    let pks_hash = compute_poseidon_hash(pks_x.as_slice())?;
-    compute_poseidon_hash(&[pks_hash, threshold_field])
+macro_rules! impl_compressed_point {
+    ($point: ty, $base_field: ty, $size: expr) => {
+        impl CompressedPoint for $point {
+            const COMPRESSED_SIZE: usize = $size;
+
+            fn to_compressed_bytes(&self) -> Result<Vec<u8>, Error> {
+                let mut bytes = vec![0u8; $size];
+                if self.is_zero() {
+                    bytes[$size - 1] |= COMPRESSED_INFINITY_FLAG;
+                } else {
+                    self.x.write(&mut bytes[..])?;
+                    if self.y > -self.y {
+                        bytes[$size - 1] |= COMPRESSED_GREATEST_Y_FLAG;
+                    }
+                }
+                Ok(bytes)
+            }
+
+            fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, Error> {
+                if bytes.len() != $size {
+                    Err(DeserializationError::InvalidSize)?
+                }
+                let flags = bytes[$size - 1] & (COMPRESSED_INFINITY_FLAG | COMPRESSED_GREATEST_Y_FLAG);
+                let mut x_bytes = bytes.to_vec();
+                x_bytes[$size - 1] ^= flags;
+
+                let point = if flags & COMPRESSED_INFINITY_FLAG != 0 {
+                    <$point>::zero()
+                } else {
+                    let x = <$base_field>::read(x_bytes.as_slice())
+                        .map_err(|_| DeserializationError::NonCanonical)?;
+                    <$point>::get_point_from_x(x, flags & COMPRESSED_GREATEST_Y_FLAG != 0)
+                        .ok_or(DeserializationError::NotOnCurve)?
+                };
+                if !point.is_on_curve() {
+                    Err(DeserializationError::NotOnCurve)?
+                }
+                if !point.is_in_correct_subgroup_assuming_on_curve() {
+                    Err(DeserializationError::NotInSubgroup)?
+                }
+
+                // Covers non reduced coordinates, a non zero x or the y flag for the point at
+                // infinity, and the y flag for points with y = 0
+                if point.to_compressed_bytes()?.as_slice() != bytes {
+                    Err(DeserializationError::NonCanonical)?
+                }
+                Ok(point)
+            }
+        }
+    }
 }
 
-//Compute and return (MR(bt_list), H(MR(bt_list), H(bi-1), H(bi))
-pub fn compute_msg_to_sign(
-    end_epoch_mc_b_hash:      &FieldElement,
-    prev_end_epoch_mc_b_hash: &FieldElement,
-    bt_list:                  &[BackwardTransfer],
-) -> Result<(FieldElement, FieldElement), Error> {
+impl_compressed_point!(MNT6G1Affine, MNT4Fr, G1_COMPRESSED_SIZE);
+impl_compressed_point!(MNT4G1Affine, MNT4Fq, G1_COMPRESSED_SIZE);
+impl_compressed_point!(MNT4G2Affine, MNT4Fq2, G2_COMPRESSED_SIZE);
 
-    let mr_bt = if bt_list.is_empty() {
-        MNT4753_PHANTOM_MERKLE_ROOT
-    } else {
-        let mut bt_field_list = vec![];
-        for bt in bt_list.iter() {
-            let bt_f = bt.to_field_element()?;
-            bt_field_list.push(bt_f);
+//*******************************Checked deserialization********************************************
+
+// FromBytes only parses bytes: it doesn't check that a point is on the curve, and may accept more
+// than one encoding of the same value. Values received from outside, e.g. through JNI, must be read
+// with deserialize_from_buffer_checked instead, which also rejects the invalid ones.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeserializationError {
+    // The buffer is not as long as the serialization of the value
+    InvalidSize = 0,
+    // The bytes are not the serialization of any value, or not the canonical one: e.g. a field
+    // element not reduced modulo the field characteristic, or an invalid flag
+    NonCanonical = 1,
+    // The point doesn't satisfy the curve equation
+    NotOnCurve = 2,
+    // The point is on the curve, but not in its prime order subgroup
+    NotInSubgroup = 3,
+    // The point is the point at infinity, or the secret key is zero
+    Zero = 4,
+}
+
+impl fmt::Display for DeserializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializationError::InvalidSize => write!(f, "Invalid size"),
+            DeserializationError::NonCanonical => write!(f, "Non canonical encoding"),
+            DeserializationError::NotOnCurve => write!(f, "Point not on the curve"),
+            DeserializationError::NotInSubgroup => write!(f, "Point not in the prime order subgroup"),
+            DeserializationError::Zero => write!(f, "Zero point or secret key"),
         }
+    }
+}
 
-        //Compute bt_list merkle_root
-        let bt_mt = new_ginger_merkle_tree(bt_field_list.as_slice())?;
-        get_ginger_merkle_root(&bt_mt)
-    };
+impl std::error::Error for DeserializationError {}
 
-    //Compute message to be verified
-    let msg = compute_poseidon_hash(&[mr_bt, *prev_end_epoch_mc_b_hash, *end_epoch_mc_b_hash])?;
+pub trait CheckedDeserialize: FromBytes + ToBytes {
+    const SERIALIZED_SIZE: usize;
 
-    Ok((mr_bt, msg))
+    // Checks performed on the parsed value, once its encoding is known to be canonical
+    fn check(&self) -> Result<(), DeserializationError> {
+        Ok(())
+    }
 }
 
-pub fn compute_wcert_sysdata_hash(
-    valid_sigs:               u64,
-    mr_bt:                    &FieldElement,
-    prev_end_epoch_mc_b_hash: &FieldElement,
-    end_epoch_mc_b_hash:      &FieldElement,
-) -> Result<FieldElement, Error> {
+// Not zero, on the curve and in the prime order subgroup
+fn check_g1_point(point: &MNT6G1Affine) -> Result<(), DeserializationError> {
+    if point.is_zero() {
+        return Err(DeserializationError::Zero)
+    }
+    if !point.is_on_curve() {
+        return Err(DeserializationError::NotOnCurve)
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(DeserializationError::NotInSubgroup)
+    }
+    Ok(())
+}
 
-    //Compute quality and wcert_sysdata_hash
-    let quality = read_field_element_from_u64(valid_sigs);
-    let wcert_sysdata_hash = compute_poseidon_hash(&[quality, *mr_bt, *prev_end_epoch_mc_b_hash, *end_epoch_mc_b_hash])?;
-    Ok(wcert_sysdata_hash)
+impl CheckedDeserialize for FieldElement {
+    const SERIALIZED_SIZE: usize = FIELD_SIZE;
 }
 
-pub fn create_naive_threshold_sig_proof(
-    pks:                      &[SchnorrPk],
-    mut sigs:                 Vec<Option<SchnorrSig>>,
-    end_epoch_mc_b_hash:      &[u8; 32],
-    prev_end_epoch_mc_b_hash: &[u8; 32],
-    bt_list:                  &[BackwardTransfer],
-    threshold:                u64,
-    proving_key_path:         &str
-) -> Result<(SCProof, u64), Error> {
+// Schnorr and VRF public keys
+impl CheckedDeserialize for MNT6G1Affine {
+    const SERIALIZED_SIZE: usize = G1_SIZE;
 
-    //Get max pks
-    let max_pks = pks.len();
-    assert_eq!(sigs.len(), max_pks);
+    fn check(&self) -> Result<(), DeserializationError> {
+        check_g1_point(self)
+    }
+}
 
-    //Read end_epoch_mc_b_hash, prev_end_epoch_mc_b_hash and bt_list as field elements
-    let end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&end_epoch_mc_b_hash[..])?;
-    let prev_end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&prev_end_epoch_mc_b_hash[..])?;
-    let (mr_bt, msg) = compute_msg_to_sign(
-        &end_epoch_mc_b_hash,
-        &prev_end_epoch_mc_b_hash,
-        bt_list,
-    )?;
+// Schnorr and VRF secret keys
+impl CheckedDeserialize for SecretScalar {
+    const SERIALIZED_SIZE: usize = SCALAR_FIELD_SIZE;
 
-    // Iterate over sigs, check and count number of valid signatures,
-    // and replace with NULL_CONST.null_sig the None ones
-    let mut valid_signatures = 0;
-    for i in 0..max_pks {
-        if sigs[i].is_some(){
-            let is_verified = schnorr_verify_signature(&msg, &pks[i], &sigs[i].unwrap())?;
-            if is_verified { valid_signatures += 1; }
-        }
-        else {
-            sigs[i] = Some(NULL_CONST.null_sig)
+    fn check(&self) -> Result<(), DeserializationError> {
+        if self.is_zero() {
+            return Err(DeserializationError::Zero)
         }
+        Ok(())
     }
+}
 
-    //Compute b as v-t and convert it to field element
-    let b = read_field_element_from_u64(valid_signatures - threshold);
+impl CheckedDeserialize for SchnorrSig {
+    const SERIALIZED_SIZE: usize = SCHNORR_SIG_SIZE;
+}
 
-    //Convert affine pks to projective
-    let pks = pks.iter().map(|&pk| pk.into_projective()).collect::<Vec<_>>();
+// MuSig partial signatures
+impl CheckedDeserialize for MNT4Fq {
+    const SERIALIZED_SIZE: usize = MUSIG_PARTIAL_SIG_SIZE;
+}
 
-    //Convert needed variables into field elements
-    let threshold = read_field_element_from_u64(threshold);
+impl CheckedDeserialize for SchnorrMultiSig {
+    const SERIALIZED_SIZE: usize = SCHNORR_MULTISIG_SIZE;
 
-    let c = NaiveTresholdSignature::<FieldElement>::new(
-        pks, sigs, threshold, b, end_epoch_mc_b_hash,
-        prev_end_epoch_mc_b_hash, mr_bt, max_pks,
-    );
+    fn check(&self) -> Result<(), DeserializationError> {
+        check_g1_point(&self.r)
+    }
+}
 
-    //Read proving key
-    let params = read_from_file(proving_key_path)?;
+impl CheckedDeserialize for VRFProof {
+    const SERIALIZED_SIZE: usize = VRF_PROOF_SIZE;
 
-    //Create and return proof
-    let mut rng = OsRng;
-    let proof = create_random_proof(c, &params, &mut rng)?;
-    Ok((proof, valid_signatures))
+    fn check(&self) -> Result<(), DeserializationError> {
+        check_g1_point(&self.gamma.into_affine())
+    }
 }
 
-pub fn verify_naive_threshold_sig_proof(
-    constant:                 &FieldElement,
-    end_epoch_mc_b_hash:      &[u8; 32],
-    prev_end_epoch_mc_b_hash: &[u8; 32],
-    bt_list:                  &[BackwardTransfer],
-    valid_sigs:               u64,
-    proof:                    &SCProof,
-    vk_path:                  &str,
-) -> Result<bool, Error>
-{
-    //Compute wcert_sysdata_hash
-    let end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&end_epoch_mc_b_hash[..])?;
-    let prev_end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&prev_end_epoch_mc_b_hash[..])?;
-    let (mr_bt, _) = compute_msg_to_sign(&end_epoch_mc_b_hash, &prev_end_epoch_mc_b_hash, bt_list)?;
-    let wcert_sysdata_hash = compute_wcert_sysdata_hash(valid_sigs, &mr_bt, &prev_end_epoch_mc_b_hash, &end_epoch_mc_b_hash)?;
-    let aggregated_input = compute_poseidon_hash(&[*constant, wcert_sysdata_hash])?;
+pub fn deserialize_from_buffer_checked<T: CheckedDeserialize>(buffer: &[u8]) -> Result<T, DeserializationError> {
+    if buffer.len() != T::SERIALIZED_SIZE {
+        return Err(DeserializationError::InvalidSize)
+    }
+    let value = T::read(buffer).map_err(|_| DeserializationError::NonCanonical)?;
 
-    //Verify proof
-    let vk = read_from_file(vk_path)?;
-    let pvk = prepare_verifying_key(&vk); //Get verifying key
-    let is_verified = verify_proof(&pvk, &proof, &[aggregated_input])?;
+    // The encoding is canonical if it's the one the value serializes to. The buffer may hold a
+    // secret key, so it's wiped.
+    let mut reserialized = vec![0u8; T::SERIALIZED_SIZE];
+    let is_canonical = value.write(reserialized.as_mut_slice()).is_ok() && reserialized.as_slice() == buffer;
+    wipe(reserialized.as_mut_slice());
+    if !is_canonical {
+        return Err(DeserializationError::NonCanonical)
+    }
 
-    Ok(is_verified)
+    value.check()?;
+    Ok(value)
 }
 
-//VRF types and functions
+// The encoding is canonical if it's the one the value serializes to: e.g. all its field elements are
+// reduced modulo the field characteristic, and its flags are valid.
+fn check_canonical_serialization(buffer: &[u8], reserialized: IoResult<Vec<u8>>) -> Result<(), DeserializationError> {
+    match reserialized {
+        Ok(ref bytes) if bytes.as_slice() == buffer => Ok(()),
+        _ => Err(DeserializationError::NonCanonical),
+    }
+}
 
-lazy_static! {
-    pub static ref VRF_GH_PARAMS: BoweHopwoodPedersenParameters<MNT6G1Projective> = {
-        let params = VRFParams::new();
-        BoweHopwoodPedersenParameters::<MNT6G1Projective>{generators: params.group_hash_generators}
-    };
+// Like deserialize_from_buffer_checked, for values of variable size, e.g. Merkle paths and proofs,
+// whose consistency is checked when reading them: the buffer must be read entirely.
+pub fn deserialize_variable_size_from_buffer_checked<T: FromBytes + ToBytes>(buffer: &[u8]) -> Result<T, DeserializationError> {
+    let mut reader = buffer;
+    let value = T::read(&mut reader).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => DeserializationError::InvalidSize,
+        _ => DeserializationError::NonCanonical,
+    })?;
+    if !reader.is_empty() {
+        return Err(DeserializationError::InvalidSize)
+    }
+
+    let mut reserialized = vec![];
+    check_canonical_serialization(buffer, value.write(&mut reserialized).map(|_| reserialized))?;
+    Ok(value)
 }
 
-type GroupHash = BoweHopwoodPedersenCRH<MNT6G1Projective, VRFWindow>;
+//***************************Schnorr types and functions********************************************
 
-pub type VRFScheme = FieldBasedEcVrf<MNT4Fr, MNT6G1Projective, MNT4PoseidonHash, GroupHash>;
-pub type VRFProof = FieldBasedEcVrfProof<MNT4Fr, MNT6G1Projective>;
-pub type VRFPk = MNT6G1Affine;
-pub type VRFSk = MNT4Fq;
+pub type SchnorrSigScheme = FieldBasedSchnorrSignatureScheme<MNT4Fr, MNT6G1Projective, MNT4PoseidonHash>;
+pub type SchnorrSig = FieldBasedSchnorrSignature<MNT4Fr>;
+pub type SchnorrPk = MNT6G1Affine;
+pub type SchnorrSk = SecretScalar;
 
-pub fn vrf_generate_key() -> (VRFPk, VRFSk) {
+pub fn schnorr_generate_key() -> (SchnorrPk, SchnorrSk) {
     let mut rng = OsRng;
-    let (pk, sk) = VRFScheme::keygen(&mut rng);
-    (pk.into_affine(), sk)
+    let (pk, mut sk) = SchnorrSigScheme::keygen(&mut rng);
+    (pk.into_affine(), SecretScalar::take(&mut sk))
 }
 
-pub fn vrf_get_public_key(sk: &VRFSk) -> VRFPk {
+pub fn schnorr_get_public_key(sk: &SchnorrSk) -> SchnorrPk {
     SchnorrSigScheme::get_public_key(sk).into_affine()
 }
 
-pub fn vrf_verify_public_key(pk: &VRFPk) -> bool {
+pub fn schnorr_verify_public_key(pk: &SchnorrPk) -> bool {
     SchnorrSigScheme::keyverify(&pk.into_projective())
 }
 
-pub fn vrf_prove(msg: &FieldElement, sk: &VRFSk, pk: &VRFPk) -> Result<(VRFProof, FieldElement), Error> {
-    let mut rng = OsRng;
+pub fn serialize_schnorr_pk_compressed(pk: &SchnorrPk) -> Result<Vec<u8>, Error> {
+    pk.to_compressed_bytes()
+}
 
-    //Compute proof
-    let proof = VRFScheme::prove(
-        &mut rng,
-        &VRF_GH_PARAMS,
-        &pk.into_projective(),
-        sk,
-        &[*msg]
-    )?;
+pub fn deserialize_schnorr_pk_compressed(bytes: &[u8]) -> Result<SchnorrPk, Error> {
+    let pk = SchnorrPk::from_compressed_bytes(bytes)?;
+    check_g1_point(&pk)?;
+    Ok(pk)
+}
 
-    //Convert gamma from proof to field elements
-    let gamma_coords = proof.gamma.to_field_elements().unwrap();
+pub fn schnorr_sign(msg: &FieldElement, sk: &SchnorrSk, pk: &SchnorrPk) -> Result<SchnorrSig, Error> {
+    schnorr_sign_field_elements(&[*msg], sk, pk)
+}
 
-    //Compute VRF output
-    let mut hash_input = Vec::new();
-    hash_input.push(*msg);
-    hash_input.extend_from_slice(gamma_coords.as_slice());
-    let output = compute_poseidon_hash(hash_input.as_ref())?;
+// Messages starting with the personalization of a dedicated signing domain, i.e. proofs of possession
+// and byte messages, are reserved: otherwise a signature on such a message would also be a valid
+// signature in that domain. They can't be signed, and their signatures are never valid.
+fn get_reserved_schnorr_message_domain(msg: &[FieldElement]) -> Option<HashDomain> {
+    let first = msg.first()?;
+    [HashDomain::ProofOfPossession, HashDomain::SchnorrBytesMessage].iter()
+        .find(|&&domain| get_personalization(domain, DomainSeparationVersion::V1).as_ref() == Some(first))
+        .copied()
+}
 
-    Ok((proof, output))
+fn check_schnorr_field_elements_message(msg: &[FieldElement]) -> Result<(), Error> {
+    if msg.is_empty() {
+        Err("Message must not be empty")?
+    }
+    if let Some(domain) = get_reserved_schnorr_message_domain(msg) {
+        Err(format!("Message must not start with the personalization of {:?}", domain))?
+    }
+    Ok(())
 }
 
-pub fn vrf_proof_to_hash(msg: &FieldElement, pk: &VRFPk, proof: &VRFProof) -> Result<FieldElement, Error> {
-    VRFScheme::proof_to_hash(&VRF_GH_PARAMS,&pk.into_projective(), &[*msg], proof)
+fn schnorr_sign_unchecked(msg: &[FieldElement], sk: &SchnorrSk, pk: &SchnorrPk) -> Result<SchnorrSig, Error> {
+    let mut rng = OsRng;
+    SchnorrSigScheme::sign(&mut rng, &pk.into_projective(), sk, msg)
 }
 
-//************Merkle Tree functions******************
+// Signs a message made of multiple field elements, without pre-hashing it: the Schnorr challenge is
+// computed on the whole message. The message must not be empty, nor start with a reserved personalization.
+pub fn schnorr_sign_field_elements(msg: &[FieldElement], sk: &SchnorrSk, pk: &SchnorrPk) -> Result<SchnorrSig, Error> {
+    check_schnorr_field_elements_message(msg)?;
+    schnorr_sign_unchecked(msg, sk, pk)
+}
 
-pub struct FieldBasedMerkleTreeParams;
+// Canonical encoding of a byte message as field elements: (personalization, len, packed bytes), the
+// last two as in compute_poseidon_hash_bytes. It's never empty, different byte strings never share an
+// encoding, and the personalization keeps it apart from field element messages.
+// Byte signatures must stay verifiable once created: the version of the personalization is fixed.
+pub fn schnorr_encode_bytes_message(msg: &[u8]) -> Result<Vec<FieldElement>, Error> {
+    let personalization = get_personalization(HashDomain::SchnorrBytesMessage, DomainSeparationVersion::V1)
+        .ok_or("Missing bytes message personalization")?;
+    let mut encoded = vec![personalization, read_field_element_from_u64(msg.len() as u64)];
+    encoded.extend(bytes_to_field_elements(msg)?);
+    Ok(encoded)
+}
 
-impl FieldBasedMerkleTreeConfig for FieldBasedMerkleTreeParams {
-    const HEIGHT: usize = 13;
-    type H = MNT4PoseidonHash;
+pub fn schnorr_sign_bytes(msg: &[u8], sk: &SchnorrSk, pk: &SchnorrPk) -> Result<SchnorrSig, Error> {
+    schnorr_sign_unchecked(schnorr_encode_bytes_message(msg)?.as_slice(), sk, pk)
 }
 
-type GingerMerkleTree = FieldBasedMerkleHashTree<FieldBasedMerkleTreeParams>;
+// Seeds a ChaCha rng with the first 32 bytes of `seed`, i.e. the output of a hash, in order to
+// deterministically sample scalars from it.
+fn field_element_to_rng(seed: &FieldElement) -> Result<ChaChaRng, Error> {
+    let mut seed_bytes = [0u8; FIELD_SIZE];
+    let written = seed.write(&mut seed_bytes[..]);
 
-#[allow(dead_code)]
-type GingerMerkleTreePath = FieldBasedMerkleTreePath<FieldBasedMerkleTreeParams>;
+    let mut chacha_seed = [0u8; 32];
+    chacha_seed.copy_from_slice(&seed_bytes[..32]);
+    wipe(&mut seed_bytes[..]);
+    let rng = ChaChaRng::from_seed(chacha_seed);
+    wipe(&mut chacha_seed[..]);
 
-pub fn new_ginger_merkle_tree(leaves: &[FieldElement]) -> Result<GingerMerkleTree, Error> {
-    GingerMerkleTree::new(leaves)
+    written?;
+    Ok(rng)
 }
 
-pub fn get_ginger_merkle_root(tree: &GingerMerkleTree) -> FieldElement {
-    tree.root()
+// Derives the seed of the nonce of a deterministic signature, in the spirit of RFC 6979 but using
+// Poseidon: seed = H(sk, pk.x, pk.y, len(msg), msg[, H(extra_randomness)]) in the SchnorrNonce domain.
+// Mixing in extra randomness, when available, hedges against fault attacks without relying on it.
+// Deterministic signatures must be reproducible: the version of the personalization is fixed.
+fn schnorr_derive_nonce_seed(
+    msg: &[FieldElement],
+    sk: &SchnorrSk,
+    pk: &SchnorrPk,
+    extra_randomness: Option<&[u8]>,
+) -> Result<FieldElement, Error>
+{
+    let mut input = with_serialized_secret_key(sk, bytes_to_field_elements)??;
+    input.push(pk.x);
+    input.push(pk.y);
+    input.push(read_field_element_from_u64(msg.len() as u64));
+    input.extend_from_slice(msg);
+    if let Some(extra_randomness) = extra_randomness {
+        input.push(compute_poseidon_hash_bytes(extra_randomness)?);
+    }
+    let seed = domain_separated_hash(HashDomain::SchnorrNonce, DomainSeparationVersion::V1, input.as_slice());
+    wipe(input.as_mut_slice());
+    seed
 }
 
-#[allow(dead_code)]
-pub fn get_ginger_merkle_path(leaf: &FieldElement, leaf_index: usize, tree: &GingerMerkleTree)
-    -> Result<GingerMerkleTreePath, Error>
+// Signs `msg` with a nonce derived from `sk`, `pk` and `msg` (and `extra_randomness`, if any), instead of
+// sampling it from the OS rng: without extra randomness, the same message always gets the same signature.
+pub fn schnorr_sign_deterministic(
+    msg: &FieldElement,
+    sk: &SchnorrSk,
+    pk: &SchnorrPk,
+    extra_randomness: Option<&[u8]>,
+) -> Result<SchnorrSig, Error>
 {
-    tree.generate_proof(leaf_index, leaf)
+    schnorr_sign_field_elements_deterministic(&[*msg], sk, pk, extra_randomness)
 }
 
-#[allow(dead_code)]
-pub fn verify_ginger_merkle_path(path: GingerMerkleTreePath, merkle_root: &FieldElement, leaf: &FieldElement)
-    -> Result<bool, Error>
+pub fn schnorr_sign_field_elements_deterministic(
+    msg: &[FieldElement],
+    sk: &SchnorrSk,
+    pk: &SchnorrPk,
+    extra_randomness: Option<&[u8]>,
+) -> Result<SchnorrSig, Error>
 {
-    path.verify(merkle_root, leaf)
+    check_schnorr_field_elements_message(msg)?;
+    schnorr_sign_deterministic_unchecked(msg, sk, pk, extra_randomness)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use rand::RngCore;
+fn schnorr_sign_deterministic_unchecked(
+    msg: &[FieldElement],
+    sk: &SchnorrSk,
+    pk: &SchnorrPk,
+    extra_randomness: Option<&[u8]>,
+) -> Result<SchnorrSig, Error>
+{
+    let seed = schnorr_derive_nonce_seed(msg, sk, pk, extra_randomness)?;
+    let mut rng = field_element_to_rng(&seed)?;
+    SchnorrSigScheme::sign(&mut rng, &pk.into_projective(), sk, msg)
+}
 
-    fn write_to_file<T: ToBytes>(to_write: &T, file_path: &str) -> IoResult<()>{
-        let mut fs = File::create(file_path)?;
-        to_write.write(&mut fs)?;
+pub fn schnorr_sign_bytes_deterministic(
+    msg: &[u8],
+    sk: &SchnorrSk,
+    pk: &SchnorrPk,
+    extra_randomness: Option<&[u8]>,
+) -> Result<SchnorrSig, Error>
+{
+    schnorr_sign_deterministic_unchecked(schnorr_encode_bytes_message(msg)?.as_slice(), sk, pk, extra_randomness)
+}
+
+// A proof of possession (PoP) is a signature on the public key itself, in a dedicated domain,
+// proving that whoever registers a public key also holds its secret key. Checking the PoPs of a
+// signer set prevents rogue keys, e.g. keys computed from other signers' keys, or copied keys.
+pub type SchnorrPoP = SchnorrSig;
+
+// PoPs must stay verifiable once created: the version of their personalization is fixed.
+fn schnorr_pop_message(pk: &SchnorrPk) -> Result<[FieldElement; 3], Error> {
+    let personalization = get_personalization(HashDomain::ProofOfPossession, DomainSeparationVersion::V1)
+        .ok_or("Missing proof of possession personalization")?;
+    Ok([personalization, pk.x, pk.y])
+}
+
+pub fn schnorr_create_pop(sk: &SchnorrSk, pk: &SchnorrPk) -> Result<SchnorrPoP, Error> {
+    schnorr_sign_unchecked(&schnorr_pop_message(pk)?, sk, pk)
+}
+
+// Returns false also if pk is not a valid public key.
+pub fn schnorr_verify_pop(pk: &SchnorrPk, pop: &SchnorrPoP) -> Result<bool, Error> {
+    if !schnorr_verify_public_key(pk) {
+        return Ok(false)
+    }
+    SchnorrSigScheme::verify(&pk.into_projective(), &schnorr_pop_message(pk)?, pop)
+}
+
+// Signatures are verified one by one, in parallel: this is not a randomized batch verification, so the
+// cost is the one of verifying each item. (pk, msg, signature) items are independent and messages may differ.
+// Returns the verdict of each item, in the same order, or an error if any of the verifications fails.
+pub fn schnorr_verify_signatures_per_item(items: &[(SchnorrPk, FieldElement, SchnorrSig)]) -> Result<Vec<bool>, Error> {
+    let verdicts = items.par_iter()
+        .map(|(pk, msg, signature)| schnorr_verify_signature(msg, pk, signature).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(verdicts)
+}
+
+// Like schnorr_verify_signatures_per_item, but returns true iff all the signatures are valid, stopping
+// as soon as an invalid one is found.
+pub fn schnorr_verify_signatures_all(items: &[(SchnorrPk, FieldElement, SchnorrSig)]) -> Result<bool, Error> {
+    let failure = items.par_iter()
+        .map(|(pk, msg, signature)| schnorr_verify_signature(msg, pk, signature).map_err(|e| e.to_string()))
+        .find_any(|verdict| verdict != &Ok(true));
+    match failure {
+        None => Ok(true),
+        Some(Ok(_)) => Ok(false),
+        Some(Err(e)) => Err(e)?,
+    }
+}
+
+pub fn schnorr_verify_signature(msg: &FieldElement, pk: &SchnorrPk, signature: &SchnorrSig) -> Result<bool, Error> {
+    schnorr_verify_signature_field_elements(&[*msg], pk, signature)
+}
+
+// Returns false if the message starts with a reserved personalization.
+pub fn schnorr_verify_signature_field_elements(msg: &[FieldElement], pk: &SchnorrPk, signature: &SchnorrSig) -> Result<bool, Error> {
+    if msg.is_empty() {
+        Err("Signed message must not be empty")?
+    }
+    if get_reserved_schnorr_message_domain(msg).is_some() {
+        return Ok(false)
+    }
+    SchnorrSigScheme::verify(&pk.into_projective(), msg, signature)
+}
+
+pub fn schnorr_verify_signature_bytes(msg: &[u8], pk: &SchnorrPk, signature: &SchnorrSig) -> Result<bool, Error> {
+    SchnorrSigScheme::verify(&pk.into_projective(), schnorr_encode_bytes_message(msg)?.as_slice(), signature)
+}
+
+//************************************Schnorr multi-signatures**************************************
+
+// MuSig-style multi-signatures on Schnorr keys: n signers jointly produce a single signature,
+// verifiable against the aggregation of their public keys.
+// - Key aggregation: apk = sum(a_i * pk_i), with a_i = Sample(H(L, pk_i)) and L = H(n, pk_1, ..., pk_n).
+//   The coefficients prevent a signer from choosing its key as a function of the other ones (rogue keys).
+// - Round 1, nonce commitment: each signer samples a fresh nonce r_i and broadcasts t_i = H(R_i), R_i = r_i * G.
+// - Round 2, nonce exchange: once all the commitments have been received, each signer broadcasts R_i.
+//   The nonces are checked against their commitments and summed up: R = sum(R_i).
+// - Partial signatures: each signer broadcasts s_i = r_i + e * a_i * sk_i, with e = Sample(H(R, apk, msg)).
+//   The multi-signature is (R, sum(s_i)), and it's valid iff s * G = R + e * apk.
+// Sample() is the hash-to-scalar of the HD key derivation. All the signers must list the signer set in
+// the same order, and a nonce must never be used for more than one partial signature.
+
+pub const MUSIG_PARTIAL_SIG_SIZE: usize = SCALAR_FIELD_SIZE; // 96
+pub const SCHNORR_MULTISIG_SIZE: usize = G1_SIZE + SCALAR_FIELD_SIZE; // 289
+
+pub type MuSigPublicNonce = SchnorrPk;
+pub type MuSigNonceCommitment = FieldElement;
+pub type MuSigPartialSig = MNT4Fq;
+
+// The nonce of a signer, i.e. a fresh key pair. musig_partial_sign consumes it, so that it can't be reused.
+pub struct MuSigSecretNonce {
+    nonce: SecretScalar,
+    public_nonce: MuSigPublicNonce,
+}
+
+impl MuSigSecretNonce {
+    pub fn public_nonce(&self) -> &MuSigPublicNonce {
+        &self.public_nonce
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchnorrMultiSig {
+    pub r: MuSigPublicNonce,
+    pub s: MNT4Fq,
+}
+
+impl ToBytes for SchnorrMultiSig {
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.r.write(&mut writer)?;
+        self.s.write(&mut writer)
+    }
+}
+
+impl FromBytes for SchnorrMultiSig {
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let r = MuSigPublicNonce::read(&mut reader)?;
+        let s = MNT4Fq::read(&mut reader)?;
+        Ok(SchnorrMultiSig { r, s })
+    }
+}
+
+// Aggregated keys, commitments and signatures must stay the same once created: the version of the
+// personalizations is fixed.
+const MUSIG_DOMAIN_SEPARATION_VERSION: DomainSeparationVersion = DomainSeparationVersion::V1;
+
+fn musig_hash_to_scalar(domain: HashDomain, input: &[FieldElement]) -> Result<MNT4Fq, Error> {
+    let seed = domain_separated_hash(domain, MUSIG_DOMAIN_SEPARATION_VERSION, input)?;
+    Ok(MNT4Fq::rand(&mut field_element_to_rng(&seed)?))
+}
+
+// Returns the coefficient of each signer, in order, and the aggregated public key.
+fn musig_key_aggregation(pks: &[SchnorrPk]) -> Result<(Vec<MNT4Fq>, SchnorrPk), Error> {
+    if pks.is_empty() {
+        Err("Signer set must not be empty")?
+    }
+    if let Some(i) = pks.iter().position(|pk| !schnorr_verify_public_key(pk)) {
+        Err(format!("Invalid public key of signer {}", i))?
+    }
+
+    let mut key_list = vec![read_field_element_from_u64(pks.len() as u64)];
+    for pk in pks.iter() {
+        key_list.push(pk.x);
+        key_list.push(pk.y);
+    }
+    let key_list_hash = domain_separated_hash(HashDomain::MuSigKeyList, MUSIG_DOMAIN_SEPARATION_VERSION, key_list.as_slice())?;
+
+    let coefficients = pks.iter()
+        .map(|pk| musig_hash_to_scalar(HashDomain::MuSigKeyCoefficient, &[key_list_hash, pk.x, pk.y]))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let aggregated_pk = pks.iter().zip(coefficients.iter())
+        .fold(MNT6G1Projective::zero(), |apk, (pk, coefficient)| apk + &pk.mul(*coefficient))
+        .into_affine();
+    if aggregated_pk.is_zero() {
+        Err("Aggregated public key is zero")?
+    }
+    Ok((coefficients, aggregated_pk))
+}
+
+fn musig_challenge(aggregated_nonce: &MuSigPublicNonce, aggregated_pk: &SchnorrPk, msg: &[FieldElement]) -> Result<MNT4Fq, Error> {
+    if msg.is_empty() {
+        Err("Message to sign must not be empty")?
+    }
+    let mut input = vec![aggregated_nonce.x, aggregated_nonce.y, aggregated_pk.x, aggregated_pk.y];
+    input.extend_from_slice(msg);
+    musig_hash_to_scalar(HashDomain::MuSigChallenge, input.as_slice())
+}
+
+// The key multi-signatures of `pks` are verified against. The order of `pks` matters.
+pub fn musig_aggregate_public_keys(pks: &[SchnorrPk]) -> Result<SchnorrPk, Error> {
+    Ok(musig_key_aggregation(pks)?.1)
+}
+
+// Round 1: the secret nonce is kept, its commitment is sent to the other signers.
+pub fn musig_generate_nonce() -> MuSigSecretNonce {
+    let (public_nonce, nonce) = schnorr_generate_key();
+    MuSigSecretNonce { nonce, public_nonce }
+}
+
+pub fn musig_nonce_commitment(public_nonce: &MuSigPublicNonce) -> Result<MuSigNonceCommitment, Error> {
+    domain_separated_hash(HashDomain::MuSigNonceCommitment, MUSIG_DOMAIN_SEPARATION_VERSION, &[public_nonce.x, public_nonce.y])
+}
+
+// Round 2: checks the public nonce of each signer against the commitment it sent in round 1, and
+// returns the aggregated nonce. Fails if any of the nonces doesn't match its commitment.
+pub fn musig_aggregate_nonces(
+    public_nonces: &[MuSigPublicNonce],
+    commitments: &[MuSigNonceCommitment],
+) -> Result<MuSigPublicNonce, Error>
+{
+    if public_nonces.is_empty() {
+        Err("No nonces to aggregate")?
+    }
+    if public_nonces.len() != commitments.len() {
+        Err(format!("Expected a commitment for each one of the {} nonces, found {}", public_nonces.len(), commitments.len()))?
+    }
+    for (i, (public_nonce, commitment)) in public_nonces.iter().zip(commitments.iter()).enumerate() {
+        if !schnorr_verify_public_key(public_nonce) || &musig_nonce_commitment(public_nonce)? != commitment {
+            Err(format!("Nonce of signer {} doesn't match its commitment", i))?
+        }
+    }
+
+    let aggregated_nonce = public_nonces.iter()
+        .fold(MNT6G1Projective::zero(), |r, public_nonce| r + &public_nonce.into_projective())
+        .into_affine();
+    if aggregated_nonce.is_zero() {
+        Err("Aggregated nonce is zero")?
+    }
+    Ok(aggregated_nonce)
+}
+
+// Partial signature of the signer at `signer_index` in `pks`, whose secret key is `sk`. Consumes
+// `secret_nonce`, which is wiped.
+pub fn musig_partial_sign(
+    secret_nonce: MuSigSecretNonce,
+    sk: &SchnorrSk,
+    pks: &[SchnorrPk],
+    signer_index: usize,
+    aggregated_nonce: &MuSigPublicNonce,
+    msg: &[FieldElement],
+) -> Result<MuSigPartialSig, Error>
+{
+    if signer_index >= pks.len() || pks[signer_index] != schnorr_get_public_key(sk) {
+        Err(format!("The secret key doesn't match the public key of signer {}", signer_index))?
+    }
+    let (coefficients, aggregated_pk) = musig_key_aggregation(pks)?;
+    let challenge = musig_challenge(aggregated_nonce, &aggregated_pk, msg)?;
+
+    let mut weighted_sk = coefficients[signer_index] * &**sk;
+    weighted_sk *= &challenge;
+    let partial_sig = *secret_nonce.nonce + &weighted_sk;
+    wipe(std::slice::from_mut(&mut weighted_sk));
+    Ok(partial_sig)
+}
+
+// Checks s_i * G = R_i + e * a_i * pk_i, in order to find out which signer sent an invalid partial signature.
+pub fn musig_verify_partial_signature(
+    partial_sig: &MuSigPartialSig,
+    public_nonce: &MuSigPublicNonce,
+    pks: &[SchnorrPk],
+    signer_index: usize,
+    aggregated_nonce: &MuSigPublicNonce,
+    msg: &[FieldElement],
+) -> Result<bool, Error>
+{
+    if signer_index >= pks.len() {
+        Err(format!("Signer index {} out of range, the signer set has {} signers", signer_index, pks.len()))?
+    }
+    let (coefficients, aggregated_pk) = musig_key_aggregation(pks)?;
+    let challenge = musig_challenge(aggregated_nonce, &aggregated_pk, msg)?;
+
+    let expected = public_nonce.into_projective() + &pks[signer_index].mul(challenge * &coefficients[signer_index]);
+    Ok(SchnorrSigScheme::get_public_key(partial_sig) == expected)
+}
+
+pub fn musig_combine_partial_signatures(
+    aggregated_nonce: &MuSigPublicNonce,
+    partial_sigs: &[MuSigPartialSig],
+) -> Result<SchnorrMultiSig, Error>
+{
+    if partial_sigs.is_empty() {
+        Err("No partial signatures to combine")?
+    }
+    let s = partial_sigs.iter().fold(MNT4Fq::zero(), |s, partial_sig| s + partial_sig);
+    Ok(SchnorrMultiSig { r: *aggregated_nonce, s })
+}
+
+pub fn musig_verify_signature(msg: &[FieldElement], aggregated_pk: &SchnorrPk, signature: &SchnorrMultiSig) -> Result<bool, Error> {
+    if !schnorr_verify_public_key(aggregated_pk) || !schnorr_verify_public_key(&signature.r) {
+        return Ok(false)
+    }
+    let challenge = musig_challenge(&signature.r, aggregated_pk, msg)?;
+
+    let expected = signature.r.into_projective() + &aggregated_pk.mul(challenge);
+    Ok(SchnorrSigScheme::get_public_key(&signature.s) == expected)
+}
+
+//************************************Poseidon Hash function****************************************
+
+pub fn compute_poseidon_hash(input: &[FieldElement]) -> Result<FieldElement, Error> {
+    MNT4PoseidonHash::evaluate(input)
+}
+
+// Computes the Poseidon hash of each one of the independent inputs, in parallel.
+// The i-th output is the hash of the i-th input; fails if any of the hashes fails.
+pub fn compute_poseidon_hash_batch(inputs: &[Vec<FieldElement>]) -> Result<Vec<FieldElement>, Error> {
+    let hashes = inputs.par_iter()
+        .map(|input| compute_poseidon_hash(input.as_slice()).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(hashes)
+}
+
+// Buffering Poseidon hasher, for inputs built piece by piece. It's not a sponge: the pinned ginger-lib
+// exposes Poseidon only through the one-shot FieldBasedHash::evaluate, neither its permutation nor its
+// state, so update() only appends to the input, whose memory grows with it, and each finalize() hashes
+// the whole input from scratch. The digest is always the same of compute_poseidon_hash() applied to the
+// concatenation of all the inputs received since the creation or the last reset().
+#[derive(Clone, Debug, Default)]
+pub struct BufferedPoseidonHasher {
+    input: Vec<FieldElement>,
+}
+
+impl BufferedPoseidonHasher {
+    pub fn new() -> Self {
+        Self { input: vec![] }
+    }
+
+    pub fn update(&mut self, input: &FieldElement) -> &mut Self {
+        self.input.push(*input);
+        self
+    }
+
+    // Doesn't modify the state: further updates will be appended to the current input
+    pub fn finalize(&self) -> Result<FieldElement, Error> {
+        compute_poseidon_hash(self.input.as_slice())
+    }
+
+    pub fn reset(&mut self) -> &mut Self {
+        self.input.clear();
+        self
+    }
+}
+
+//************************************Bytes hashing functions***************************************
+
+// Number of bytes packed into each field element: any integer of this size is strictly smaller
+// than the modulus, so the packing is injective and never reduces.
+pub const FIELD_ELEMENT_PACKING_SIZE: usize = (<<FieldElement as PrimeField>::Params as FpParameters>::CAPACITY / 8) as usize; // 94
+
+// Like read_field_element_from_buffer_with_padding, but also returns an error, instead of silently
+// reducing, if the little endian integer represented by buffer is not smaller than the modulus.
+pub fn read_field_element_from_buffer_checked(buffer: &[u8]) -> IoResult<FieldElement>
+{
+    if buffer.len() > FIELD_SIZE {
+        return Err(IoError::new(ErrorKind::InvalidData, "buffer bigger than a field element"))
+    }
+
+    let mut new_buffer = buffer.to_vec();
+    new_buffer.resize(FIELD_SIZE, 0u8);
+
+    let repr = BigInteger768::read(new_buffer.as_slice())?;
+    if repr >= <FieldElement as PrimeField>::Params::MODULUS {
+        return Err(IoError::new(ErrorKind::InvalidData, "buffer value exceeds the field modulus"))
+    }
+    Ok(FieldElement::from_repr(repr))
+}
+
+// Canonical packing of bytes into field elements: bytes are split in chunks of
+// FIELD_ELEMENT_PACKING_SIZE bytes (the last one possibly shorter), each of them read as a little
+// endian integer. The packing of an empty buffer is empty.
+pub fn bytes_to_field_elements(bytes: &[u8]) -> Result<Vec<FieldElement>, Error>
+{
+    let mut fes = Vec::with_capacity((bytes.len() + FIELD_ELEMENT_PACKING_SIZE - 1) / FIELD_ELEMENT_PACKING_SIZE);
+    for chunk in bytes.chunks(FIELD_ELEMENT_PACKING_SIZE) {
+        fes.push(read_field_element_from_buffer_checked(chunk)?);
+    }
+    Ok(fes)
+}
+
+// Computes H(len, packed bytes): prepending the length in bytes makes the hash injective also with
+// respect to trailing zero bytes, which the packing alone would lose.
+pub fn compute_poseidon_hash_bytes(bytes: &[u8]) -> Result<FieldElement, Error>
+{
+    let mut hasher = BufferedPoseidonHasher::new();
+    hasher.update(&read_field_element_from_u64(bytes.len() as u64));
+    for fe in bytes_to_field_elements(bytes)?.iter() {
+        hasher.update(fe);
+    }
+    hasher.finalize()
+}
+
+//*****************************Naive threshold sig circuit related functions************************
+
+pub type SCProof = Proof<MNT4>;
+
+// a (compressed) || b (compressed) || c (compressed)
+pub fn serialize_sc_proof_compressed(proof: &SCProof) -> Result<Vec<u8>, Error> {
+    let mut bytes = proof.a.to_compressed_bytes()?;
+    bytes.extend(proof.b.to_compressed_bytes()?);
+    bytes.extend(proof.c.to_compressed_bytes()?);
+    Ok(bytes)
+}
+
+pub fn deserialize_sc_proof_compressed(bytes: &[u8]) -> Result<SCProof, Error> {
+    if bytes.len() != ZK_PROOF_COMPRESSED_SIZE {
+        Err(format!("Invalid compressed proof size: expected {}, found {}", ZK_PROOF_COMPRESSED_SIZE, bytes.len()))?
+    }
+    let a = MNT4G1Affine::from_compressed_bytes(&bytes[..G1_COMPRESSED_SIZE])?;
+    let b = MNT4G2Affine::from_compressed_bytes(&bytes[G1_COMPRESSED_SIZE..G1_COMPRESSED_SIZE + G2_COMPRESSED_SIZE])?;
+    let c = MNT4G1Affine::from_compressed_bytes(&bytes[G1_COMPRESSED_SIZE + G2_COMPRESSED_SIZE..])?;
+    Ok(SCProof { a, b, c })
+}
+
+#[derive(Clone, Default)]
+pub struct BackwardTransfer {
+    pub pk_dest:    [u8; 20],
+    pub amount:     u64,
+}
+
+impl BackwardTransfer {
+    pub fn new(pk_dest: [u8; 20], amount: u64) -> Self
+    {
+        Self{ pk_dest, amount }
+    }
+
+    pub fn to_field_element(&self) -> IoResult<FieldElement>
+    {
+        let mut buffer = vec![];
+        self.pk_dest.write(&mut buffer)?;
+        self.amount.write(&mut buffer)?;
+        read_field_element_from_buffer_with_padding(buffer.as_slice())
+    }
+}
+
+//Will return error if buffer.len > FIELD_SIZE. If buffer.len < FIELD_SIZE, padding 0s will be added
+pub fn read_field_element_from_buffer_with_padding(buffer: &[u8]) -> IoResult<FieldElement>
+{
+    let buff_len = buffer.len();
+
+    //Pad to reach field element size
+    let mut new_buffer = vec![];
+    new_buffer.extend_from_slice(buffer);
+    for _ in buff_len..FIELD_SIZE { new_buffer.push(0u8) } //Add padding zeros to reach field size
+
+    FieldElement::read(&new_buffer[..])
+}
+
+pub fn read_field_element_from_u64(num: u64) -> FieldElement {
+    FieldElement::from_repr(BigInteger768::from(num))
+}
+
+// Computes H(H(pks), threshold): used to generate the constant value needed to be declared
+// in MC during SC creation. Each hash is domain separated according to `version`.
+pub fn compute_pks_threshold_hash(
+    pks:       &[SchnorrPk],
+    threshold: u64,
+    version:   DomainSeparationVersion,
+) -> Result<FieldElement, Error> {
+    let threshold_field = read_field_element_from_u64(threshold);
+    let mut hasher = BufferedPoseidonHasher::new();
+    if let Some(personalization) = get_personalization(HashDomain::PksHash, version) {
+        hasher.update(&personalization);
+    }
+    pks.iter().for_each(|pk| { hasher.update(&pk.x); });
+    let pks_hash = hasher.finalize()?;
+    domain_separated_hash(HashDomain::PksThresholdHash, version, &[pks_hash, threshold_field])
+}
+
+// Like compute_pks_threshold_hash, but fails unless the i-th element of pops is a valid proof of
+// possession for the i-th public key.
+pub fn compute_pks_threshold_hash_with_pops(
+    pks:       &[SchnorrPk],
+    pops:      &[SchnorrPoP],
+    threshold: u64,
+    version:   DomainSeparationVersion,
+) -> Result<FieldElement, Error> {
+    if pks.len() != pops.len() {
+        Err(format!("Expected a proof of possession for each one of the {} public keys, found {}", pks.len(), pops.len()))?
+    }
+    let invalid_pop = pks.par_iter().zip(pops.par_iter()).enumerate()
+        .map(|(i, (pk, pop))| schnorr_verify_pop(pk, pop).map(|valid| (i, valid)).map_err(|e| e.to_string()))
+        .find_any(|verdict| match verdict { Ok((_, valid)) => !valid, Err(_) => true });
+    match invalid_pop {
+        None => compute_pks_threshold_hash(pks, threshold, version),
+        Some(Ok((i, _))) => Err(format!("Invalid proof of possession for public key {}", i))?,
+        Some(Err(e)) => Err(e)?,
+    }
+}
+
+//Compute and return (MR(bt_list), H(MR(bt_list), H(bi-1), H(bi))
+pub fn compute_msg_to_sign(
+    end_epoch_mc_b_hash:      &FieldElement,
+    prev_end_epoch_mc_b_hash: &FieldElement,
+    bt_list:                  &[BackwardTransfer],
+    version:                  DomainSeparationVersion,
+) -> Result<(FieldElement, FieldElement), Error> {
+
+    let mr_bt = if bt_list.is_empty() {
+        MNT4753_PHANTOM_MERKLE_ROOT
+    } else {
+        let mut bt_field_list = vec![];
+        for bt in bt_list.iter() {
+            let bt_f = bt.to_field_element()?;
+            bt_field_list.push(bt_f);
+        }
+
+        //Compute bt_list merkle_root
+        let bt_mt = new_ginger_merkle_tree(bt_field_list.as_slice())?;
+        get_ginger_merkle_root(&bt_mt)
+    };
+
+    //Compute message to be verified
+    let msg = domain_separated_hash(
+        HashDomain::MsgToSign, version, &[mr_bt, *prev_end_epoch_mc_b_hash, *end_epoch_mc_b_hash]
+    )?;
+
+    Ok((mr_bt, msg))
+}
+
+pub fn compute_wcert_sysdata_hash(
+    valid_sigs:               u64,
+    mr_bt:                    &FieldElement,
+    prev_end_epoch_mc_b_hash: &FieldElement,
+    end_epoch_mc_b_hash:      &FieldElement,
+    version:                  DomainSeparationVersion,
+) -> Result<FieldElement, Error> {
+
+    //Compute quality and wcert_sysdata_hash
+    let quality = read_field_element_from_u64(valid_sigs);
+    let wcert_sysdata_hash = domain_separated_hash(
+        HashDomain::WCertSysDataHash, version, &[quality, *mr_bt, *prev_end_epoch_mc_b_hash, *end_epoch_mc_b_hash]
+    )?;
+    Ok(wcert_sysdata_hash)
+}
+
+pub fn create_naive_threshold_sig_proof(
+    pks:                      &[SchnorrPk],
+    mut sigs:                 Vec<Option<SchnorrSig>>,
+    end_epoch_mc_b_hash:      &[u8; 32],
+    prev_end_epoch_mc_b_hash: &[u8; 32],
+    bt_list:                  &[BackwardTransfer],
+    threshold:                u64,
+    proving_key_path:         &str,
+    version:                  DomainSeparationVersion,
+) -> Result<(SCProof, u64), Error> {
+
+    //Get max pks
+    let max_pks = pks.len();
+    assert_eq!(sigs.len(), max_pks);
+
+    //Read end_epoch_mc_b_hash, prev_end_epoch_mc_b_hash and bt_list as field elements
+    let end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&end_epoch_mc_b_hash[..])?;
+    let prev_end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&prev_end_epoch_mc_b_hash[..])?;
+    let (mr_bt, msg) = compute_msg_to_sign(
+        &end_epoch_mc_b_hash,
+        &prev_end_epoch_mc_b_hash,
+        bt_list,
+        version,
+    )?;
+
+    // Check and count number of valid signatures,
+    // and replace with NULL_CONST.null_sig the None ones
+    let items = sigs.iter().zip(pks.iter())
+        .filter_map(|(sig, pk)| sig.map(|sig| (*pk, msg, sig)))
+        .collect::<Vec<_>>();
+    let valid_signatures = schnorr_verify_signatures_per_item(items.as_slice())?
+        .into_iter()
+        .filter(|&verdict| verdict)
+        .count() as u64;
+    for sig in sigs.iter_mut() {
+        if sig.is_none() { *sig = Some(NULL_CONST.null_sig) }
+    }
+
+    //Compute b as v-t and convert it to field element
+    let b = read_field_element_from_u64(valid_signatures - threshold);
+
+    //Convert affine pks to projective
+    let pks = pks.iter().map(|&pk| pk.into_projective()).collect::<Vec<_>>();
+
+    //Convert needed variables into field elements
+    let threshold = read_field_element_from_u64(threshold);
+
+    let c = NaiveTresholdSignature::<FieldElement>::new(
+        pks, sigs, threshold, b, end_epoch_mc_b_hash,
+        prev_end_epoch_mc_b_hash, mr_bt, max_pks, version,
+    );
+
+    //Read proving key
+    let params = read_from_file(proving_key_path)?;
+
+    //Create and return proof
+    let mut rng = OsRng;
+    let proof = create_random_proof(c, &params, &mut rng)?;
+    Ok((proof, valid_signatures))
+}
+
+pub fn verify_naive_threshold_sig_proof(
+    constant:                 &FieldElement,
+    end_epoch_mc_b_hash:      &[u8; 32],
+    prev_end_epoch_mc_b_hash: &[u8; 32],
+    bt_list:                  &[BackwardTransfer],
+    valid_sigs:               u64,
+    proof:                    &SCProof,
+    vk_path:                  &str,
+    version:                  DomainSeparationVersion,
+) -> Result<bool, Error>
+{
+    //Compute wcert_sysdata_hash
+    let end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&end_epoch_mc_b_hash[..])?;
+    let prev_end_epoch_mc_b_hash = read_field_element_from_buffer_with_padding(&prev_end_epoch_mc_b_hash[..])?;
+    let (mr_bt, _) = compute_msg_to_sign(&end_epoch_mc_b_hash, &prev_end_epoch_mc_b_hash, bt_list, version)?;
+    let wcert_sysdata_hash = compute_wcert_sysdata_hash(
+        valid_sigs, &mr_bt, &prev_end_epoch_mc_b_hash, &end_epoch_mc_b_hash, version
+    )?;
+    let aggregated_input = domain_separated_hash(HashDomain::AggregatedInput, version, &[*constant, wcert_sysdata_hash])?;
+
+    //Verify proof
+    let vk = read_from_file(vk_path)?;
+    let pvk = prepare_verifying_key(&vk); //Get verifying key
+    let is_verified = verify_proof(&pvk, &proof, &[aggregated_input])?;
+
+    Ok(is_verified)
+}
+
+//VRF types and functions
+
+lazy_static! {
+    pub static ref VRF_GH_PARAMS: BoweHopwoodPedersenParameters<MNT6G1Projective> = {
+        let params = VRFParams::new();
+        BoweHopwoodPedersenParameters::<MNT6G1Projective>{generators: params.group_hash_generators}
+    };
+}
+
+type GroupHash = BoweHopwoodPedersenCRH<MNT6G1Projective, VRFWindow>;
+
+pub type VRFScheme = FieldBasedEcVrf<MNT4Fr, MNT6G1Projective, MNT4PoseidonHash, GroupHash>;
+pub type VRFProof = FieldBasedEcVrfProof<MNT4Fr, MNT6G1Projective>;
+pub type VRFPk = MNT6G1Affine;
+pub type VRFSk = SecretScalar;
+
+pub fn vrf_generate_key() -> (VRFPk, VRFSk) {
+    let mut rng = OsRng;
+    let (pk, mut sk) = VRFScheme::keygen(&mut rng);
+    (pk.into_affine(), SecretScalar::take(&mut sk))
+}
+
+pub fn vrf_get_public_key(sk: &VRFSk) -> VRFPk {
+    SchnorrSigScheme::get_public_key(sk).into_affine()
+}
+
+pub fn vrf_verify_public_key(pk: &VRFPk) -> bool {
+    SchnorrSigScheme::keyverify(&pk.into_projective())
+}
+
+pub fn vrf_prove(msg: &FieldElement, sk: &VRFSk, pk: &VRFPk) -> Result<(VRFProof, FieldElement), Error> {
+    let mut rng = OsRng;
+
+    //Compute proof
+    let proof = VRFScheme::prove(
+        &mut rng,
+        &VRF_GH_PARAMS,
+        &pk.into_projective(),
+        sk,
+        &[*msg]
+    )?;
+
+    //Convert gamma from proof to field elements
+    let gamma_coords = proof.gamma.to_field_elements().unwrap();
+
+    //Compute VRF output
+    let mut hash_input = Vec::new();
+    hash_input.push(*msg);
+    hash_input.extend_from_slice(gamma_coords.as_slice());
+    let output = compute_poseidon_hash(hash_input.as_ref())?;
+
+    Ok((proof, output))
+}
+
+pub fn vrf_proof_to_hash(msg: &FieldElement, pk: &VRFPk, proof: &VRFProof) -> Result<FieldElement, Error> {
+    VRFScheme::proof_to_hash(&VRF_GH_PARAMS,&pk.into_projective(), &[*msg], proof)
+}
+
+pub fn serialize_vrf_pk_compressed(pk: &VRFPk) -> Result<Vec<u8>, Error> {
+    pk.to_compressed_bytes()
+}
+
+pub fn deserialize_vrf_pk_compressed(bytes: &[u8]) -> Result<VRFPk, Error> {
+    let pk = VRFPk::from_compressed_bytes(bytes)?;
+    check_g1_point(&pk)?;
+    Ok(pk)
+}
+
+// gamma (compressed) || c || s
+pub fn serialize_vrf_proof_compressed(proof: &VRFProof) -> Result<Vec<u8>, Error> {
+    let mut bytes = proof.gamma.into_affine().to_compressed_bytes()?;
+    proof.c.write(&mut bytes)?;
+    proof.s.write(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn deserialize_vrf_proof_compressed(bytes: &[u8]) -> Result<VRFProof, Error> {
+    if bytes.len() != VRF_PROOF_COMPRESSED_SIZE {
+        Err(DeserializationError::InvalidSize)?
+    }
+    let (gamma_bytes, scalars_bytes) = bytes.split_at(G1_COMPRESSED_SIZE);
+    let gamma = MNT6G1Affine::from_compressed_bytes(gamma_bytes)?;
+    check_g1_point(&gamma)?;
+    let c = read_field_element_from_buffer_checked(&scalars_bytes[..FIELD_SIZE])
+        .map_err(|_| DeserializationError::NonCanonical)?;
+    let s = read_field_element_from_buffer_checked(&scalars_bytes[FIELD_SIZE..])
+        .map_err(|_| DeserializationError::NonCanonical)?;
+    Ok(VRFProof { gamma: gamma.into_projective(), c, s })
+}
+
+//Ouroboros consensus types and functions
+
+pub const FORGER_ELIGIBILITY_PROOF_SIZE: usize = 2 * VRF_PROOF_SIZE; // 770
+
+// Proof that a forger is the leader of a slot: the VRF proofs of the leader election and of the
+// epoch nonce evaluations of the slot.
+#[derive(Clone)]
+pub struct ForgerEligibilityProof {
+    pub leader_proof: LeaderProof<VRFScheme>,
+    pub nonce_proof: NonceProof<VRFScheme>,
+}
+
+impl ToBytes for ForgerEligibilityProof {
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.leader_proof.0.write(&mut writer)?;
+        self.nonce_proof.0.write(&mut writer)
+    }
+}
+
+impl FromBytes for ForgerEligibilityProof {
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let leader_proof = VRFProof::read(&mut reader)?;
+        let nonce_proof = VRFProof::read(&mut reader)?;
+        Ok(ForgerEligibilityProof { leader_proof: LeaderProof(leader_proof), nonce_proof: NonceProof(nonce_proof) })
+    }
+}
+
+impl CheckedDeserialize for ForgerEligibilityProof {
+    const SERIALIZED_SIZE: usize = FORGER_ELIGIBILITY_PROOF_SIZE;
+
+    fn check(&self) -> Result<(), DeserializationError> {
+        self.leader_proof.0.check()?;
+        self.nonce_proof.0.check()
+    }
+}
+
+// Returns None if the forger is not the leader of the slot.
+pub fn ouroboros_prove_forger_eligibility(
+    epoch_randomness: &FieldElement,
+    slot_number: u32,
+    forger_stake: u64,
+    total_forgers_stake: u64,
+    sk: &VRFSk,
+    pk: &VRFPk,
+) -> Result<Option<ForgerEligibilityProof>, Error>
+{
+    let proofs = ouroboros_create_proof::<VRFScheme>(
+        &VRF_GH_PARAMS,
+        *epoch_randomness,
+        slot_number,
+        &pk.into_projective(),
+        sk,
+        forger_stake,
+        total_forgers_stake
+    )?;
+
+    Ok(proofs.map(|(leader_proof, nonce_proof)| ForgerEligibilityProof { leader_proof, nonce_proof }))
+}
+
+// Returns the leader election and the epoch nonce VRF outputs of the slot if the proof is valid and
+// the forger is the leader of the slot, None otherwise.
+pub fn ouroboros_check_forger_eligibility(
+    proof: &ForgerEligibilityProof,
+    epoch_randomness: &FieldElement,
+    slot_number: u32,
+    forger_stake: u64,
+    total_forgers_stake: u64,
+    pk: &VRFPk,
+) -> Result<Option<(LeaderOutput<FieldElement>, NonceOutput<FieldElement>)>, Error>
+{
+    ouroboros_check_proof::<VRFScheme>(
+        &VRF_GH_PARAMS,
+        &proof.leader_proof,
+        &proof.nonce_proof,
+        *epoch_randomness,
+        slot_number,
+        &pk.into_projective(),
+        forger_stake,
+        total_forgers_stake
+    )
+}
+
+//************************************HD key derivation functions************************************
+
+// Seed-based hierarchical deterministic derivation of Schnorr and VRF secret keys, in the spirit of
+// BIP32 but using Poseidon. An extended key is a (key, chain code) pair: the master one is derived from
+// the seed and the key type, each child from its parent and its index. Children with index at least
+// HD_HARDENED_KEY_OFFSET are hardened: they're derived from the parent secret key, so that a leaked
+// child secret key and parent chain code don't reveal the parent secret key. Non-hardened children
+// are derived from the parent public key as parent_sk + tweak, so that their public keys can be
+// derived from the parent extended public key alone.
+
+pub const HD_HARDENED_KEY_OFFSET: u32 = 1 << 31;
+pub const HD_SEED_MIN_LENGTH: usize = 16;
+pub const HD_SEED_MAX_LENGTH: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HDKeyType {
+    Schnorr,
+    Vrf,
+}
+
+impl HDKeyType {
+    pub fn from_u8(key_type: u8) -> Option<Self> {
+        match key_type {
+            0 => Some(HDKeyType::Schnorr),
+            1 => Some(HDKeyType::Vrf),
+            _ => None,
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            HDKeyType::Schnorr => 0,
+            HDKeyType::Vrf => 1,
+        }
+    }
+
+    fn to_field_element(&self) -> FieldElement {
+        match self {
+            HDKeyType::Schnorr => read_field_element_from_u64(0),
+            HDKeyType::Vrf => read_field_element_from_u64(1),
+        }
+    }
+}
+
+// Expands `input` into a key (or tweak), sampled from the hash of (input, 0), and a chain code,
+// the hash of (input, 1).
+// Input may hold the parent secret key, so it's wiped before returning.
+// Keys derived from a seed must never change: the version of the personalization is fixed.
+fn hd_expand(domain: HashDomain, mut input: Vec<FieldElement>) -> Result<(MNT4Fq, FieldElement), Error> {
+    input.push(read_field_element_from_u64(0));
+    let key_seed = domain_separated_hash(domain, DomainSeparationVersion::V1, input.as_slice());
+
+    *input.last_mut().unwrap() = read_field_element_from_u64(1);
+    let chain_code = domain_separated_hash(domain, DomainSeparationVersion::V1, input.as_slice());
+    wipe(input.as_mut_slice());
+
+    let mut key_seed = key_seed?;
+    let key = MNT4Fq::rand(&mut field_element_to_rng(&key_seed)?);
+    wipe(std::slice::from_mut(&mut key_seed));
+    Ok((key, chain_code?))
+}
+
+fn hd_child_input(
+    key_type: HDKeyType,
+    chain_code: &FieldElement,
+    parent_key: Vec<FieldElement>,
+    index: u32,
+) -> Vec<FieldElement>
+{
+    let mut input = vec![key_type.to_field_element(), *chain_code];
+    input.extend(parent_key);
+    input.push(read_field_element_from_u64(index as u64));
+    input
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HDExtendedSecretKey {
+    pub key_type: HDKeyType,
+    pub depth: u8,
+    pub child_index: u32,
+    pub secret_key: SecretScalar,
+    pub chain_code: FieldElement,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HDExtendedPublicKey {
+    pub key_type: HDKeyType,
+    pub depth: u8,
+    pub child_index: u32,
+    pub public_key: MNT6G1Affine,
+    pub chain_code: FieldElement,
+}
+
+impl HDExtendedSecretKey {
+    pub fn master(key_type: HDKeyType, seed: &[u8]) -> Result<Self, Error> {
+        if seed.len() < HD_SEED_MIN_LENGTH || seed.len() > HD_SEED_MAX_LENGTH {
+            Err(format!("Seed length must be between {} and {} bytes, found {}",
+                        HD_SEED_MIN_LENGTH, HD_SEED_MAX_LENGTH, seed.len()))?
+        }
+        let input = vec![key_type.to_field_element(), compute_poseidon_hash_bytes(seed)?];
+        let (mut secret_key, chain_code) = hd_expand(HashDomain::HdMasterKey, input)?;
+        let secret_key = SecretScalar::take(&mut secret_key);
+        if secret_key.is_zero() {
+            Err("Invalid master key, a different seed must be used")?
+        }
+        Ok(Self { key_type, depth: 0, child_index: 0, secret_key, chain_code })
+    }
+
+    pub fn public_key(&self) -> MNT6G1Affine {
+        SchnorrSigScheme::get_public_key(&self.secret_key).into_affine()
+    }
+
+    pub fn to_extended_public_key(&self) -> HDExtendedPublicKey {
+        HDExtendedPublicKey {
+            key_type: self.key_type,
+            depth: self.depth,
+            child_index: self.child_index,
+            public_key: self.public_key(),
+            chain_code: self.chain_code,
+        }
+    }
+
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        if self.depth == u8::max_value() {
+            Err("Maximum derivation depth reached")?
+        }
+        let hardened = index >= HD_HARDENED_KEY_OFFSET;
+        let parent_key = if hardened {
+            with_serialized_secret_key(&self.secret_key, bytes_to_field_elements)??
+        } else {
+            let pk = self.public_key();
+            vec![pk.x, pk.y]
+        };
+        let input = hd_child_input(self.key_type, &self.chain_code, parent_key, index);
+        let (mut tweak, chain_code) = hd_expand(HashDomain::HdChildKey, input)?;
+
+        let mut secret_key = if hardened { tweak } else { *self.secret_key + &tweak };
+        wipe(std::slice::from_mut(&mut tweak));
+        let secret_key = SecretScalar::take(&mut secret_key);
+        if secret_key.is_zero() {
+            Err(format!("Invalid child key at index {}, the next index must be used", index))?
+        }
+        Ok(Self { key_type: self.key_type, depth: self.depth + 1, child_index: index, secret_key, chain_code })
+    }
+
+    pub fn derive_path(&self, path: &[u32]) -> Result<Self, Error> {
+        let mut key = self.clone();
+        for &index in path.iter() {
+            key = key.derive_child(index)?;
+        }
+        Ok(key)
+    }
+}
+
+impl HDExtendedPublicKey {
+    // Derives the public key of the non-hardened child `index`: hardened children can't be derived
+    // without the parent secret key.
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        if index >= HD_HARDENED_KEY_OFFSET {
+            Err("Hardened child public keys can't be derived from the parent public key")?
+        }
+        if self.depth == u8::max_value() {
+            Err("Maximum derivation depth reached")?
+        }
+        let parent_key = vec![self.public_key.x, self.public_key.y];
+        let input = hd_child_input(self.key_type, &self.chain_code, parent_key, index);
+        let (tweak, chain_code) = hd_expand(HashDomain::HdChildKey, input)?;
+
+        let public_key = (self.public_key.into_projective() + &SchnorrSigScheme::get_public_key(&tweak)).into_affine();
+        if public_key.is_zero() {
+            Err(format!("Invalid child key at index {}, the next index must be used", index))?
+        }
+        Ok(Self { key_type: self.key_type, depth: self.depth + 1, child_index: index, public_key, chain_code })
+    }
+}
+
+// Parses a derivation path like "m/44'/0'/1", in which ' (or h) marks hardened indices.
+pub fn parse_hd_path(path: &str) -> Result<Vec<u32>, Error> {
+    let mut components = path.split('/');
+    if components.next() != Some("m") {
+        Err(format!("Derivation path must start with 'm': {}", path))?
+    }
+    components.map(|component| -> Result<u32, Error> {
+        let (index, hardened) = if component.ends_with('\'') || component.ends_with('h') {
+            (&component[..component.len() - 1], true)
+        } else {
+            (component, false)
+        };
+        if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+            Err(format!("Invalid derivation path component: {}", component))?
+        }
+        let index = index.parse::<u32>().ok()
+            .filter(|&index| index < HD_HARDENED_KEY_OFFSET)
+            .ok_or(format!("Derivation path index out of range: {}", component))?;
+        Ok(if hardened { index + HD_HARDENED_KEY_OFFSET } else { index })
+    }).collect()
+}
+
+pub fn schnorr_derive_key(seed: &[u8], path: &[u32]) -> Result<(SchnorrPk, SchnorrSk), Error> {
+    let key = HDExtendedSecretKey::master(HDKeyType::Schnorr, seed)?.derive_path(path)?;
+    Ok((key.public_key(), key.secret_key))
+}
+
+pub fn vrf_derive_key(seed: &[u8], path: &[u32]) -> Result<(VRFPk, VRFSk), Error> {
+    let key = HDExtendedSecretKey::master(HDKeyType::Vrf, seed)?.derive_path(path)?;
+    Ok((key.public_key(), key.secret_key))
+}
+
+//************************************Keystore functions********************************************
+
+// Password-encrypted storage of Schnorr and VRF secret keys. A keystore is serialized as:
+// version (1 byte) || key type (1 byte) || scrypt log_n (1 byte) || scrypt r (u32) || scrypt p (u32) ||
+// salt (32 bytes) || nonce (12 bytes) || ChaCha20Poly1305(secret key) (96 + 16 bytes) || checksum (4 bytes).
+// The encryption key is derived from the password with scrypt, and everything preceding the ciphertext
+// is authenticated as associated data. The checksum, the first 4 bytes of the SHA-256 of everything
+// preceding it, tells corrupted keystores apart from wrong passwords.
+
+pub const KEYSTORE_VERSION: u8 = 1;
+pub const KEYSTORE_SALT_SIZE: usize = 32;
+pub const KEYSTORE_NONCE_SIZE: usize = 12;
+pub const KEYSTORE_TAG_SIZE: usize = 16;
+pub const KEYSTORE_CHECKSUM_SIZE: usize = 4;
+pub const KEYSTORE_HEADER_SIZE: usize = 3 + 2 * 4 + KEYSTORE_SALT_SIZE + KEYSTORE_NONCE_SIZE; // 55
+pub const KEYSTORE_SIZE: usize = KEYSTORE_HEADER_SIZE + SCALAR_FIELD_SIZE + KEYSTORE_TAG_SIZE + KEYSTORE_CHECKSUM_SIZE; // 171
+
+// Upper bound on the memory used by scrypt, i.e. 128 * r * N bytes: 256 MiB.
+pub const KEYSTORE_KDF_MAX_MEMORY: u64 = 256 * 1024 * 1024;
+pub const KEYSTORE_KDF_MAX_P: u32 = 4;
+
+// Parameters of the scrypt KDF: N = 2^log_n, r, p.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeystoreKdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl KeystoreKdfParams {
+    // Bounds the cost of decryption, so that a crafted keystore can't exhaust the memory (nor the time,
+    // which also grows with p) of the node. Checked before running the KDF.
+    fn is_within_bounds(&self) -> bool {
+        if self.log_n == 0 || self.log_n >= 64 || self.r == 0 || self.p == 0 || self.p > KEYSTORE_KDF_MAX_P {
+            return false
+        }
+        // 128 * r * 2^log_n <= KEYSTORE_KDF_MAX_MEMORY, written not to overflow
+        self.r as u64 <= (KEYSTORE_KDF_MAX_MEMORY >> self.log_n) / 128
+    }
+}
+
+impl Default for KeystoreKdfParams {
+    // Interactive login parameters recommended by the scrypt paper: 2^15 iterations, 32 MB of memory.
+    fn default() -> Self {
+        Self { log_n: 15, r: 8, p: 1 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeystoreError {
+    // Wrong size, unknown version or key type, invalid KDF parameters or checksum mismatch
+    Corrupted,
+    // The keystore holds a different type of key than the requested one
+    WrongKeyType,
+    // The ciphertext can't be authenticated: the password is wrong
+    WrongPassword,
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::Corrupted => write!(f, "Keystore is corrupted"),
+            KeystoreError::WrongKeyType => write!(f, "Keystore holds a different type of key"),
+            KeystoreError::WrongPassword => write!(f, "Wrong keystore password"),
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+fn keystore_derive_key(password: &[u8], salt: &[u8], params: &KeystoreKdfParams) -> Result<[u8; 32], Error> {
+    let scrypt_params = ScryptParams::new(params.log_n, params.r, params.p)
+        .map_err(|_| KeystoreError::Corrupted)?;
+    let mut key = [0u8; 32];
+    scrypt(password, salt, &scrypt_params, &mut key).map_err(|e| format!("{:?}", e))?;
+    Ok(key)
+}
+
+fn keystore_checksum(bytes: &[u8]) -> [u8; KEYSTORE_CHECKSUM_SIZE] {
+    let mut checksum = [0u8; KEYSTORE_CHECKSUM_SIZE];
+    checksum.copy_from_slice(&Sha256::digest(bytes)[..KEYSTORE_CHECKSUM_SIZE]);
+    checksum
+}
+
+pub fn encrypt_secret_key_with_params(
+    sk: &SecretScalar,
+    key_type: HDKeyType,
+    password: &[u8],
+    params: &KeystoreKdfParams,
+) -> Result<Vec<u8>, Error>
+{
+    if !params.is_within_bounds() {
+        Err("Invalid KDF parameters")?
+    }
+
+    let mut rng = OsRng;
+    let mut salt = [0u8; KEYSTORE_SALT_SIZE];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; KEYSTORE_NONCE_SIZE];
+    rng.fill_bytes(&mut nonce);
+
+    let mut keystore = Vec::with_capacity(KEYSTORE_SIZE);
+    keystore.push(KEYSTORE_VERSION);
+    keystore.push(key_type.to_u8());
+    keystore.push(params.log_n);
+    keystore.extend_from_slice(&params.r.to_le_bytes());
+    keystore.extend_from_slice(&params.p.to_le_bytes());
+    keystore.extend_from_slice(&salt);
+    keystore.extend_from_slice(&nonce);
+
+    let mut key = keystore_derive_key(password, &salt, params)?;
+    let cipher = ChaCha20Poly1305::new(GenericArray::clone_from_slice(&key));
+    wipe(&mut key[..]);
+
+    let ciphertext = with_serialized_secret_key(sk, |sk_bytes| cipher.encrypt(
+        GenericArray::from_slice(&nonce),
+        Payload { msg: sk_bytes, aad: keystore.as_slice() },
+    ))?.map_err(|_| "Unable to encrypt secret key")?;
+    keystore.extend_from_slice(ciphertext.as_slice());
+
+    let checksum = keystore_checksum(keystore.as_slice());
+    keystore.extend_from_slice(&checksum);
+    Ok(keystore)
+}
+
+pub fn encrypt_secret_key(sk: &SecretScalar, key_type: HDKeyType, password: &[u8]) -> Result<Vec<u8>, Error> {
+    encrypt_secret_key_with_params(sk, key_type, password, &KeystoreKdfParams::default())
+}
+
+// Returns the secret key of `key_type` stored in `keystore`. On failure, the error is a KeystoreError
+// telling a corrupted keystore, a wrong key type and a wrong password apart.
+pub fn decrypt_secret_key(keystore: &[u8], key_type: HDKeyType, password: &[u8]) -> Result<SecretScalar, Error> {
+    if keystore.len() != KEYSTORE_SIZE {
+        Err(KeystoreError::Corrupted)?
+    }
+    let (content, checksum) = keystore.split_at(KEYSTORE_SIZE - KEYSTORE_CHECKSUM_SIZE);
+    if &keystore_checksum(content)[..] != checksum {
+        Err(KeystoreError::Corrupted)?
+    }
+    if content[0] != KEYSTORE_VERSION {
+        Err(KeystoreError::Corrupted)?
+    }
+    match HDKeyType::from_u8(content[1]) {
+        Some(stored_type) => if stored_type != key_type {
+            Err(KeystoreError::WrongKeyType)?
+        },
+        None => Err(KeystoreError::Corrupted)?,
+    }
+
+    let mut u32_bytes = [0u8; 4];
+    u32_bytes.copy_from_slice(&content[3..7]);
+    let r = u32::from_le_bytes(u32_bytes);
+    u32_bytes.copy_from_slice(&content[7..11]);
+    let p = u32::from_le_bytes(u32_bytes);
+    let params = KeystoreKdfParams { log_n: content[2], r, p };
+    if !params.is_within_bounds() {
+        Err(KeystoreError::Corrupted)?
+    }
+
+    let (header, ciphertext) = content.split_at(KEYSTORE_HEADER_SIZE);
+    let salt = &header[11..11 + KEYSTORE_SALT_SIZE];
+    let nonce = &header[11 + KEYSTORE_SALT_SIZE..];
+
+    let mut key = keystore_derive_key(password, salt, &params)?;
+    let cipher = ChaCha20Poly1305::new(GenericArray::clone_from_slice(&key));
+    wipe(&mut key[..]);
+    let mut sk_bytes = cipher.decrypt(
+        GenericArray::from_slice(nonce),
+        Payload { msg: ciphertext, aad: header },
+    ).map_err(|_| KeystoreError::WrongPassword)?;
+
+    let sk = SecretScalar::read(sk_bytes.as_slice()).map_err(|_| KeystoreError::Corrupted);
+    wipe(sk_bytes.as_mut_slice());
+    Ok(sk?)
+}
+
+pub fn schnorr_encrypt_secret_key(sk: &SchnorrSk, password: &[u8]) -> Result<Vec<u8>, Error> {
+    encrypt_secret_key(sk, HDKeyType::Schnorr, password)
+}
+
+pub fn schnorr_decrypt_secret_key(keystore: &[u8], password: &[u8]) -> Result<SchnorrSk, Error> {
+    decrypt_secret_key(keystore, HDKeyType::Schnorr, password)
+}
+
+pub fn vrf_encrypt_secret_key(sk: &VRFSk, password: &[u8]) -> Result<Vec<u8>, Error> {
+    encrypt_secret_key(sk, HDKeyType::Vrf, password)
+}
+
+pub fn vrf_decrypt_secret_key(keystore: &[u8], password: &[u8]) -> Result<VRFSk, Error> {
+    decrypt_secret_key(keystore, HDKeyType::Vrf, password)
+}
+
+//************Merkle Tree functions******************
+
+pub struct FieldBasedMerkleTreeParams;
+
+impl FieldBasedMerkleTreeConfig for FieldBasedMerkleTreeParams {
+    const HEIGHT: usize = 13;
+    type H = MNT4PoseidonHash;
+}
+
+pub type GingerMerkleTree = FieldBasedMerkleHashTree<FieldBasedMerkleTreeParams>;
+pub type GingerMerkleTreePath = FieldBasedMerkleTreePath<FieldBasedMerkleTreeParams>;
+
+//Each node of the path is serialized as the sibling hash followed by one byte for the direction
+pub const GINGER_MERKLE_PATH_SIZE: usize = (FieldBasedMerkleTreeParams::HEIGHT - 1) * (FIELD_SIZE + 1);
+
+pub fn new_ginger_merkle_tree(leaves: &[FieldElement]) -> Result<GingerMerkleTree, Error> {
+    GingerMerkleTree::new(leaves)
+}
+
+pub fn get_ginger_merkle_root(tree: &GingerMerkleTree) -> FieldElement {
+    tree.root()
+}
+
+pub fn get_ginger_merkle_path(leaf: &FieldElement, leaf_index: usize, tree: &GingerMerkleTree)
+    -> Result<GingerMerkleTreePath, Error>
+{
+    tree.generate_proof(leaf_index, leaf)
+}
+
+pub fn verify_ginger_merkle_path(path: &GingerMerkleTreePath, merkle_root: &FieldElement, leaf: &FieldElement)
+    -> Result<bool, Error>
+{
+    path.verify(merkle_root, leaf)
+}
+
+pub fn serialize_ginger_merkle_path(path: &GingerMerkleTreePath, buffer: &mut [u8]) -> IoResult<()> {
+    let mut writer = buffer;
+    for &(ref sibling, direction) in path.path.iter() {
+        sibling.write(&mut writer)?;
+        (direction as u8).write(&mut writer)?;
+    }
+    Ok(())
+}
+
+// Siblings must be canonical field elements, and directions 0 or 1.
+pub fn deserialize_ginger_merkle_path(buffer: &[u8]) -> Result<GingerMerkleTreePath, DeserializationError> {
+    if buffer.len() != GINGER_MERKLE_PATH_SIZE {
+        return Err(DeserializationError::InvalidSize)
+    }
+
+    let mut path = Vec::with_capacity(FieldBasedMerkleTreeParams::HEIGHT - 1);
+    for node in buffer.chunks(FIELD_SIZE + 1) {
+        let sibling = deserialize_from_buffer_checked::<FieldElement>(&node[..FIELD_SIZE])?;
+        let direction = match node[FIELD_SIZE] {
+            0 => false,
+            1 => true,
+            _ => return Err(DeserializationError::NonCanonical),
+        };
+        path.push((sibling, direction));
+    }
+    Ok(GingerMerkleTreePath{ path })
+}
+
+//************Merkle Multi Path functions******************
+
+// Compact opening of several leaves of a GingerMerkleTree at once. Leaf indices are kept sorted in
+// strictly increasing order, and only the siblings that can't be recomputed from the opened leaves
+// are stored, level by level (from the leaves to the root) and, within a level, by increasing index.
+
+pub const GINGER_MERKLE_TREE_LEVELS: usize = FieldBasedMerkleTreeParams::HEIGHT - 1;
+
+pub fn get_ginger_merkle_multi_path_size(path: &GingerMerkleTreeMultiPath) -> usize {
+    4 + 8 * path.leaf_indices.len() + 4 + FIELD_SIZE * path.siblings.len()
+}
+
+fn check_ginger_merkle_multi_path_indices(leaf_indices: &[u64]) -> Result<(), Error> {
+    if leaf_indices.is_empty() {
+        Err("At least one leaf must be opened")?
+    }
+    for pair in leaf_indices.windows(2) {
+        if pair[0] >= pair[1] {
+            Err("Leaf indices must be strictly increasing")?
+        }
+    }
+    let last = leaf_indices[leaf_indices.len() - 1];
+    if last >> GINGER_MERKLE_TREE_LEVELS != 0 {
+        Err(format!("Leaf index out of range: {}", last))?
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GingerMerkleTreeMultiPath {
+    pub leaf_indices: Vec<u64>,
+    pub siblings:     Vec<FieldElement>,
+}
+
+impl ToBytes for GingerMerkleTreeMultiPath {
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        (self.leaf_indices.len() as u32).write(&mut writer)?;
+        for leaf_index in self.leaf_indices.iter() {
+            leaf_index.write(&mut writer)?;
+        }
+        (self.siblings.len() as u32).write(&mut writer)?;
+        for sibling in self.siblings.iter() {
+            sibling.write(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromBytes for GingerMerkleTreeMultiPath {
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let max_leaves = 1usize << GINGER_MERKLE_TREE_LEVELS;
+
+        let leaves_count = u32::read(&mut reader)? as usize;
+        if leaves_count > max_leaves {
+            return Err(IoError::new(ErrorKind::InvalidData, "too many leaf indices"))
+        }
+        let mut leaf_indices = Vec::with_capacity(leaves_count);
+        for _ in 0..leaves_count {
+            leaf_indices.push(u64::read(&mut reader)?);
+        }
+        check_ginger_merkle_multi_path_indices(leaf_indices.as_slice())
+            .map_err(|e| IoError::new(ErrorKind::InvalidData, e.to_string()))?;
+
+        // Each opened leaf contributes at most one sibling per level
+        let siblings_count = u32::read(&mut reader)? as usize;
+        if siblings_count > leaves_count * GINGER_MERKLE_TREE_LEVELS {
+            return Err(IoError::new(ErrorKind::InvalidData, "too many siblings"))
+        }
+        // Every sibling must be a canonical field element
+        let mut siblings = Vec::with_capacity(siblings_count);
+        let mut sibling_bytes = [0u8; FIELD_SIZE];
+        for _ in 0..siblings_count {
+            reader.read_exact(&mut sibling_bytes)?;
+            let sibling = deserialize_from_buffer_checked::<FieldElement>(&sibling_bytes)
+                .map_err(|_| IoError::new(ErrorKind::InvalidData, "non canonical sibling"))?;
+            siblings.push(sibling);
+        }
+        Ok(Self { leaf_indices, siblings })
+    }
+}
+
+// Leaves must be passed in the same order of leaf_indices, which must be strictly increasing
+pub fn get_ginger_merkle_multi_path(leaves: &[FieldElement], leaf_indices: &[usize], tree: &GingerMerkleTree)
+    -> Result<GingerMerkleTreeMultiPath, Error>
+{
+    if leaves.len() != leaf_indices.len() {
+        Err(format!("Expected {} leaves, found {}", leaf_indices.len(), leaves.len()))?
+    }
+    let leaf_indices = leaf_indices.iter().map(|&index| index as u64).collect::<Vec<_>>();
+    check_ginger_merkle_multi_path_indices(leaf_indices.as_slice())?;
+
+    // Collect, for each level, the siblings of the nodes on the paths of the opened leaves
+    let mut known_siblings = vec![HashMap::new(); GINGER_MERKLE_TREE_LEVELS];
+    for (leaf, &leaf_index) in leaves.iter().zip(leaf_indices.iter()) {
+        let path = tree.generate_proof(leaf_index as usize, leaf)?;
+        for (level, &(sibling, _)) in path.path.iter().enumerate() {
+            known_siblings[level].insert((leaf_index >> level) ^ 1, sibling);
+        }
+    }
+
+    // Keep only the siblings which are not themselves on the path of an opened leaf
+    let mut siblings = Vec::new();
+    let mut indices = leaf_indices.clone();
+    for level in 0..GINGER_MERKLE_TREE_LEVELS {
+        let mut parent_indices = Vec::with_capacity(indices.len());
+        let mut i = 0;
+        while i < indices.len() {
+            let index = indices[i];
+            if index & 1 == 0 && i + 1 < indices.len() && indices[i + 1] == index + 1 {
+                i += 1;
+            } else {
+                let sibling = known_siblings[level].get(&(index ^ 1))
+                    .ok_or("Unable to find a sibling in the merkle tree")?;
+                siblings.push(*sibling);
+            }
+            parent_indices.push(index >> 1);
+            i += 1;
+        }
+        indices = parent_indices;
+    }
+
+    Ok(GingerMerkleTreeMultiPath { leaf_indices, siblings })
+}
+
+// Leaves must be passed in the same order of path.leaf_indices
+pub fn verify_ginger_merkle_multi_path(
+    path:        &GingerMerkleTreeMultiPath,
+    merkle_root: &FieldElement,
+    leaves:      &[FieldElement]
+) -> Result<bool, Error>
+{
+    if leaves.len() != path.leaf_indices.len() {
+        Err(format!("Expected {} leaves, found {}", path.leaf_indices.len(), leaves.len()))?
+    }
+    if check_ginger_merkle_multi_path_indices(path.leaf_indices.as_slice()).is_err() { return Ok(false) }
+
+    let mut siblings = path.siblings.iter();
+    let mut nodes = path.leaf_indices.iter().cloned().zip(leaves.iter().cloned()).collect::<Vec<_>>();
+    for _ in 0..GINGER_MERKLE_TREE_LEVELS {
+        let mut parents = Vec::with_capacity(nodes.len());
+        let mut i = 0;
+        while i < nodes.len() {
+            let (index, node) = nodes[i];
+            let parent = if index & 1 == 0 && i + 1 < nodes.len() && nodes[i + 1].0 == index + 1 {
+                i += 1;
+                hash_merkle_node(&node, &nodes[i].1)?
+            } else {
+                let sibling = match siblings.next() {
+                    Some(sibling) => sibling,
+                    None => return Ok(false),
+                };
+                if index & 1 == 0 {
+                    hash_merkle_node(&node, sibling)?
+                } else {
+                    hash_merkle_node(sibling, &node)?
+                }
+            };
+            parents.push((index >> 1, parent));
+            i += 1;
+        }
+        nodes = parents;
+    }
+
+    Ok(siblings.next().is_none() && nodes[0].1 == *merkle_root)
+}
+
+//************Incremental Merkle Tree functions******************
+
+// Append-only Poseidon Merkle tree. Unlike GingerMerkleTree, it doesn't need to be rebuilt from the
+// whole leaves set: leaves are appended one by one and the root is updated in O(height) hashes.
+// Empty leaves are represented by FieldElement::zero(), while an empty subtree of height h+1 is
+// H(empty_h, empty_h); the roots are therefore not interchangeable with the ones of GingerMerkleTree.
+
+pub const INCREMENTAL_MERKLE_TREE_MAX_HEIGHT: usize = 63;
+const INCREMENTAL_MERKLE_TREE_SERIALIZATION_VERSION: u8 = 1;
+
+pub(crate) fn hash_merkle_node(left: &FieldElement, right: &FieldElement) -> Result<FieldElement, Error> {
+    compute_poseidon_hash(&[*left, *right])
+}
+
+// Returns [empty_0, empty_1, ..., empty_height], being empty_i the root of an empty subtree of height i
+pub(crate) fn compute_empty_merkle_nodes(height: usize) -> Result<Vec<FieldElement>, Error> {
+    let mut empty_nodes = Vec::with_capacity(height + 1);
+    empty_nodes.push(FieldElement::zero());
+    for i in 0..height {
+        let node = hash_merkle_node(&empty_nodes[i], &empty_nodes[i])?;
+        empty_nodes.push(node);
+    }
+    Ok(empty_nodes)
+}
+
+// Recompute the root starting from `leaf`: the bits of leaf_index tell, at each level,
+// if the current node is a left (0) or a right (1) child.
+pub(crate) fn compute_merkle_root_from_path(
+    leaf:       &FieldElement,
+    leaf_index: u64,
+    siblings:   &[FieldElement],
+) -> Result<FieldElement, Error>
+{
+    let mut node = *leaf;
+    for (i, sibling) in siblings.iter().enumerate() {
+        node = if (leaf_index >> i) & 1 == 0 {
+            hash_merkle_node(&node, sibling)?
+        } else {
+            hash_merkle_node(sibling, &node)?
+        };
+    }
+    Ok(node)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GingerIncrementalMerkleTreePath {
+    pub leaf_index: u64,
+    pub siblings:   Vec<FieldElement>,
+}
+
+impl GingerIncrementalMerkleTreePath {
+
+    pub fn compute_root(&self, leaf: &FieldElement) -> Result<FieldElement, Error> {
+        compute_merkle_root_from_path(leaf, self.leaf_index, self.siblings.as_slice())
+    }
+
+    pub fn verify(&self, root: &FieldElement, leaf: &FieldElement) -> Result<bool, Error> {
+        if self.siblings.len() > INCREMENTAL_MERKLE_TREE_MAX_HEIGHT ||
+            self.leaf_index >> self.siblings.len() != 0 { return Ok(false) }
+        Ok(&self.compute_root(leaf)? == root)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GingerIncrementalMerkleTree {
+    height:       usize,
+    leaves_count: u64,
+
+    // frontier[i] is the root of the rightmost complete subtree of height i still waiting for its
+    // right sibling. It is set iff the i-th bit of leaves_count is set (frontier[height] is the
+    // root of the tree when it is full).
+    frontier:     Vec<Option<FieldElement>>,
+
+    // All the complete nodes of the tree, level by level. None in frontier-only (compact) mode.
+    nodes:        Option<Vec<Vec<FieldElement>>>,
+
+    empty_nodes:  Vec<FieldElement>,
+}
+
+impl GingerIncrementalMerkleTree {
+
+    // If `compact` is true, only the frontier is kept: root computation and appending are still
+    // supported, but Merkle paths can't be generated.
+    pub fn new(height: usize, compact: bool) -> Result<Self, Error> {
+        if height == 0 || height > INCREMENTAL_MERKLE_TREE_MAX_HEIGHT {
+            Err(format!("Unsupported incremental merkle tree height: {}", height))?
+        }
+        Ok(Self {
+            height,
+            leaves_count: 0,
+            frontier: vec![None; height + 1],
+            nodes: if compact { None } else { Some(vec![vec![]; height]) },
+            empty_nodes: compute_empty_merkle_nodes(height)?,
+        })
+    }
+
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn leaves_count(&self) -> u64 { self.leaves_count }
+
+    pub fn is_compact(&self) -> bool { self.nodes.is_none() }
+
+    pub fn capacity(&self) -> u64 { 1u64 << self.height }
+
+    pub fn append(&mut self, leaf: &FieldElement) -> Result<(), Error> {
+        if self.leaves_count == self.capacity() {
+            Err(format!("Incremental merkle tree is full: capacity {} reached", self.capacity()))?
+        }
+
+        let mut node = *leaf;
+        for i in 0..=self.height {
+            if let Some(ref mut nodes) = self.nodes {
+                if i < self.height { nodes[i].push(node); }
+            }
+            match self.frontier[i].take() {
+                // A complete left sibling was waiting: merge and go up
+                Some(left) => node = hash_merkle_node(&left, &node)?,
+                // This node becomes the rightmost complete subtree at level i
+                None => {
+                    self.frontier[i] = Some(node);
+                    break;
+                }
+            }
+        }
+        self.leaves_count += 1;
+        Ok(())
+    }
+
+    pub fn root(&self) -> Result<FieldElement, Error> {
+        if let Some(root) = self.frontier[self.height] { return Ok(root) }
+
+        // acc is the root of the non-empty rightmost subtree at the current level, if any
+        let mut acc: Option<FieldElement> = None;
+        for i in 0..self.height {
+            acc = match (self.frontier[i], acc) {
+                (Some(left), Some(right)) => Some(hash_merkle_node(&left, &right)?),
+                (Some(left), None) => Some(hash_merkle_node(&left, &self.empty_nodes[i])?),
+                (None, Some(left)) => Some(hash_merkle_node(&left, &self.empty_nodes[i])?),
+                (None, None) => None,
+            };
+        }
+        Ok(acc.unwrap_or(self.empty_nodes[self.height]))
+    }
+
+    // Value of the node at `level` and position `index`, being it complete, partially filled or empty
+    fn node(&self, nodes: &[Vec<FieldElement>], level: usize, index: u64) -> Result<FieldElement, Error> {
+        if index < nodes[level].len() as u64 {
+            Ok(nodes[level][index as usize])
+        } else if index << level >= self.leaves_count {
+            Ok(self.empty_nodes[level])
+        } else {
+            let left = self.node(nodes, level - 1, 2 * index)?;
+            let right = self.node(nodes, level - 1, 2 * index + 1)?;
+            hash_merkle_node(&left, &right)
+        }
+    }
+
+    pub fn path(&self, leaf_index: u64) -> Result<GingerIncrementalMerkleTreePath, Error> {
+        let nodes = match self.nodes {
+            Some(ref nodes) => nodes,
+            None => Err("Unable to generate a merkle path from a compact incremental merkle tree")?,
+        };
+        if leaf_index >= self.leaves_count {
+            Err(format!("Leaf index {} out of range: {} leaves appended", leaf_index, self.leaves_count))?
+        }
+
+        let mut siblings = Vec::with_capacity(self.height);
+        for i in 0..self.height {
+            siblings.push(self.node(nodes, i, (leaf_index >> i) ^ 1)?);
+        }
+        Ok(GingerIncrementalMerkleTreePath { leaf_index, siblings })
+    }
+
+    // Switch to frontier-only mode, dropping all the nodes not needed to append new leaves
+    pub fn compact(&mut self) {
+        self.nodes = None;
+    }
+
+    // Serialization format:
+    // version (u8) || height (u8) || compact (u8) || leaves_count (u64) || data, where data is
+    // the list of all the leaves in full mode and the frontier nodes (from the bottom) in compact mode.
+    pub fn serialize(&self) -> IoResult<Vec<u8>> {
+        let mut buffer = vec![];
+        INCREMENTAL_MERKLE_TREE_SERIALIZATION_VERSION.write(&mut buffer)?;
+        (self.height as u8).write(&mut buffer)?;
+        (self.is_compact() as u8).write(&mut buffer)?;
+        self.leaves_count.write(&mut buffer)?;
+        match self.nodes {
+            Some(ref nodes) => for leaf in nodes[0].iter() { leaf.write(&mut buffer)?; },
+            None => for node in self.frontier.iter().filter_map(|n| n.as_ref()) { node.write(&mut buffer)?; },
+        }
+        Ok(buffer)
+    }
+
+    pub fn deserialize(buffer: &[u8]) -> Result<Self, Error> {
+        let mut reader = buffer;
+        let version = u8::read(&mut reader)?;
+        if version != INCREMENTAL_MERKLE_TREE_SERIALIZATION_VERSION {
+            Err(format!("Unsupported incremental merkle tree serialization version: {}", version))?
+        }
+        let height = u8::read(&mut reader)? as usize;
+        let compact = match u8::read(&mut reader)? {
+            0 => false,
+            1 => true,
+            _ => Err("Invalid incremental merkle tree mode")?,
+        };
+        let leaves_count = u64::read(&mut reader)?;
+
+        let mut tree = Self::new(height, compact)?;
+        if leaves_count > tree.capacity() {
+            Err("Invalid incremental merkle tree leaves count")?
+        }
+
+        if compact {
+            for i in 0..=height {
+                if (leaves_count >> i) & 1 == 1 {
+                    tree.frontier[i] = Some(FieldElement::read(&mut reader)?);
+                }
+            }
+            tree.leaves_count = leaves_count;
+        } else {
+            for _ in 0..leaves_count {
+                let leaf = FieldElement::read(&mut reader)?;
+                tree.append(&leaf)?;
+            }
+        }
+
+        if !reader.is_empty() {
+            Err("Unexpected trailing bytes after incremental merkle tree")?
+        }
+        Ok(tree)
+    }
+}
+
+//************Variable height Merkle Tree functions******************
+
+// Dense Poseidon Merkle tree whose height is chosen at construction time, instead of being fixed
+// by FieldBasedMerkleTreeParams::HEIGHT. Roots and paths carry the height of the tree they come
+// from, and checking a path against a root of a different height is an error.
+
+pub const VARIABLE_HEIGHT_MERKLE_TREE_MIN_HEIGHT: usize = 1;
+pub const VARIABLE_HEIGHT_MERKLE_TREE_MAX_HEIGHT: usize = 32;
+
+pub const VARIABLE_HEIGHT_MERKLE_ROOT_SIZE: usize = 1 + FIELD_SIZE; // 97
+
+pub fn get_variable_height_merkle_path_size(height: usize) -> usize {
+    1 + 8 + height * FIELD_SIZE
+}
+
+fn check_variable_height_merkle_tree_height(height: usize) -> Result<(), Error> {
+    if height < VARIABLE_HEIGHT_MERKLE_TREE_MIN_HEIGHT || height > VARIABLE_HEIGHT_MERKLE_TREE_MAX_HEIGHT {
+        Err(format!(
+            "Unsupported merkle tree height {}: must be between {} and {}",
+            height, VARIABLE_HEIGHT_MERKLE_TREE_MIN_HEIGHT, VARIABLE_HEIGHT_MERKLE_TREE_MAX_HEIGHT
+        ))?
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GingerVariableHeightMerkleRoot {
+    pub height: usize,
+    pub value:  FieldElement,
+}
+
+impl ToBytes for GingerVariableHeightMerkleRoot {
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        (self.height as u8).write(&mut writer)?;
+        self.value.write(&mut writer)
+    }
+}
+
+impl FromBytes for GingerVariableHeightMerkleRoot {
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let height = u8::read(&mut reader)? as usize;
+        check_variable_height_merkle_tree_height(height)
+            .map_err(|e| IoError::new(ErrorKind::InvalidData, e.to_string()))?;
+        let value = FieldElement::read(&mut reader)?;
+        Ok(Self { height, value })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GingerVariableHeightMerklePath {
+    pub height:     usize,
+    pub leaf_index: u64,
+    pub siblings:   Vec<FieldElement>,
+}
+
+impl ToBytes for GingerVariableHeightMerklePath {
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        (self.height as u8).write(&mut writer)?;
+        self.leaf_index.write(&mut writer)?;
+        for sibling in self.siblings.iter() {
+            sibling.write(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromBytes for GingerVariableHeightMerklePath {
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let height = u8::read(&mut reader)? as usize;
+        check_variable_height_merkle_tree_height(height)
+            .map_err(|e| IoError::new(ErrorKind::InvalidData, e.to_string()))?;
+        let leaf_index = u64::read(&mut reader)?;
+        if leaf_index >> height != 0 {
+            return Err(IoError::new(ErrorKind::InvalidData, "leaf index out of range"))
+        }
+        let mut siblings = Vec::with_capacity(height);
+        for _ in 0..height {
+            siblings.push(FieldElement::read(&mut reader)?);
+        }
+        Ok(Self { height, leaf_index, siblings })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GingerVariableHeightMerkleTree {
+    tree: GingerIncrementalMerkleTree,
+}
+
+pub fn new_ginger_variable_height_merkle_tree(leaves: &[FieldElement], height: usize)
+    -> Result<GingerVariableHeightMerkleTree, Error>
+{
+    check_variable_height_merkle_tree_height(height)?;
+    if leaves.len() as u64 > 1u64 << height {
+        Err(format!("Too many leaves for a merkle tree of height {}: {}", height, leaves.len()))?
+    }
+
+    let mut tree = GingerIncrementalMerkleTree::new(height, false)?;
+    for leaf in leaves.iter() {
+        tree.append(leaf)?;
+    }
+    Ok(GingerVariableHeightMerkleTree { tree })
+}
+
+pub fn get_ginger_variable_height_merkle_root(tree: &GingerVariableHeightMerkleTree)
+    -> Result<GingerVariableHeightMerkleRoot, Error>
+{
+    Ok(GingerVariableHeightMerkleRoot { height: tree.tree.height(), value: tree.tree.root()? })
+}
+
+pub fn get_ginger_variable_height_merkle_path(tree: &GingerVariableHeightMerkleTree, leaf_index: usize)
+    -> Result<GingerVariableHeightMerklePath, Error>
+{
+    let path = tree.tree.path(leaf_index as u64)?;
+    Ok(GingerVariableHeightMerklePath {
+        height: tree.tree.height(),
+        leaf_index: path.leaf_index,
+        siblings: path.siblings,
+    })
+}
+
+pub fn verify_ginger_variable_height_merkle_path(
+    path:        &GingerVariableHeightMerklePath,
+    merkle_root: &GingerVariableHeightMerkleRoot,
+    leaf:        &FieldElement
+) -> Result<bool, Error>
+{
+    if path.height != merkle_root.height {
+        Err(format!(
+            "Merkle path of height {} can't be verified against a root of height {}",
+            path.height, merkle_root.height
+        ))?
+    }
+    if path.siblings.len() != path.height || path.leaf_index >> path.height != 0 { return Ok(false) }
+
+    let root = compute_merkle_root_from_path(leaf, path.leaf_index, path.siblings.as_slice())?;
+    Ok(root == merkle_root.value)
+}
+
+//************Sparse Merkle Tree functions******************
+
+// Poseidon-based key-value Sparse Merkle Tree. The position of a key is given by the `height` least
+// significant bits of H(key) in the SmtLeafPosition domain, so that positions can't be chosen by
+// picking keys, and the corresponding leaf is H(key, value) in the SmtLeaf domain, so that the
+// children of an internal node can't be passed off as a (key, value) pair. An empty leaf is
+// FieldElement::zero() and the empty subtrees are the same of the incremental tree. Since each
+// position can host a single key, inserting a key whose position is already taken by a different
+// key fails. Non-membership of a key is proven by opening its position, showing that the leaf is
+// either empty or occupied by a different key. Proofs are verified against the height of the tree,
+// never against the number of siblings they carry.
+
+pub const SPARSE_MERKLE_TREE_MAX_HEIGHT: usize = 64;
+
+pub fn get_smt_leaf_position(key: &FieldElement, height: usize) -> Result<u64, Error> {
+    let low_bits = domain_separated_hash(
+        HashDomain::SmtLeafPosition, DomainSeparationVersion::V1, &[*key]
+    )?.into_repr().0[0];
+    Ok(if height == 64 { low_bits } else { low_bits & ((1u64 << height) - 1) })
+}
+
+pub fn hash_smt_leaf(key: &FieldElement, value: &FieldElement) -> Result<FieldElement, Error> {
+    domain_separated_hash(HashDomain::SmtLeaf, DomainSeparationVersion::V1, &[*key, *value])
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GingerSmtMembershipProof {
+    pub siblings: Vec<FieldElement>,
+}
+
+impl GingerSmtMembershipProof {
+    pub fn verify(&self, root: &FieldElement, key: &FieldElement, value: &FieldElement, height: usize) -> Result<bool, Error> {
+        if height == 0 || height > SPARSE_MERKLE_TREE_MAX_HEIGHT {
+            Err(format!("Unsupported sparse merkle tree height: {}", height))?
+        }
+        if self.siblings.len() != height { return Ok(false) }
+
+        let leaf = hash_smt_leaf(key, value)?;
+        let position = get_smt_leaf_position(key, height)?;
+        Ok(&compute_merkle_root_from_path(&leaf, position, self.siblings.as_slice())? == root)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GingerSmtNonMembershipProof {
+    // (key, value) stored at the position of the absent key, if any
+    pub occupant: Option<(FieldElement, FieldElement)>,
+    pub siblings: Vec<FieldElement>,
+}
+
+impl GingerSmtNonMembershipProof {
+    pub fn verify(&self, root: &FieldElement, key: &FieldElement, height: usize) -> Result<bool, Error> {
+        if height == 0 || height > SPARSE_MERKLE_TREE_MAX_HEIGHT {
+            Err(format!("Unsupported sparse merkle tree height: {}", height))?
+        }
+        if self.siblings.len() != height { return Ok(false) }
+
+        let position = get_smt_leaf_position(key, height)?;
+        let leaf = match self.occupant {
+            Some((ref occupant_key, ref occupant_value)) => {
+                if occupant_key == key || get_smt_leaf_position(occupant_key, height)? != position {
+                    return Ok(false)
+                }
+                hash_smt_leaf(occupant_key, occupant_value)?
+            },
+            None => FieldElement::zero(),
+        };
+        Ok(&compute_merkle_root_from_path(&leaf, position, self.siblings.as_slice())? == root)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GingerSparseMerkleTree {
+    height:      usize,
+
+    // position -> (key, value)
+    leaves:      HashMap<u64, (FieldElement, FieldElement)>,
+
+    // (level, index) -> node, for the non-empty nodes only. Level 0 holds the leaves hashes.
+    nodes:       HashMap<(usize, u64), FieldElement>,
+
+    empty_nodes: Vec<FieldElement>,
+}
+
+impl GingerSparseMerkleTree {
+
+    pub fn new(height: usize) -> Result<Self, Error> {
+        if height == 0 || height > SPARSE_MERKLE_TREE_MAX_HEIGHT {
+            Err(format!("Unsupported sparse merkle tree height: {}", height))?
+        }
+        Ok(Self {
+            height,
+            leaves: HashMap::new(),
+            nodes: HashMap::new(),
+            empty_nodes: compute_empty_merkle_nodes(height)?,
+        })
+    }
+
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn len(&self) -> usize { self.leaves.len() }
+
+    pub fn is_empty(&self) -> bool { self.leaves.is_empty() }
+
+    pub fn root(&self) -> FieldElement {
+        self.get_node(self.height, 0)
+    }
+
+    pub fn get(&self, key: &FieldElement) -> Result<Option<FieldElement>, Error> {
+        match self.leaves.get(&get_smt_leaf_position(key, self.height)?) {
+            Some(&(ref k, v)) if k == key => Ok(Some(v)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn contains(&self, key: &FieldElement) -> Result<bool, Error> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    pub fn insert(&mut self, key: &FieldElement, value: &FieldElement) -> Result<(), Error> {
+        let position = get_smt_leaf_position(key, self.height)?;
+        if let Some(&(ref k, _)) = self.leaves.get(&position) {
+            if k == key {
+                Err("Key already present in the sparse merkle tree")?
+            } else {
+                Err(format!("Position {} already taken by a different key", position))?
+            }
+        }
+        self.set_leaf(position, Some((*key, *value)))
+    }
+
+    pub fn update(&mut self, key: &FieldElement, value: &FieldElement) -> Result<(), Error> {
+        if !self.contains(key)? {
+            Err("Key not present in the sparse merkle tree")?
+        }
+        self.set_leaf(get_smt_leaf_position(key, self.height)?, Some((*key, *value)))
+    }
+
+    pub fn remove(&mut self, key: &FieldElement) -> Result<(), Error> {
+        if !self.contains(key)? {
+            Err("Key not present in the sparse merkle tree")?
+        }
+        self.set_leaf(get_smt_leaf_position(key, self.height)?, None)
+    }
+
+    pub fn membership_proof(&self, key: &FieldElement) -> Result<GingerSmtMembershipProof, Error> {
+        if !self.contains(key)? {
+            Err("Key not present in the sparse merkle tree")?
+        }
+        let position = get_smt_leaf_position(key, self.height)?;
+        Ok(GingerSmtMembershipProof { siblings: self.get_siblings(position) })
+    }
+
+    pub fn non_membership_proof(&self, key: &FieldElement) -> Result<GingerSmtNonMembershipProof, Error> {
+        if self.contains(key)? {
+            Err("Key present in the sparse merkle tree")?
+        }
+        let position = get_smt_leaf_position(key, self.height)?;
+        Ok(GingerSmtNonMembershipProof {
+            occupant: self.leaves.get(&position).cloned(),
+            siblings: self.get_siblings(position),
+        })
+    }
+
+    fn get_node(&self, level: usize, index: u64) -> FieldElement {
+        match self.nodes.get(&(level, index)) {
+            Some(node) => *node,
+            None => self.empty_nodes[level],
+        }
+    }
+
+    fn get_siblings(&self, position: u64) -> Vec<FieldElement> {
+        (0..self.height).map(|i| self.get_node(i, (position >> i) ^ 1)).collect()
+    }
+
+    fn set_node(&mut self, level: usize, index: u64, node: FieldElement) {
+        if node == self.empty_nodes[level] {
+            self.nodes.remove(&(level, index));
+        } else {
+            self.nodes.insert((level, index), node);
+        }
+    }
+
+    // Set (or clear) the leaf at `position` and update all the nodes up to the root
+    fn set_leaf(&mut self, position: u64, leaf: Option<(FieldElement, FieldElement)>) -> Result<(), Error> {
+        let mut node = match leaf {
+            Some((key, value)) => {
+                self.leaves.insert(position, (key, value));
+                hash_smt_leaf(&key, &value)?
+            },
+            None => {
+                self.leaves.remove(&position);
+                self.empty_nodes[0]
+            },
+        };
+        self.set_node(0, position, node);
+
+        for i in 0..self.height {
+            let index = position >> i;
+            let sibling = self.get_node(i, index ^ 1);
+            node = if index & 1 == 0 {
+                hash_merkle_node(&node, &sibling)?
+            } else {
+                hash_merkle_node(&sibling, &node)?
+            };
+            self.set_node(i + 1, index >> 1, node);
+        }
+        Ok(())
+    }
+}
+
+//************Merkle Mountain Range functions******************
+
+// Append-only Poseidon Merkle Mountain Range. The leaves are covered by a list of perfect binary
+// trees (the peaks), one for each bit set in the leaves count, from the highest to the lowest.
+// Appending a leaf only merges the rightmost peaks of equal height, so the structure can grow
+// indefinitely. The root commits to the leaves count and to all the peaks: H(leaves_count, peaks).
+
+const MERKLE_MOUNTAIN_RANGE_SERIALIZATION_VERSION: u8 = 1;
+
+// Position in the peaks list, height and index within the peak of the leaf at `leaf_index`
+fn get_mmr_leaf_peak(leaf_index: u64, leaves_count: u64) -> Option<(usize, usize, u64)> {
+    if leaf_index >= leaves_count { return None }
+
+    let mut offset = 0u64;
+    let mut position = 0;
+    for height in (0..64).rev() {
+        if (leaves_count >> height) & 1 == 1 {
+            let peak_size = 1u64 << height;
+            if leaf_index < offset + peak_size {
+                return Some((position, height, leaf_index - offset))
+            }
+            offset += peak_size;
+            position += 1;
+        }
+    }
+    None
+}
+
+fn bag_mmr_peaks(leaves_count: u64, peaks: &[FieldElement]) -> Result<FieldElement, Error> {
+    let mut hasher = BufferedPoseidonHasher::new();
+    hasher.update(&read_field_element_from_u64(leaves_count));
+    peaks.iter().for_each(|peak| { hasher.update(peak); });
+    hasher.finalize()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GingerMerkleMountainRangeProof {
+    pub leaves_count: u64,
+    pub leaf_index:   u64,
+
+    // Siblings from the leaf up to (excluded) its peak
+    pub siblings:     Vec<FieldElement>,
+
+    // All the peaks of the range, except the one containing the leaf
+    pub other_peaks:  Vec<FieldElement>,
+}
+
+impl GingerMerkleMountainRangeProof {
+    pub fn compute_root(&self, leaf: &FieldElement) -> Result<FieldElement, Error> {
+        let (position, height, index) = get_mmr_leaf_peak(self.leaf_index, self.leaves_count)
+            .ok_or("Leaf index out of range")?;
+        if self.siblings.len() != height || self.other_peaks.len() != self.leaves_count.count_ones() as usize - 1 {
+            Err("Invalid merkle mountain range proof length")?
+        }
+
+        let peak = compute_merkle_root_from_path(leaf, index, self.siblings.as_slice())?;
+        let mut peaks = self.other_peaks.clone();
+        peaks.insert(position, peak);
+        bag_mmr_peaks(self.leaves_count, peaks.as_slice())
+    }
+
+    pub fn verify(&self, root: &FieldElement, leaf: &FieldElement) -> Result<bool, Error> {
+        match self.compute_root(leaf) {
+            Ok(computed_root) => Ok(&computed_root == root),
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+impl ToBytes for GingerMerkleMountainRangeProof {
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.leaves_count.write(&mut writer)?;
+        self.leaf_index.write(&mut writer)?;
+        (self.siblings.len() as u8).write(&mut writer)?;
+        for sibling in self.siblings.iter() {
+            sibling.write(&mut writer)?;
+        }
+        (self.other_peaks.len() as u8).write(&mut writer)?;
+        for peak in self.other_peaks.iter() {
+            peak.write(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromBytes for GingerMerkleMountainRangeProof {
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let leaves_count = u64::read(&mut reader)?;
+        let leaf_index = u64::read(&mut reader)?;
+        let (_, height, _) = get_mmr_leaf_peak(leaf_index, leaves_count)
+            .ok_or(IoError::new(ErrorKind::InvalidData, "leaf index out of range"))?;
+
+        let siblings_len = u8::read(&mut reader)? as usize;
+        if siblings_len != height {
+            return Err(IoError::new(ErrorKind::InvalidData, "invalid number of siblings"))
+        }
+        let mut siblings = Vec::with_capacity(siblings_len);
+        for _ in 0..siblings_len {
+            siblings.push(FieldElement::read(&mut reader)?);
+        }
+
+        let other_peaks_len = u8::read(&mut reader)? as usize;
+        if other_peaks_len != leaves_count.count_ones() as usize - 1 {
+            return Err(IoError::new(ErrorKind::InvalidData, "invalid number of peaks"))
+        }
+        let mut other_peaks = Vec::with_capacity(other_peaks_len);
+        for _ in 0..other_peaks_len {
+            other_peaks.push(FieldElement::read(&mut reader)?);
+        }
+
+        Ok(Self { leaves_count, leaf_index, siblings, other_peaks })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GingerMerkleMountainRange {
+    leaves_count: u64,
+
+    // All the nodes of the range, level by level: nodes[i][j] is the root of the perfect
+    // subtree of height i covering the leaves [j * 2^i, (j + 1) * 2^i).
+    nodes:        Vec<Vec<FieldElement>>,
+}
+
+impl GingerMerkleMountainRange {
+    pub fn new() -> Self {
+        Self { leaves_count: 0, nodes: vec![] }
+    }
+
+    pub fn leaves_count(&self) -> u64 { self.leaves_count }
+
+    pub fn append(&mut self, leaf: &FieldElement) -> Result<(), Error> {
+        let mut node = *leaf;
+        let mut level = 0;
+        loop {
+            if self.nodes.len() == level { self.nodes.push(vec![]); }
+            self.nodes[level].push(node);
+
+            // A right child completes a subtree of height level + 1: merge and go up
+            let len = self.nodes[level].len();
+            if len % 2 == 1 { break }
+            node = hash_merkle_node(&self.nodes[level][len - 2], &self.nodes[level][len - 1])?;
+            level += 1;
+        }
+        self.leaves_count += 1;
+        Ok(())
+    }
+
+    // Roots of the perfect subtrees covering the leaves, from the highest to the lowest
+    pub fn peaks(&self) -> Vec<FieldElement> {
+        (0..self.nodes.len()).rev()
+            .filter(|&height| (self.leaves_count >> height) & 1 == 1)
+            .map(|height| self.nodes[height][((self.leaves_count >> height) - 1) as usize])
+            .collect()
+    }
+
+    pub fn root(&self) -> Result<FieldElement, Error> {
+        bag_mmr_peaks(self.leaves_count, self.peaks().as_slice())
+    }
+
+    pub fn proof(&self, leaf_index: u64) -> Result<GingerMerkleMountainRangeProof, Error> {
+        let (position, height, _) = get_mmr_leaf_peak(leaf_index, self.leaves_count)
+            .ok_or(format!("Leaf index {} out of range: {} leaves appended", leaf_index, self.leaves_count))?;
+
+        let siblings = (0..height)
+            .map(|level| self.nodes[level][((leaf_index >> level) ^ 1) as usize])
+            .collect();
+
+        let mut other_peaks = self.peaks();
+        other_peaks.remove(position);
+
+        Ok(GingerMerkleMountainRangeProof { leaves_count: self.leaves_count, leaf_index, siblings, other_peaks })
+    }
+
+    // Serialization format: version (u8) || leaves_count (u64) || leaves
+    pub fn serialize(&self) -> IoResult<Vec<u8>> {
+        let mut buffer = vec![];
+        MERKLE_MOUNTAIN_RANGE_SERIALIZATION_VERSION.write(&mut buffer)?;
+        self.leaves_count.write(&mut buffer)?;
+        if let Some(leaves) = self.nodes.first() {
+            for leaf in leaves.iter() { leaf.write(&mut buffer)?; }
+        }
+        Ok(buffer)
+    }
+
+    pub fn deserialize(buffer: &[u8]) -> Result<Self, Error> {
+        let mut reader = buffer;
+        let version = u8::read(&mut reader)?;
+        if version != MERKLE_MOUNTAIN_RANGE_SERIALIZATION_VERSION {
+            Err(format!("Unsupported merkle mountain range serialization version: {}", version))?
+        }
+        let leaves_count = u64::read(&mut reader)?;
+        if leaves_count > (reader.len() / FIELD_SIZE) as u64 {
+            Err("Invalid merkle mountain range leaves count")?
+        }
+
+        let mut mmr = Self::new();
+        for _ in 0..leaves_count {
+            let leaf = FieldElement::read(&mut reader)?;
+            mmr.append(&leaf)?;
+        }
+
+        if !reader.is_empty() {
+            Err("Unexpected trailing bytes after merkle mountain range")?
+        }
+        Ok(mmr)
+    }
+
+    // Like deserialize, but also fails unless buffer is the canonical serialization of the range.
+    pub fn deserialize_checked(buffer: &[u8]) -> Result<Self, DeserializationError> {
+        let mmr = Self::deserialize(buffer).map_err(|_| DeserializationError::NonCanonical)?;
+        check_canonical_serialization(buffer, mmr.serialize())?;
+        Ok(mmr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::RngCore;
+
+    fn write_to_file<T: ToBytes>(to_write: &T, file_path: &str) -> IoResult<()>{
+        let mut fs = File::create(file_path)?;
+        to_write.write(&mut fs)?;
         Ok(())
     }
 
@@ -418,7 +2810,7 @@ mod test {
         unsafe { Vec::from_raw_parts(p as *mut i8, len, cap) }
     }
 
-    fn create_sample_naive_threshold_sig_circuit(bt_num: usize) {
+    fn create_sample_naive_threshold_sig_circuit(bt_num: usize, version: DomainSeparationVersion) {
         //assume to have 3 pks, threshold = 2
         let mut rng = OsRng;
 
@@ -430,120 +2822,728 @@ mod test {
         let end_epoch_mc_b_hash_f = read_field_element_from_buffer_with_padding(&end_epoch_mc_b_hash[..]).unwrap();
         let prev_end_epoch_mc_b_hash_f = read_field_element_from_buffer_with_padding(&prev_end_epoch_mc_b_hash[..]).unwrap();
 
-        let mut bt_list = vec![];
-        for _ in 0..bt_num {
-            bt_list.push(BackwardTransfer::default());
+        let mut bt_list = vec![];
+        for _ in 0..bt_num {
+            bt_list.push(BackwardTransfer::default());
+        }
+
+        //Compute msg to sign
+        let (_, msg) = compute_msg_to_sign(
+            &end_epoch_mc_b_hash_f,
+            &prev_end_epoch_mc_b_hash_f,
+            bt_list.as_slice(),
+            version,
+        ).unwrap();
+
+        //Generate params and write them to file
+        let params = generate_parameters(3, version).unwrap();
+        let proving_key_path = "./sample_proving_key";
+        write_to_file(&params, proving_key_path).unwrap();
+
+        let verifying_key_path = "./sample_vk";
+        write_to_file(&(params.vk), verifying_key_path).unwrap();
+
+        //Generate sample pks and sigs vec
+        let threshold: u64 = 2;
+        let mut pks = vec![];
+        let mut sks = vec![];
+        for _ in 0..3 {
+            let keypair = schnorr_generate_key();
+            pks.push(keypair.0);
+            sks.push(keypair.1);
+        }
+
+        let mut sigs = vec![];
+        sigs.push(Some(schnorr_sign(&msg, &sks[0], &pks[0]).unwrap()));
+        sigs.push(None);
+        sigs.push(Some(schnorr_sign(&msg, &sks[2], &pks[2]).unwrap()));
+
+        let constant = compute_pks_threshold_hash(pks.as_slice(), threshold, version).unwrap();
+
+        //Create and serialize proof
+        let (proof, quality) = create_naive_threshold_sig_proof(
+            pks.as_slice(),
+            sigs,
+            &end_epoch_mc_b_hash,
+            &prev_end_epoch_mc_b_hash,
+            bt_list.as_slice(),
+            threshold,
+            proving_key_path,
+            version,
+        ).unwrap();
+        let proof_path = "./sample_proof";
+        write_to_file(&proof, proof_path).unwrap();
+
+        //Verify proof
+        assert!(verify_naive_threshold_sig_proof(
+            &constant,
+            &end_epoch_mc_b_hash,
+            &prev_end_epoch_mc_b_hash,
+            bt_list.as_slice(),
+            quality,
+            &proof,
+            "./sample_vk",
+            version,
+        ).unwrap());
+
+
+        //Generate wrong public inputs by changing quality and assert proof verification doesn't pass
+        assert!(!verify_naive_threshold_sig_proof(
+            &constant,
+            &end_epoch_mc_b_hash,
+            &prev_end_epoch_mc_b_hash,
+            bt_list.as_slice(),
+            quality - 1,
+            &proof,
+            "./sample_vk",
+            version,
+        ).unwrap());
+    }
+
+    #[test]
+    fn naive_threshold_sig_circuit_test() {
+        create_sample_naive_threshold_sig_circuit(10, CURRENT_DOMAIN_SEPARATION_VERSION);
+        create_sample_naive_threshold_sig_circuit(0, CURRENT_DOMAIN_SEPARATION_VERSION);
+
+        //Legacy constants and proofs remain verifiable
+        create_sample_naive_threshold_sig_circuit(10, DomainSeparationVersion::V0);
+    }
+
+    #[test]
+    fn sample_schnorr_sig_parallel_verify(){
+        let mut rng = OsRng;
+        let mut items = vec![];
+        for i in 0..20 {
+            let msg = if i % 2 == 0 { FieldElement::rand(&mut rng) } else { FieldElement::one() };
+            let (pk, sk) = schnorr_generate_key();
+            let sig = schnorr_sign(&msg, &sk, &pk).unwrap();
+            items.push((pk, msg, sig));
+        }
+
+        assert_eq!(schnorr_verify_signatures_per_item(items.as_slice()).unwrap(), vec![true; 20]);
+        assert!(schnorr_verify_signatures_all(items.as_slice()).unwrap());
+
+        //Negative case: some items with the wrong message
+        items[3].1 = FieldElement::rand(&mut rng);
+        items[17].1 = FieldElement::rand(&mut rng);
+        let verdicts = schnorr_verify_signatures_per_item(items.as_slice()).unwrap();
+        for (i, verdict) in verdicts.iter().enumerate() {
+            assert_eq!(*verdict, i != 3 && i != 17);
+        }
+        assert!(!schnorr_verify_signatures_all(items.as_slice()).unwrap());
+
+        //No items
+        assert!(schnorr_verify_signatures_per_item(&[]).unwrap().is_empty());
+        assert!(schnorr_verify_signatures_all(&[]).unwrap());
+    }
+
+    #[test]
+    fn sample_schnorr_sig_prove_verify(){
+        let mut rng = OsRng;
+        let msg = FieldElement::rand(&mut rng);
+
+        let (pk, sk) = schnorr_generate_key(); //Keygen
+        assert_eq!(schnorr_get_public_key(&sk), pk); //Get pk
+        assert!(schnorr_verify_public_key(&pk)); //Verify pk
+
+        //Serialize/deserialize pk
+        let mut pk_serialized = vec![0u8; SCHNORR_PK_SIZE];
+        serialize_to_buffer(&pk, &mut pk_serialized).unwrap();
+        let pk_deserialized = deserialize_from_buffer(&pk_serialized).unwrap();
+        assert_eq!(pk, pk_deserialized);
+
+        //Serialize/deserialize sk
+        let mut sk_serialized = vec![0u8; SCHNORR_SK_SIZE];
+        serialize_to_buffer(&sk, &mut sk_serialized).unwrap();
+        let sk_deserialized = deserialize_from_buffer(&sk_serialized).unwrap();
+        assert_eq!(sk, sk_deserialized);
+
+        let sig = schnorr_sign(&msg, &sk, &pk).unwrap(); //Sign msg
+
+        //Serialize/deserialize sig
+        let mut sig_serialized = vec![0u8; SCHNORR_SIG_SIZE];
+        serialize_to_buffer(&sig, &mut sig_serialized).unwrap();
+        let sig_deserialized = deserialize_from_buffer(&sig_serialized).unwrap();
+        assert_eq!(sig, sig_deserialized);
+
+        assert!(schnorr_verify_signature(&msg, &pk, &sig).unwrap()); //Verify sig
+
+        //Negative case
+        let wrong_msg = FieldElement::rand(&mut rng);
+        assert!(!schnorr_verify_signature(&wrong_msg, &pk, &sig).unwrap());
+    }
+
+    #[test]
+    fn sample_schnorr_sig_deterministic_nonce(){
+        let mut rng = OsRng;
+        let msg = FieldElement::rand(&mut rng);
+        let (pk, sk) = schnorr_generate_key();
+
+        //Deterministic signatures are valid and reproducible
+        let sig = schnorr_sign_deterministic(&msg, &sk, &pk, None).unwrap();
+        assert!(schnorr_verify_signature(&msg, &pk, &sig).unwrap());
+        assert_eq!(sig, schnorr_sign_deterministic(&msg, &sk, &pk, None).unwrap());
+
+        //A different message or key yields a different signature
+        let other_msg = FieldElement::rand(&mut rng);
+        let other_sig = schnorr_sign_deterministic(&other_msg, &sk, &pk, None).unwrap();
+        assert!(schnorr_verify_signature(&other_msg, &pk, &other_sig).unwrap());
+        assert_ne!(sig, other_sig);
+
+        let (other_pk, other_sk) = schnorr_generate_key();
+        assert_ne!(sig, schnorr_sign_deterministic(&msg, &other_sk, &other_pk, None).unwrap());
+
+        //Extra randomness changes the nonce, but the signature stays valid
+        let hedged_sig = schnorr_sign_deterministic(&msg, &sk, &pk, Some(b"extra randomness")).unwrap();
+        assert!(schnorr_verify_signature(&msg, &pk, &hedged_sig).unwrap());
+        assert_ne!(sig, hedged_sig);
+        assert_ne!(hedged_sig, schnorr_sign_deterministic(&msg, &sk, &pk, Some(b"other randomness")).unwrap());
+        assert_ne!(sig, schnorr_sign_deterministic(&msg, &sk, &pk, Some(&[])).unwrap());
+    }
+
+    #[test]
+    fn sample_schnorr_sig_multi_element_and_bytes(){
+        let mut rng = OsRng;
+        let (pk, sk) = schnorr_generate_key();
+
+        //Multi-element messages
+        let msg: Vec<FieldElement> = (0..5).map(|_| FieldElement::rand(&mut rng)).collect();
+        let sig = schnorr_sign_field_elements(msg.as_slice(), &sk, &pk).unwrap();
+        assert!(schnorr_verify_signature_field_elements(msg.as_slice(), &pk, &sig).unwrap());
+        assert!(!schnorr_verify_signature_field_elements(&msg[..4], &pk, &sig).unwrap());
+
+        let mut wrong_msg = msg.clone();
+        wrong_msg.swap(0, 1);
+        assert!(!schnorr_verify_signature_field_elements(wrong_msg.as_slice(), &pk, &sig).unwrap());
+
+        //Single element messages are the same as the single-element functions
+        let sig = schnorr_sign_field_elements(&msg[..1], &sk, &pk).unwrap();
+        assert!(schnorr_verify_signature(&msg[0], &pk, &sig).unwrap());
+        assert_eq!(
+            schnorr_sign_deterministic(&msg[0], &sk, &pk, None).unwrap(),
+            schnorr_sign_field_elements_deterministic(&msg[..1], &sk, &pk, None).unwrap()
+        );
+
+        //Empty messages are rejected
+        assert!(schnorr_sign_field_elements(&[], &sk, &pk).is_err());
+        assert!(schnorr_sign_field_elements_deterministic(&[], &sk, &pk, None).is_err());
+        assert!(schnorr_verify_signature_field_elements(&[], &pk, &sig).is_err());
+
+        //Byte messages
+        let mut msg_bytes = vec![0u8; 300];
+        rng.fill_bytes(&mut msg_bytes);
+        let sig = schnorr_sign_bytes(msg_bytes.as_slice(), &sk, &pk).unwrap();
+        assert!(schnorr_verify_signature_bytes(msg_bytes.as_slice(), &pk, &sig).unwrap());
+
+        //Byte messages are kept apart from field element messages
+        let encoded_msg = schnorr_encode_bytes_message(msg_bytes.as_slice()).unwrap();
+        assert!(!schnorr_verify_signature_field_elements(&encoded_msg[1..], &pk, &sig).unwrap());
+        assert!(!schnorr_verify_signature_field_elements(encoded_msg.as_slice(), &pk, &sig).unwrap());
+        assert!(schnorr_sign_field_elements(encoded_msg.as_slice(), &sk, &pk).is_err());
+        let field_elements_sig = schnorr_sign_field_elements(&encoded_msg[1..], &sk, &pk).unwrap();
+        assert!(!schnorr_verify_signature_bytes(msg_bytes.as_slice(), &pk, &field_elements_sig).unwrap());
+
+        //Trailing zeros are not lost by the encoding
+        let mut padded_msg_bytes = msg_bytes.clone();
+        padded_msg_bytes.push(0u8);
+        assert!(!schnorr_verify_signature_bytes(padded_msg_bytes.as_slice(), &pk, &sig).unwrap());
+
+        //Empty byte messages can be signed
+        let sig = schnorr_sign_bytes(&[], &sk, &pk).unwrap();
+        assert!(schnorr_verify_signature_bytes(&[], &pk, &sig).unwrap());
+        assert!(!schnorr_verify_signature_bytes(&[0u8], &pk, &sig).unwrap());
+        assert!(!schnorr_verify_signature(&FieldElement::zero(), &pk, &sig).unwrap());
+
+        //Deterministic byte signatures
+        let sig = schnorr_sign_bytes_deterministic(msg_bytes.as_slice(), &sk, &pk, None).unwrap();
+        assert!(schnorr_verify_signature_bytes(msg_bytes.as_slice(), &pk, &sig).unwrap());
+        assert_eq!(sig, schnorr_sign_bytes_deterministic(msg_bytes.as_slice(), &sk, &pk, None).unwrap());
+    }
+
+    #[test]
+    fn sample_hd_key_derivation(){
+        let seed = (0u8..32).collect::<Vec<_>>();
+
+        //Paths are parsed as expected
+        let h = HD_HARDENED_KEY_OFFSET;
+        assert_eq!(parse_hd_path("m").unwrap(), Vec::<u32>::new());
+        assert_eq!(parse_hd_path("m/44'/0h/1/2147483647").unwrap(), vec![44 + h, h, 1, h - 1]);
+        for &wrong_path in ["", "n/0", "m/", "m/-1", "m/+1", "m/1''", "m/2147483648", "m/0/x"].iter() {
+            assert!(parse_hd_path(wrong_path).is_err(), "{} must not be parsed", wrong_path);
+        }
+
+        //Seed length is enforced
+        assert!(HDExtendedSecretKey::master(HDKeyType::Schnorr, &seed[..HD_SEED_MIN_LENGTH - 1]).is_err());
+        assert!(HDExtendedSecretKey::master(HDKeyType::Schnorr, &vec![0u8; HD_SEED_MAX_LENGTH + 1]).is_err());
+
+        //Derivation is deterministic, and keys are valid
+        let path = parse_hd_path("m/0'/1/2'").unwrap();
+        let (pk, sk) = schnorr_derive_key(seed.as_slice(), path.as_slice()).unwrap();
+        assert_eq!((pk, sk), schnorr_derive_key(seed.as_slice(), path.as_slice()).unwrap());
+        assert_eq!(schnorr_get_public_key(&sk), pk);
+        assert!(schnorr_verify_public_key(&pk));
+        let msg = get_random_field_element();
+        assert!(schnorr_verify_signature(&msg, &pk, &schnorr_sign(&msg, &sk, &pk).unwrap()).unwrap());
+
+        let (vrf_pk, vrf_sk) = vrf_derive_key(seed.as_slice(), path.as_slice()).unwrap();
+        assert_eq!(vrf_get_public_key(&vrf_sk), vrf_pk);
+        let (vrf_proof, vrf_out) = vrf_prove(&msg, &vrf_sk, &vrf_pk).unwrap();
+        assert_eq!(vrf_proof_to_hash(&msg, &vrf_pk, &vrf_proof).unwrap(), vrf_out);
+
+        //Key types, seeds, indices and hardening all lead to different keys
+        assert_ne!(sk, vrf_sk);
+        let mut other_seed = seed.clone();
+        other_seed[0] ^= 1;
+        assert_ne!(sk, schnorr_derive_key(other_seed.as_slice(), path.as_slice()).unwrap().1);
+
+        let master = HDExtendedSecretKey::master(HDKeyType::Schnorr, seed.as_slice()).unwrap();
+        let child = master.derive_child(0).unwrap();
+        let hardened_child = master.derive_child(h).unwrap();
+        assert_ne!(child.secret_key, hardened_child.secret_key);
+        assert_ne!(child.chain_code, hardened_child.chain_code);
+        assert_ne!(child.secret_key, master.derive_child(1).unwrap().secret_key);
+        assert_eq!((child.depth, child.child_index), (1, 0));
+        assert_eq!((hardened_child.depth, hardened_child.child_index), (1, h));
+
+        //Paths are derived one index at a time
+        assert_eq!(master.derive_path(path.as_slice()).unwrap().secret_key, sk);
+        assert_eq!(
+            master.derive_child(path[0]).unwrap().derive_path(&path[1..]).unwrap(),
+            master.derive_path(path.as_slice()).unwrap()
+        );
+
+        //Non-hardened children public keys can be derived from the parent extended public key
+        let xpk = master.to_extended_public_key();
+        let child_xpk = xpk.derive_child(0).unwrap();
+        assert_eq!(child_xpk, child.to_extended_public_key());
+        assert_eq!(child_xpk.derive_child(7).unwrap(), child.derive_child(7).unwrap().to_extended_public_key());
+        assert!(xpk.derive_child(h).is_err());
+    }
+
+    // Prints the test vectors published in doc/HDKeyDerivation.md.
+    // Run with: cargo test -p api -- --ignored print_hd_key_derivation_test_vectors --nocapture
+    #[ignore]
+    #[test]
+    fn print_hd_key_derivation_test_vectors(){
+        fn bytes_to_hex(bytes: &[u8]) -> String {
+            bytes.iter().map(|b| format!("{:02x}", b)).collect()
+        }
+
+        fn to_hex<T: ToBytes>(t: &T) -> String {
+            let mut bytes = vec![];
+            t.write(&mut bytes).unwrap();
+            bytes_to_hex(bytes.as_slice())
+        }
+
+        let seeds = vec![
+            (0u8..16).collect::<Vec<_>>(),
+            (0u8..64).map(|i| 0xff - i).collect::<Vec<_>>(),
+        ];
+        let paths = ["m", "m/0'", "m/0'/1", "m/0'/1/2'", "m/44'/0'/0'/0/2147483647"];
+
+        for &key_type in [HDKeyType::Schnorr, HDKeyType::Vrf].iter() {
+            for seed in seeds.iter() {
+                let master = HDExtendedSecretKey::master(key_type, seed.as_slice()).unwrap();
+                println!("key type: {:?}, seed: {}", key_type, bytes_to_hex(seed.as_slice()));
+                for &path in paths.iter() {
+                    let key = master.derive_path(parse_hd_path(path).unwrap().as_slice()).unwrap();
+                    println!("  path: {}", path);
+                    println!("    sk: {}", to_hex(&key.secret_key));
+                    println!("    pk: {}", to_hex(&key.public_key()));
+                    println!("    chain code: {}", to_hex(&key.chain_code));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sample_keystore_encrypt_decrypt(){
+        //Cheap KDF parameters, to keep the test fast
+        let params = KeystoreKdfParams { log_n: 10, r: 8, p: 1 };
+        let password = b"correct horse battery staple";
+        let expect_error = |result: Result<SecretScalar, Error>, expected: KeystoreError| {
+            let e = result.unwrap_err();
+            assert_eq!(e.downcast_ref::<KeystoreError>(), Some(&expected));
+        };
+
+        let (_, sk) = schnorr_generate_key();
+        let keystore = encrypt_secret_key_with_params(&sk, HDKeyType::Schnorr, password, &params).unwrap();
+        assert_eq!(keystore.len(), KEYSTORE_SIZE);
+        assert_eq!(decrypt_secret_key(keystore.as_slice(), HDKeyType::Schnorr, password).unwrap(), sk);
+
+        //Encryption is randomized
+        let other_keystore = encrypt_secret_key_with_params(&sk, HDKeyType::Schnorr, password, &params).unwrap();
+        assert_ne!(keystore, other_keystore);
+        assert_eq!(decrypt_secret_key(other_keystore.as_slice(), HDKeyType::Schnorr, password).unwrap(), sk);
+
+        //Wrong password
+        expect_error(decrypt_secret_key(keystore.as_slice(), HDKeyType::Schnorr, b"wrong password"), KeystoreError::WrongPassword);
+        expect_error(decrypt_secret_key(keystore.as_slice(), HDKeyType::Schnorr, b""), KeystoreError::WrongPassword);
+
+        //Wrong key type
+        expect_error(decrypt_secret_key(keystore.as_slice(), HDKeyType::Vrf, password), KeystoreError::WrongKeyType);
+
+        //Any corrupted byte is detected, truncated and extended keystores too
+        for i in 0..KEYSTORE_SIZE {
+            let mut corrupted = keystore.clone();
+            corrupted[i] ^= 0x01;
+            expect_error(decrypt_secret_key(corrupted.as_slice(), HDKeyType::Schnorr, password), KeystoreError::Corrupted);
+        }
+        expect_error(decrypt_secret_key(&keystore[..KEYSTORE_SIZE - 1], HDKeyType::Schnorr, password), KeystoreError::Corrupted);
+        let mut extended = keystore.clone();
+        extended.push(0u8);
+        expect_error(decrypt_secret_key(extended.as_slice(), HDKeyType::Schnorr, password), KeystoreError::Corrupted);
+
+        //Tampering with the header is detected by the authentication, even if the checksum is fixed
+        let mut tampered = keystore.clone();
+        tampered[KEYSTORE_HEADER_SIZE - 1] ^= 0x01;
+        let checksum = keystore_checksum(&tampered[..KEYSTORE_SIZE - KEYSTORE_CHECKSUM_SIZE]);
+        tampered[KEYSTORE_SIZE - KEYSTORE_CHECKSUM_SIZE..].copy_from_slice(&checksum);
+        expect_error(decrypt_secret_key(tampered.as_slice(), HDKeyType::Schnorr, password), KeystoreError::WrongPassword);
+
+        //Out of bounds KDF parameters are rejected
+        assert!(encrypt_secret_key_with_params(&sk, HDKeyType::Schnorr, password, &KeystoreKdfParams { log_n: 30, r: 8, p: 1 }).is_err());
+        assert!(KeystoreKdfParams::default().is_within_bounds());
+        assert!(KeystoreKdfParams { log_n: 18, r: 8, p: KEYSTORE_KDF_MAX_P }.is_within_bounds()); // 256 MiB
+        assert!(!KeystoreKdfParams { log_n: 19, r: 8, p: 1 }.is_within_bounds()); // 512 MiB
+        assert!(!KeystoreKdfParams { log_n: 18, r: 9, p: 1 }.is_within_bounds());
+        assert!(!KeystoreKdfParams { log_n: 10, r: 8, p: KEYSTORE_KDF_MAX_P + 1 }.is_within_bounds());
+        assert!(!KeystoreKdfParams { log_n: 63, r: u32::max_value(), p: 1 }.is_within_bounds());
+
+        //A keystore asking for 16 GiB (log_n = 22, r = 32) is rejected as corrupted before running the KDF,
+        //even with a valid checksum
+        let mut over_budget = keystore.clone();
+        over_budget[2] = 22;
+        over_budget[3..7].copy_from_slice(&32u32.to_le_bytes());
+        let checksum = keystore_checksum(&over_budget[..KEYSTORE_SIZE - KEYSTORE_CHECKSUM_SIZE]);
+        over_budget[KEYSTORE_SIZE - KEYSTORE_CHECKSUM_SIZE..].copy_from_slice(&checksum);
+        expect_error(decrypt_secret_key(over_budget.as_slice(), HDKeyType::Schnorr, password), KeystoreError::Corrupted);
+
+        //VRF keys, with the default parameters
+        let (_, vrf_sk) = vrf_generate_key();
+        let vrf_keystore = vrf_encrypt_secret_key(&vrf_sk, password).unwrap();
+        assert_eq!(vrf_decrypt_secret_key(vrf_keystore.as_slice(), password).unwrap(), vrf_sk);
+        expect_error(schnorr_decrypt_secret_key(vrf_keystore.as_slice(), password), KeystoreError::WrongKeyType);
+    }
+
+    #[test]
+    fn sample_compressed_points(){
+        let mut rng = OsRng;
+
+        //Schnorr and VRF public keys
+        for _ in 0..10 {
+            let (pk, _) = schnorr_generate_key();
+            let pk_bytes = serialize_schnorr_pk_compressed(&pk).unwrap();
+            assert_eq!(pk_bytes.len(), SCHNORR_PK_COMPRESSED_SIZE);
+            assert_eq!(deserialize_schnorr_pk_compressed(pk_bytes.as_slice()).unwrap(), pk);
+
+            //The opposite point differs only in the y flag
+            let neg_pk_bytes = serialize_schnorr_pk_compressed(&-pk).unwrap();
+            assert_eq!(pk_bytes[..SCHNORR_PK_COMPRESSED_SIZE - 1], neg_pk_bytes[..SCHNORR_PK_COMPRESSED_SIZE - 1]);
+            assert_eq!(pk_bytes[SCHNORR_PK_COMPRESSED_SIZE - 1] ^ COMPRESSED_GREATEST_Y_FLAG, neg_pk_bytes[SCHNORR_PK_COMPRESSED_SIZE - 1]);
+            assert_eq!(deserialize_schnorr_pk_compressed(neg_pk_bytes.as_slice()).unwrap(), -pk);
+        }
+
+        //The point at infinity has an encoding, but it's not a valid public key
+        let zero_bytes = serialize_schnorr_pk_compressed(&SchnorrPk::zero()).unwrap();
+        assert_eq!(SchnorrPk::from_compressed_bytes(zero_bytes.as_slice()).unwrap(), SchnorrPk::zero());
+        assert_eq!(
+            deserialize_schnorr_pk_compressed(zero_bytes.as_slice()).unwrap_err().downcast_ref::<DeserializationError>(),
+            Some(&DeserializationError::Zero)
+        );
+
+        //Invalid encodings
+        let (pk, _) = vrf_generate_key();
+        let pk_bytes = serialize_vrf_pk_compressed(&pk).unwrap();
+        assert!(deserialize_vrf_pk_compressed(&pk_bytes[..VRF_PK_COMPRESSED_SIZE - 1]).is_err());
+
+        let mut wrong_zero_bytes = zero_bytes.clone();
+        wrong_zero_bytes[0] = 1;
+        assert!(deserialize_vrf_pk_compressed(wrong_zero_bytes.as_slice()).is_err());
+
+        let mut non_reduced_bytes = vec![0xffu8; VRF_PK_COMPRESSED_SIZE];
+        non_reduced_bytes[VRF_PK_COMPRESSED_SIZE - 1] &= !(COMPRESSED_INFINITY_FLAG | COMPRESSED_GREATEST_Y_FLAG);
+        assert!(deserialize_vrf_pk_compressed(non_reduced_bytes.as_slice()).is_err());
+
+        //About half of the x coordinates are not on the curve
+        let mut rejected = 0;
+        for _ in 0..20 {
+            let mut x_bytes = vec![0u8; VRF_PK_COMPRESSED_SIZE];
+            FieldElement::rand(&mut rng).write(&mut x_bytes[..]).unwrap();
+            if deserialize_vrf_pk_compressed(x_bytes.as_slice()).is_err() { rejected += 1; }
+        }
+        assert!(rejected > 0);
+
+        //VRF proofs
+        let (pk, sk) = vrf_generate_key();
+        let msg = FieldElement::rand(&mut rng);
+        let (proof, _) = vrf_prove(&msg, &sk, &pk).unwrap();
+        let proof_bytes = serialize_vrf_proof_compressed(&proof).unwrap();
+        assert_eq!(proof_bytes.len(), VRF_PROOF_COMPRESSED_SIZE);
+        let proof_deserialized = deserialize_vrf_proof_compressed(proof_bytes.as_slice()).unwrap();
+        assert_eq!(proof_deserialized, proof);
+        assert!(vrf_proof_to_hash(&msg, &pk, &proof_deserialized).is_ok());
+        assert!(deserialize_vrf_proof_compressed(&proof_bytes[1..]).is_err());
+
+        //SNARK proofs: only the points matter for the encoding, so any valid points do
+        let proof = SCProof {
+            a: MNT4G1Affine::prime_subgroup_generator().mul(MNT4Fr::rand(&mut rng)).into_affine(),
+            b: MNT4G2Affine::prime_subgroup_generator().mul(MNT4Fr::rand(&mut rng)).into_affine(),
+            c: MNT4G1Affine::prime_subgroup_generator().mul(MNT4Fr::rand(&mut rng)).into_affine(),
+        };
+        let proof_bytes = serialize_sc_proof_compressed(&proof).unwrap();
+        assert_eq!(proof_bytes.len(), ZK_PROOF_COMPRESSED_SIZE);
+        assert!(deserialize_sc_proof_compressed(proof_bytes.as_slice()).unwrap() == proof);
+        assert!(deserialize_sc_proof_compressed(&proof_bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn sample_schnorr_multisig(){
+        let mut rng = OsRng;
+        let signers = 4;
+
+        let keys = (0..signers).map(|_| schnorr_generate_key()).collect::<Vec<_>>();
+        let pks = keys.iter().map(|(pk, _)| *pk).collect::<Vec<_>>();
+        let aggregated_pk = musig_aggregate_public_keys(pks.as_slice()).unwrap();
+        let msg = vec![FieldElement::rand(&mut rng), FieldElement::rand(&mut rng)];
+
+        //The aggregated key is not the plain sum of the keys, and depends on the order of the signers
+        let plain_sum = pks.iter().fold(MNT6G1Projective::zero(), |sum, pk| sum + &pk.into_projective()).into_affine();
+        assert_ne!(aggregated_pk, plain_sum);
+        let mut swapped_pks = pks.clone();
+        swapped_pks.swap(0, 1);
+        assert_ne!(musig_aggregate_public_keys(swapped_pks.as_slice()).unwrap(), aggregated_pk);
+
+        //Round 1: nonce commitment
+        let nonces = (0..signers).map(|_| musig_generate_nonce()).collect::<Vec<_>>();
+        let public_nonces = nonces.iter().map(|nonce| *nonce.public_nonce()).collect::<Vec<_>>();
+        let commitments = public_nonces.iter().map(|nonce| musig_nonce_commitment(nonce).unwrap()).collect::<Vec<_>>();
+
+        //Round 2: nonce exchange
+        let aggregated_nonce = musig_aggregate_nonces(public_nonces.as_slice(), commitments.as_slice()).unwrap();
+
+        let mut wrong_commitments = commitments.clone();
+        wrong_commitments.swap(2, 3);
+        assert!(musig_aggregate_nonces(public_nonces.as_slice(), wrong_commitments.as_slice()).is_err());
+        assert!(musig_aggregate_nonces(public_nonces.as_slice(), &commitments[1..]).is_err());
+
+        //A signer can only sign with its own key and position
+        assert!(musig_partial_sign(musig_generate_nonce(), &keys[0].1, pks.as_slice(), 1, &aggregated_nonce, msg.as_slice()).is_err());
+        assert!(musig_partial_sign(musig_generate_nonce(), &keys[0].1, pks.as_slice(), signers, &aggregated_nonce, msg.as_slice()).is_err());
+
+        //Partial signatures
+        let partial_sigs = nonces.into_iter().zip(keys.iter()).enumerate()
+            .map(|(i, (nonce, (_, sk)))| musig_partial_sign(nonce, sk, pks.as_slice(), i, &aggregated_nonce, msg.as_slice()).unwrap())
+            .collect::<Vec<_>>();
+        for i in 0..signers {
+            assert!(musig_verify_partial_signature(
+                &partial_sigs[i], &public_nonces[i], pks.as_slice(), i, &aggregated_nonce, msg.as_slice()
+            ).unwrap());
+            assert!(!musig_verify_partial_signature(
+                &partial_sigs[i], &public_nonces[(i + 1) % signers], pks.as_slice(), i, &aggregated_nonce, msg.as_slice()
+            ).unwrap());
+        }
+
+        //Combination and verification
+        let signature = musig_combine_partial_signatures(&aggregated_nonce, partial_sigs.as_slice()).unwrap();
+        assert!(musig_verify_signature(msg.as_slice(), &aggregated_pk, &signature).unwrap());
+        assert!(!musig_verify_signature(&msg[..1], &aggregated_pk, &signature).unwrap());
+        assert!(!musig_verify_signature(msg.as_slice(), &plain_sum, &signature).unwrap());
+
+        //All the partial signatures are needed
+        let partial_signature = musig_combine_partial_signatures(&aggregated_nonce, &partial_sigs[1..]).unwrap();
+        assert!(!musig_verify_signature(msg.as_slice(), &aggregated_pk, &partial_signature).unwrap());
+
+        let mut tampered_partial_sigs = partial_sigs.clone();
+        tampered_partial_sigs[2] += &MNT4Fq::one();
+        assert!(!musig_verify_partial_signature(
+            &tampered_partial_sigs[2], &public_nonces[2], pks.as_slice(), 2, &aggregated_nonce, msg.as_slice()
+        ).unwrap());
+        let tampered_signature = musig_combine_partial_signatures(&aggregated_nonce, tampered_partial_sigs.as_slice()).unwrap();
+        assert!(!musig_verify_signature(msg.as_slice(), &aggregated_pk, &tampered_signature).unwrap());
+
+        //Serialization
+        let mut signature_bytes = vec![];
+        signature.write(&mut signature_bytes).unwrap();
+        assert_eq!(signature_bytes.len(), SCHNORR_MULTISIG_SIZE);
+        assert_eq!(deserialize_from_buffer_checked::<SchnorrMultiSig>(signature_bytes.as_slice()).unwrap(), signature);
+    }
+
+    #[test]
+    fn sample_checked_deserialization(){
+        let mut rng = OsRng;
+
+        let expect_error = |result: Result<(), DeserializationError>, expected: DeserializationError| {
+            assert_eq!(result.unwrap_err(), expected);
+        };
+        fn to_bytes<T: ToBytes>(value: &T) -> Vec<u8> {
+            let mut bytes = vec![];
+            value.write(&mut bytes).unwrap();
+            bytes
         }
 
-        //Compute msg to sign
-        let (_, msg) = compute_msg_to_sign(
-            &end_epoch_mc_b_hash_f,
-            &prev_end_epoch_mc_b_hash_f,
-            bt_list.as_slice()
-        ).unwrap();
+        //Valid values
+        let (pk, sk) = schnorr_generate_key();
+        let msg = FieldElement::rand(&mut rng);
+        let sig = schnorr_sign(&msg, &sk, &pk).unwrap();
+        let (vrf_pk, vrf_sk) = vrf_generate_key();
+        let (proof, _) = vrf_prove(&msg, &vrf_sk, &vrf_pk).unwrap();
 
-        //Generate params and write them to file
-        let params = generate_parameters(3).unwrap();
-        let proving_key_path = "./sample_proving_key";
-        write_to_file(&params, proving_key_path).unwrap();
+        let pk_bytes = to_bytes(&pk);
+        let sk_bytes = to_bytes(&sk);
+        let msg_bytes = to_bytes(&msg);
+        let sig_bytes = to_bytes(&sig);
+        let proof_bytes = to_bytes(&proof);
 
-        let verifying_key_path = "./sample_vk";
-        write_to_file(&(params.vk), verifying_key_path).unwrap();
+        assert_eq!(deserialize_from_buffer_checked::<SchnorrPk>(pk_bytes.as_slice()).unwrap(), pk);
+        assert!(deserialize_from_buffer_checked::<SchnorrSk>(sk_bytes.as_slice()).unwrap() == sk);
+        assert_eq!(deserialize_from_buffer_checked::<FieldElement>(msg_bytes.as_slice()).unwrap(), msg);
+        assert_eq!(deserialize_from_buffer_checked::<SchnorrSig>(sig_bytes.as_slice()).unwrap(), sig);
+        assert_eq!(deserialize_from_buffer_checked::<VRFProof>(proof_bytes.as_slice()).unwrap(), proof);
 
-        //Generate sample pks and sigs vec
-        let threshold: u64 = 2;
-        let mut pks = vec![];
-        let mut sks = vec![];
-        for _ in 0..3 {
-            let keypair = schnorr_generate_key();
-            pks.push(keypair.0);
-            sks.push(keypair.1);
-        }
+        //Wrong sizes
+        expect_error(deserialize_from_buffer_checked::<SchnorrPk>(&pk_bytes[1..]).map(|_| ()), DeserializationError::InvalidSize);
+        let mut extended = sig_bytes.clone();
+        extended.push(0);
+        expect_error(deserialize_from_buffer_checked::<SchnorrSig>(extended.as_slice()).map(|_| ()), DeserializationError::InvalidSize);
 
-        let mut sigs = vec![];
-        sigs.push(Some(schnorr_sign(&msg, &sks[0], &pks[0]).unwrap()));
-        sigs.push(None);
-        sigs.push(Some(schnorr_sign(&msg, &sks[2], &pks[2]).unwrap()));
+        //Non canonical encodings: a field element exceeding the modulus, and an invalid infinity flag
+        let non_reduced = vec![0xffu8; FIELD_SIZE];
+        expect_error(deserialize_from_buffer_checked::<FieldElement>(non_reduced.as_slice()).map(|_| ()), DeserializationError::NonCanonical);
+        expect_error(deserialize_from_buffer_checked::<SchnorrSk>(non_reduced.as_slice()).map(|_| ()), DeserializationError::NonCanonical);
 
-        let constant = compute_pks_threshold_hash(pks.as_slice(), threshold).unwrap();
+        let mut non_reduced_sig = sig_bytes.clone();
+        non_reduced_sig[FIELD_SIZE..].copy_from_slice(non_reduced.as_slice());
+        expect_error(deserialize_from_buffer_checked::<SchnorrSig>(non_reduced_sig.as_slice()).map(|_| ()), DeserializationError::NonCanonical);
 
-        //Create and serialize proof
-        let (proof, quality) = create_naive_threshold_sig_proof(
-            pks.as_slice(),
-            sigs,
-            &end_epoch_mc_b_hash,
-            &prev_end_epoch_mc_b_hash,
-            bt_list.as_slice(),
-            threshold,
-            proving_key_path
-        ).unwrap();
-        let proof_path = "./sample_proof";
-        write_to_file(&proof, proof_path).unwrap();
+        let mut non_reduced_pk = pk_bytes.clone();
+        non_reduced_pk[..FIELD_SIZE].copy_from_slice(non_reduced.as_slice());
+        expect_error(deserialize_from_buffer_checked::<SchnorrPk>(non_reduced_pk.as_slice()).map(|_| ()), DeserializationError::NonCanonical);
 
-        //Verify proof
-        assert!(verify_naive_threshold_sig_proof(
-            &constant,
-            &end_epoch_mc_b_hash,
-            &prev_end_epoch_mc_b_hash,
-            bt_list.as_slice(),
-            quality,
-            &proof,
-            "./sample_vk",
-        ).unwrap());
+        let mut wrong_flag_pk = pk_bytes.clone();
+        wrong_flag_pk[G1_SIZE - 1] = 2;
+        expect_error(deserialize_from_buffer_checked::<SchnorrPk>(wrong_flag_pk.as_slice()).map(|_| ()), DeserializationError::NonCanonical);
 
+        //Points not on the curve
+        let off_curve_pk = MNT6G1Affine::new(pk.x, pk.y + &FieldElement::one(), false);
+        let off_curve_pk_bytes = to_bytes(&off_curve_pk);
+        expect_error(deserialize_from_buffer_checked::<SchnorrPk>(off_curve_pk_bytes.as_slice()).map(|_| ()), DeserializationError::NotOnCurve);
 
-        //Generate wrong public inputs by changing quality and assert proof verification doesn't pass
-        assert!(!verify_naive_threshold_sig_proof(
-            &constant,
-            &end_epoch_mc_b_hash,
-            &prev_end_epoch_mc_b_hash,
-            bt_list.as_slice(),
-            quality - 1,
-            &proof,
-            "./sample_vk",
-        ).unwrap());
+        let mut off_curve_proof = proof.clone();
+        off_curve_proof.gamma = off_curve_pk.into_projective();
+        let off_curve_proof_bytes = to_bytes(&off_curve_proof);
+        expect_error(deserialize_from_buffer_checked::<VRFProof>(off_curve_proof_bytes.as_slice()).map(|_| ()), DeserializationError::NotOnCurve);
+
+        //Zero public key and secret key
+        let zero_pk_bytes = to_bytes(&SchnorrPk::zero());
+        expect_error(deserialize_from_buffer_checked::<VRFPk>(zero_pk_bytes.as_slice()).map(|_| ()), DeserializationError::Zero);
+        let zero_sk_bytes = vec![0u8; SCALAR_FIELD_SIZE];
+        expect_error(deserialize_from_buffer_checked::<VRFSk>(zero_sk_bytes.as_slice()).map(|_| ()), DeserializationError::Zero);
+
+        //G1 has prime order, so points outside the subgroup can be only found in G2, through the compressed encoding
+        let mut found = false;
+        while !found {
+            let x = MNT4Fq2::rand(&mut rng);
+            if let Some(point) = MNT4G2Affine::get_point_from_x(x, false) {
+                if point.is_in_correct_subgroup_assuming_on_curve() { continue; }
+                let point_bytes = point.to_compressed_bytes().unwrap();
+                assert_eq!(
+                    MNT4G2Affine::from_compressed_bytes(point_bytes.as_slice()).unwrap_err().downcast_ref::<DeserializationError>(),
+                    Some(&DeserializationError::NotInSubgroup)
+                );
+                found = true;
+            }
+        }
     }
 
     #[test]
-    fn naive_threshold_sig_circuit_test() {
-        create_sample_naive_threshold_sig_circuit(10);
-        create_sample_naive_threshold_sig_circuit(0);
+    fn sample_secret_key_zeroization(){
+        use std::mem::ManuallyDrop;
+
+        let is_wiped = |sk: &MNT4Fq| {
+            let mut sk_bytes = [0xffu8; SCALAR_FIELD_SIZE];
+            sk.write(&mut sk_bytes[..]).unwrap();
+            sk_bytes.iter().all(|&b| b == 0)
+        };
+
+        //take() wipes the source
+        let (_, sk) = schnorr_generate_key();
+        let mut scalar = *sk;
+        assert!(!is_wiped(&scalar));
+        let taken = SecretScalar::take(&mut scalar);
+        assert!(is_wiped(&scalar));
+        assert_eq!(taken, sk);
+
+        //zeroize() and drop wipe the key
+        let mut zeroized = sk.clone();
+        zeroized.zeroize();
+        assert!(is_wiped(&zeroized));
+
+        let mut dropped = ManuallyDrop::new(sk.clone());
+        let dropped_ptr: *const MNT4Fq = &**dropped;
+        unsafe { ManuallyDrop::drop(&mut dropped) };
+        assert!(is_wiped(unsafe { &*dropped_ptr }));
+
+        //Serialization through a temporary buffer gives the usual encoding
+        let mut sk_bytes = vec![0u8; SCALAR_FIELD_SIZE];
+        serialize_to_buffer(&sk, &mut sk_bytes).unwrap();
+        assert_eq!(with_serialized_secret_key(&sk, |bytes| bytes.to_vec()).unwrap(), sk_bytes);
+
+        //Temporary buffers are wiped
+        let mut bytes = vec![0xffu8; 10];
+        wipe(bytes.as_mut_slice());
+        assert_eq!(bytes, vec![0u8; 10]);
+
+        let mut fes = vec![get_random_field_element(); 3];
+        wipe(fes.as_mut_slice());
+        assert!(fes.iter().all(|fe| fe.is_zero()));
+
+        //Secret keys are never printed
+        assert_eq!(format!("{:?}", sk), "SecretScalar(<redacted>)");
     }
 
     #[test]
-    fn sample_schnorr_sig_prove_verify(){
-        let mut rng = OsRng;
-        let msg = FieldElement::rand(&mut rng);
+    fn sample_schnorr_proof_of_possession(){
+        let keys = (0..4).map(|_| schnorr_generate_key()).collect::<Vec<_>>();
+        let pks = keys.iter().map(|(pk, _)| *pk).collect::<Vec<_>>();
+        let mut pops = keys.iter().map(|(pk, sk)| schnorr_create_pop(sk, pk).unwrap()).collect::<Vec<_>>();
 
-        let (pk, sk) = schnorr_generate_key(); //Keygen
-        assert_eq!(schnorr_get_public_key(&sk), pk); //Get pk
-        assert!(schnorr_verify_public_key(&pk)); //Verify pk
+        for (pk, pop) in pks.iter().zip(pops.iter()) {
+            assert!(schnorr_verify_pop(pk, pop).unwrap());
+        }
 
-        //Serialize/deserialize pk
-        let mut pk_serialized = vec![0u8; SCHNORR_PK_SIZE];
-        serialize_to_buffer(&pk, &mut pk_serialized).unwrap();
-        let pk_deserialized = deserialize_from_buffer(&pk_serialized).unwrap();
-        assert_eq!(pk, pk_deserialized);
+        //A PoP is bound to its public key
+        assert!(!schnorr_verify_pop(&pks[1], &pops[0]).unwrap());
 
-        //Serialize/deserialize sk
-        let mut sk_serialized = vec![0u8; SCHNORR_SK_SIZE];
-        serialize_to_buffer(&sk, &mut sk_serialized).unwrap();
-        let sk_deserialized = deserialize_from_buffer(&sk_serialized).unwrap();
-        assert_eq!(sk, sk_deserialized);
+        //A PoP is not a signature on any of the coordinates of the public key, and vice versa
+        assert!(!schnorr_verify_signature(&pks[0].x, &pks[0], &pops[0]).unwrap());
+        let sig = schnorr_sign_field_elements(&[pks[0].x, pks[0].y], &keys[0].1, &pks[0]).unwrap();
+        assert!(!schnorr_verify_pop(&pks[0], &sig).unwrap());
 
-        let sig = schnorr_sign(&msg, &sk, &pk).unwrap(); //Sign msg
+        //A PoP can't be obtained, nor verified, as an ordinary field elements signature
+        let pop_message = schnorr_pop_message(&pks[0]).unwrap();
+        assert!(schnorr_sign_field_elements(&pop_message, &keys[0].1, &pks[0]).is_err());
+        assert!(schnorr_sign_field_elements_deterministic(&pop_message, &keys[0].1, &pks[0], None).is_err());
+        assert!(schnorr_sign_field_elements(&pop_message[..1], &keys[0].1, &pks[0]).is_err());
+        assert!(!schnorr_verify_signature_field_elements(&pop_message, &pks[0], &pops[0]).unwrap());
 
-        //Serialize/deserialize sig
-        let mut sig_serialized = vec![0u8; SCHNORR_SIG_SIZE];
-        serialize_to_buffer(&sig, &mut sig_serialized).unwrap();
-        let sig_deserialized = deserialize_from_buffer(&sig_serialized).unwrap();
-        assert_eq!(sig, sig_deserialized);
+        //A message starting with a reserved personalization doesn't fail the verification of the other items
+        let msg = get_random_field_element();
+        let sig = schnorr_sign(&msg, &keys[0].1, &pks[0]).unwrap();
+        let items = [(pks[0], msg, sig), (pks[0], pop_message[0], pops[0])];
+        assert_eq!(schnorr_verify_signatures_per_item(&items).unwrap(), vec![true, false]);
+        assert!(schnorr_sign_field_elements(&pop_message[1..], &keys[0].1, &pks[0]).is_ok());
 
-        assert!(schnorr_verify_signature(&msg, &pk, &sig).unwrap()); //Verify sig
+        //The constant can be computed only if all the PoPs are valid, and is the same as without PoPs
+        let version = CURRENT_DOMAIN_SEPARATION_VERSION;
+        assert_eq!(
+            compute_pks_threshold_hash_with_pops(pks.as_slice(), pops.as_slice(), 2, version).unwrap(),
+            compute_pks_threshold_hash(pks.as_slice(), 2, version).unwrap()
+        );
+        assert!(compute_pks_threshold_hash_with_pops(pks.as_slice(), &pops[1..], 2, version).is_err());
 
-        //Negative case
-        let wrong_msg = FieldElement::rand(&mut rng);
-        assert!(!schnorr_verify_signature(&wrong_msg, &pk, &sig).unwrap());
+        pops.swap(2, 3);
+        assert!(compute_pks_threshold_hash_with_pops(pks.as_slice(), pops.as_slice(), 2, version).is_err());
     }
 
     #[test]
@@ -589,6 +3589,124 @@ mod test {
         assert!(vrf_proof_to_hash(&wrong_msg, &pk, &vrf_proof).is_err());
     }
 
+    #[test]
+    fn sample_poseidon_hash_batch(){
+        let mut rng = OsRng;
+        let inputs = (0..100)
+            .map(|i| (0..(i % 5) + 1).map(|_| FieldElement::rand(&mut rng)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let hashes = compute_poseidon_hash_batch(inputs.as_slice()).unwrap();
+        assert_eq!(hashes.len(), inputs.len());
+        for (input, hash) in inputs.iter().zip(hashes.iter()) {
+            assert_eq!(*hash, compute_poseidon_hash(input.as_slice()).unwrap());
+        }
+
+        //Empty batch
+        assert!(compute_poseidon_hash_batch(&[]).unwrap().is_empty());
+    }
+
+    // Number of field elements absorbed by each permutation inside MNT4PoseidonHash::evaluate
+    const POSEIDON_RATE: usize = 2;
+
+    #[test]
+    fn sample_buffered_poseidon_hasher(){
+        let mut rng = OsRng;
+        let mut hasher = BufferedPoseidonHasher::new();
+
+        for len in 1..6 {
+            let input = (0..len).map(|_| FieldElement::rand(&mut rng)).collect::<Vec<_>>();
+            let expected_digest = compute_poseidon_hash(input.as_slice()).unwrap();
+
+            hasher.reset();
+            input.iter().for_each(|fe| { hasher.update(fe); });
+            assert_eq!(hasher.finalize().unwrap(), expected_digest);
+
+            //Finalize doesn't modify the state
+            assert_eq!(hasher.finalize().unwrap(), expected_digest);
+
+            //Updating after finalize continues absorbing the same input
+            let extra = FieldElement::rand(&mut rng);
+            let mut extended_input = input.clone();
+            extended_input.push(extra);
+            assert_eq!(hasher.update(&extra).finalize().unwrap(), compute_poseidon_hash(extended_input.as_slice()).unwrap());
+        }
+
+        //Splitting the input, also at the boundaries of the rate-sized chunks absorbed by evaluate,
+        //and finalizing in between never changes the digest
+        let input = (0..3 * POSEIDON_RATE + 1).map(|_| FieldElement::rand(&mut rng)).collect::<Vec<_>>();
+        for len in 1..=input.len() {
+            let expected_digest = compute_poseidon_hash(&input[..len]).unwrap();
+            for split in 1..len {
+                hasher.reset();
+                input[..split].iter().for_each(|fe| { hasher.update(fe); });
+                assert_eq!(hasher.finalize().unwrap(), compute_poseidon_hash(&input[..split]).unwrap());
+                input[split..len].iter().for_each(|fe| { hasher.update(fe); });
+                assert_eq!(hasher.finalize().unwrap(), expected_digest, "len {}, split at {}", len, split);
+            }
+        }
+
+        //Pks threshold hash computed by collecting the pks first
+        let pks = (0..4).map(|_| schnorr_generate_key().0).collect::<Vec<_>>();
+        let pks_x = pks.iter().map(|pk| pk.x).collect::<Vec<_>>();
+        let pks_hash = compute_poseidon_hash(pks_x.as_slice()).unwrap();
+        let expected = compute_poseidon_hash(&[pks_hash, read_field_element_from_u64(3)]).unwrap();
+        assert_eq!(compute_pks_threshold_hash(pks.as_slice(), 3, DomainSeparationVersion::V0).unwrap(), expected);
+
+        let version = DomainSeparationVersion::V1;
+        let pks_hash = domain_separated_hash(HashDomain::PksHash, version, pks_x.as_slice()).unwrap();
+        let expected = domain_separated_hash(
+            HashDomain::PksThresholdHash, version, &[pks_hash, read_field_element_from_u64(3)]
+        ).unwrap();
+        assert_eq!(compute_pks_threshold_hash(pks.as_slice(), 3, version).unwrap(), expected);
+    }
+
+    #[test]
+    fn sample_poseidon_hash_bytes(){
+        //Packing
+        for &len in [0, 1, FIELD_ELEMENT_PACKING_SIZE, FIELD_ELEMENT_PACKING_SIZE + 1, 1000].iter() {
+            let bytes = vec![0xffu8; len];
+            let fes = bytes_to_field_elements(bytes.as_slice()).unwrap();
+            assert_eq!(fes.len(), (len + FIELD_ELEMENT_PACKING_SIZE - 1) / FIELD_ELEMENT_PACKING_SIZE);
+
+            //Packing is injective: the original bytes can be recovered
+            let mut unpacked = vec![];
+            for (i, fe) in fes.iter().enumerate() {
+                let mut fe_bytes = vec![];
+                fe.write(&mut fe_bytes).unwrap();
+                let chunk_len = std::cmp::min(FIELD_ELEMENT_PACKING_SIZE, len - i * FIELD_ELEMENT_PACKING_SIZE);
+                assert!(fe_bytes[chunk_len..].iter().all(|&b| b == 0));
+                unpacked.extend_from_slice(&fe_bytes[..chunk_len]);
+            }
+            assert_eq!(unpacked, bytes);
+        }
+
+        //Hashing: trailing zeros change the hash
+        let h1 = compute_poseidon_hash_bytes(b"sidechain").unwrap();
+        let h2 = compute_poseidon_hash_bytes(b"sidechain\0").unwrap();
+        assert_ne!(h1, h2);
+        assert_eq!(h1, compute_poseidon_hash_bytes(b"sidechain").unwrap());
+
+        //Hashing of empty input is the hash of its length only
+        assert_eq!(
+            compute_poseidon_hash_bytes(&[]).unwrap(),
+            compute_poseidon_hash(&[FieldElement::zero()]).unwrap()
+        );
+
+        //Checked read
+        assert!(read_field_element_from_buffer_checked(&[0xffu8; FIELD_SIZE]).is_err());
+        assert!(read_field_element_from_buffer_checked(&[0u8; FIELD_SIZE + 1]).is_err());
+
+        let mut modulus_bytes = vec![];
+        <FieldElement as PrimeField>::Params::MODULUS.write(&mut modulus_bytes).unwrap();
+        assert!(read_field_element_from_buffer_checked(modulus_bytes.as_slice()).is_err());
+
+        let max_fe = -FieldElement::one();
+        let mut max_fe_bytes = vec![];
+        max_fe.write(&mut max_fe_bytes).unwrap();
+        assert_eq!(read_field_element_from_buffer_checked(max_fe_bytes.as_slice()).unwrap(), max_fe);
+    }
+
     #[test]
     fn sample_merkle_tree(){
         let leaves_num = 16;
@@ -598,14 +3716,601 @@ mod test {
             leaves.push(FieldElement::rand(&mut rng));
         }
 
-        let mt = GingerMerkleTree::new(leaves.as_slice()).unwrap();
+        let mt = new_ginger_merkle_tree(leaves.as_slice()).unwrap();
         let root = get_ginger_merkle_root(&mt);
         let wrong_root = FieldElement::rand(&mut rng);
 
         for i in 0..leaves_num {
             let path = get_ginger_merkle_path(&leaves[i], i, &mt).unwrap();
-            assert!(verify_ginger_merkle_path(path.clone(), &root, &leaves[i]).unwrap());
-            assert!(!verify_ginger_merkle_path(path, &wrong_root, &leaves[i]).unwrap());
+            assert!(verify_ginger_merkle_path(&path, &root, &leaves[i]).unwrap());
+            assert!(!verify_ginger_merkle_path(&path, &wrong_root, &leaves[i]).unwrap());
+
+            //Serialize/deserialize path
+            let mut path_serialized = vec![0u8; GINGER_MERKLE_PATH_SIZE];
+            serialize_ginger_merkle_path(&path, &mut path_serialized).unwrap();
+            let path_deserialized = deserialize_ginger_merkle_path(&path_serialized).unwrap();
+            assert!(verify_ginger_merkle_path(&path_deserialized, &root, &leaves[i]).unwrap());
+        }
+
+        //Negative case: truncated path
+        let mut path_serialized = vec![0u8; GINGER_MERKLE_PATH_SIZE];
+        let path = get_ginger_merkle_path(&leaves[0], 0, &mt).unwrap();
+        serialize_ginger_merkle_path(&path, &mut path_serialized).unwrap();
+        assert!(deserialize_ginger_merkle_path(&path_serialized[1..]).is_err());
+    }
+
+    #[test]
+    fn sample_merkle_tree_multi_path(){
+        let leaves_num = 16;
+        let mut leaves = vec![];
+        let mut rng = OsRng;
+        for _ in 0..leaves_num {
+            leaves.push(FieldElement::rand(&mut rng));
+        }
+
+        let mt = new_ginger_merkle_tree(leaves.as_slice()).unwrap();
+        let root = get_ginger_merkle_root(&mt);
+        let wrong_root = FieldElement::rand(&mut rng);
+
+        let indices_sets: Vec<Vec<usize>> = vec![vec![0], vec![0, 1], vec![1, 2, 5, 15], (0..leaves_num).collect()];
+        for indices in indices_sets.iter() {
+            let opened_leaves = indices.iter().map(|&i| leaves[i]).collect::<Vec<_>>();
+            let multi_path = get_ginger_merkle_multi_path(opened_leaves.as_slice(), indices.as_slice(), &mt).unwrap();
+            assert!(verify_ginger_merkle_multi_path(&multi_path, &root, opened_leaves.as_slice()).unwrap());
+            assert!(!verify_ginger_merkle_multi_path(&multi_path, &wrong_root, opened_leaves.as_slice()).unwrap());
+
+            //The multi path must not be bigger than the single paths
+            assert!(multi_path.siblings.len() <= indices.len() * GINGER_MERKLE_TREE_LEVELS);
+
+            //Serialize/deserialize multi path
+            let mut multi_path_serialized = vec![];
+            multi_path.write(&mut multi_path_serialized).unwrap();
+            assert_eq!(multi_path_serialized.len(), get_ginger_merkle_multi_path_size(&multi_path));
+            let multi_path_deserialized = GingerMerkleTreeMultiPath::read(multi_path_serialized.as_slice()).unwrap();
+            assert_eq!(multi_path, multi_path_deserialized);
+            assert!(verify_ginger_merkle_multi_path(&multi_path_deserialized, &root, opened_leaves.as_slice()).unwrap());
+
+            assert_eq!(
+                deserialize_variable_size_from_buffer_checked::<GingerMerkleTreeMultiPath>(multi_path_serialized.as_slice()).unwrap(),
+                multi_path
+            );
+
+            //Negative case: truncated multi path, or with trailing bytes
+            assert!(GingerMerkleTreeMultiPath::read(&multi_path_serialized[..multi_path_serialized.len() - 1]).is_err());
+            assert_eq!(
+                deserialize_variable_size_from_buffer_checked::<GingerMerkleTreeMultiPath>(
+                    &multi_path_serialized[..multi_path_serialized.len() - 1]
+                ).err(),
+                Some(DeserializationError::InvalidSize)
+            );
+            let mut multi_path_with_trailing_byte = multi_path_serialized.clone();
+            multi_path_with_trailing_byte.push(0u8);
+            assert_eq!(
+                deserialize_variable_size_from_buffer_checked::<GingerMerkleTreeMultiPath>(
+                    multi_path_with_trailing_byte.as_slice()
+                ).err(),
+                Some(DeserializationError::InvalidSize)
+            );
+
+            //Negative case: non canonical sibling, i.e. the modulus in place of the last one
+            if !multi_path.siblings.is_empty() {
+                let mut modulus_bytes = vec![];
+                <FieldElement as PrimeField>::Params::MODULUS.write(&mut modulus_bytes).unwrap();
+                let mut non_canonical_bytes = multi_path_serialized.clone();
+                let offset = non_canonical_bytes.len() - FIELD_SIZE;
+                non_canonical_bytes[offset..].copy_from_slice(modulus_bytes.as_slice());
+                assert!(GingerMerkleTreeMultiPath::read(non_canonical_bytes.as_slice()).is_err());
+                assert_eq!(
+                    deserialize_variable_size_from_buffer_checked::<GingerMerkleTreeMultiPath>(
+                        non_canonical_bytes.as_slice()
+                    ).err(),
+                    Some(DeserializationError::NonCanonical)
+                );
+            }
+
+            //Negative case: wrong leaf
+            let mut wrong_leaves = opened_leaves.clone();
+            wrong_leaves[0] = FieldElement::rand(&mut rng);
+            assert!(!verify_ginger_merkle_multi_path(&multi_path, &root, wrong_leaves.as_slice()).unwrap());
+
+            //Negative case: missing sibling
+            if !multi_path.siblings.is_empty() {
+                let mut wrong_multi_path = multi_path.clone();
+                wrong_multi_path.siblings.pop();
+                assert!(!verify_ginger_merkle_multi_path(&wrong_multi_path, &root, opened_leaves.as_slice()).unwrap());
+            }
+        }
+
+        //Opening all the leaves requires only the siblings above the non-empty part of the tree
+        let all_indices = (0..leaves_num).collect::<Vec<_>>();
+        let multi_path = get_ginger_merkle_multi_path(leaves.as_slice(), all_indices.as_slice(), &mt).unwrap();
+        assert_eq!(multi_path.siblings.len(), GINGER_MERKLE_TREE_LEVELS - 4);
+
+        //Negative case: unsorted or duplicated indices
+        assert!(get_ginger_merkle_multi_path(&[leaves[1], leaves[0]], &[1, 0], &mt).is_err());
+        assert!(get_ginger_merkle_multi_path(&[leaves[1], leaves[1]], &[1, 1], &mt).is_err());
+
+        //Negative case: leaves and indices count mismatch
+        assert!(get_ginger_merkle_multi_path(&[leaves[0]], &[0, 1], &mt).is_err());
+    }
+
+    //Naive root computation over the full, padded, leaves list
+    fn compute_incremental_merkle_root(leaves: &[FieldElement], height: usize) -> FieldElement {
+        let mut level = leaves.to_vec();
+        level.resize(1 << height, FieldElement::zero());
+        while level.len() > 1 {
+            level = level.chunks(2).map(|pair| hash_merkle_node(&pair[0], &pair[1]).unwrap()).collect();
+        }
+        level[0]
+    }
+
+    #[test]
+    fn sample_incremental_merkle_tree(){
+        let height = 4;
+        let mut rng = OsRng;
+        let mut tree = GingerIncrementalMerkleTree::new(height, false).unwrap();
+        let mut compact_tree = GingerIncrementalMerkleTree::new(height, true).unwrap();
+
+        //Empty tree
+        assert_eq!(tree.root().unwrap(), compute_incremental_merkle_root(&[], height));
+
+        let mut leaves = vec![];
+        for i in 0..(1 << height) {
+            let leaf = FieldElement::rand(&mut rng);
+            leaves.push(leaf);
+            tree.append(&leaf).unwrap();
+            compact_tree.append(&leaf).unwrap();
+
+            let root = tree.root().unwrap();
+            assert_eq!(root, compute_incremental_merkle_root(leaves.as_slice(), height));
+            assert_eq!(root, compact_tree.root().unwrap());
+
+            //Check paths of all the leaves appended so far
+            for j in 0..=i {
+                let path = tree.path(j as u64).unwrap();
+                assert!(path.verify(&root, &leaves[j]).unwrap());
+                assert!(!path.verify(&root, &FieldElement::rand(&mut rng)).unwrap());
+            }
+            assert!(tree.path(i as u64 + 1).is_err());
+            assert!(compact_tree.path(i as u64).is_err());
+        }
+
+        //Tree is full
+        assert!(tree.append(&FieldElement::rand(&mut rng)).is_err());
+        assert!(compact_tree.append(&FieldElement::rand(&mut rng)).is_err());
+    }
+
+    #[test]
+    fn sample_incremental_merkle_tree_serialization(){
+        let height = 10;
+        let mut rng = OsRng;
+        let mut tree = GingerIncrementalMerkleTree::new(height, false).unwrap();
+        for _ in 0..37 {
+            tree.append(&FieldElement::rand(&mut rng)).unwrap();
+        }
+        let mut compact_tree = tree.clone();
+        compact_tree.compact();
+
+        //Restore both and check they keep evolving like the original ones
+        let mut tree_restored = GingerIncrementalMerkleTree::deserialize(&tree.serialize().unwrap()).unwrap();
+        let mut compact_tree_restored = GingerIncrementalMerkleTree::deserialize(&compact_tree.serialize().unwrap()).unwrap();
+        assert_eq!(tree, tree_restored);
+        assert_eq!(compact_tree, compact_tree_restored);
+
+        let leaf = FieldElement::rand(&mut rng);
+        tree.append(&leaf).unwrap();
+        tree_restored.append(&leaf).unwrap();
+        compact_tree_restored.append(&leaf).unwrap();
+        assert_eq!(tree.root().unwrap(), tree_restored.root().unwrap());
+        assert_eq!(tree.root().unwrap(), compact_tree_restored.root().unwrap());
+        assert_eq!(tree.path(37).unwrap(), tree_restored.path(37).unwrap());
+
+        //Negative cases
+        let mut serialized = compact_tree.serialize().unwrap();
+        assert!(GingerIncrementalMerkleTree::deserialize(&serialized[..serialized.len() - 1]).is_err());
+        serialized.push(0u8);
+        assert!(GingerIncrementalMerkleTree::deserialize(&serialized).is_err());
+        serialized[0] = 0u8;
+        assert!(GingerIncrementalMerkleTree::deserialize(&serialized).is_err());
+    }
+
+    #[test]
+    fn sample_merkle_mountain_range(){
+        let mut rng = OsRng;
+        let mut mmr = GingerMerkleMountainRange::new();
+        let mut leaves = vec![];
+
+        //Empty range
+        assert!(mmr.peaks().is_empty());
+        assert_eq!(mmr.root().unwrap(), compute_poseidon_hash(&[FieldElement::zero()]).unwrap());
+        assert!(mmr.proof(0).is_err());
+
+        for _ in 0..20 {
+            let leaf = FieldElement::rand(&mut rng);
+            leaves.push(leaf);
+            mmr.append(&leaf).unwrap();
+            let leaves_count = leaves.len() as u64;
+            assert_eq!(mmr.leaves_count(), leaves_count);
+
+            //Peaks are the roots of the perfect subtrees given by the bits of leaves_count
+            let mut expected_peaks = vec![];
+            let mut offset = 0;
+            for height in (0..64).rev() {
+                if (leaves_count >> height) & 1 == 1 {
+                    let size = 1 << height;
+                    expected_peaks.push(compute_incremental_merkle_root(&leaves[offset..offset + size], height));
+                    offset += size;
+                }
+            }
+            assert_eq!(mmr.peaks(), expected_peaks);
+
+            let mut root_input = vec![read_field_element_from_u64(leaves_count)];
+            root_input.extend_from_slice(expected_peaks.as_slice());
+            let root = mmr.root().unwrap();
+            assert_eq!(root, compute_poseidon_hash(root_input.as_slice()).unwrap());
+
+            //Inclusion proofs
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = mmr.proof(i as u64).unwrap();
+                assert!(proof.verify(&root, leaf).unwrap());
+                assert!(!proof.verify(&root, &FieldElement::rand(&mut rng)).unwrap());
+
+                //Serialize/deserialize proof
+                let mut proof_serialized = vec![];
+                proof.write(&mut proof_serialized).unwrap();
+                let proof_deserialized = GingerMerkleMountainRangeProof::read(proof_serialized.as_slice()).unwrap();
+                assert_eq!(proof, proof_deserialized);
+                assert!(GingerMerkleMountainRangeProof::read(&proof_serialized[..proof_serialized.len() - 1]).is_err());
+            }
+            assert!(mmr.proof(leaves_count).is_err());
+        }
+
+        //Proofs are bound to the leaves count of the range
+        let old_root = mmr.root().unwrap();
+        let old_proof = mmr.proof(3).unwrap();
+        mmr.append(&FieldElement::rand(&mut rng)).unwrap();
+        assert!(!old_proof.verify(&mmr.root().unwrap(), &leaves[3]).unwrap());
+        assert!(old_proof.verify(&old_root, &leaves[3]).unwrap());
+
+        //Serialize/deserialize range
+        let mmr_serialized = mmr.serialize().unwrap();
+        let mmr_deserialized = GingerMerkleMountainRange::deserialize(mmr_serialized.as_slice()).unwrap();
+        assert_eq!(mmr, mmr_deserialized);
+        assert!(GingerMerkleMountainRange::deserialize(&mmr_serialized[..mmr_serialized.len() - 1]).is_err());
+
+        let mut mmr_serialized_wrong_version = mmr_serialized.clone();
+        mmr_serialized_wrong_version[0] = 0;
+        assert!(GingerMerkleMountainRange::deserialize(mmr_serialized_wrong_version.as_slice()).is_err());
+    }
+
+    #[test]
+    fn sample_variable_height_merkle_tree(){
+        let leaves_num = 20;
+        let mut leaves = vec![];
+        let mut rng = OsRng;
+        for _ in 0..leaves_num {
+            leaves.push(FieldElement::rand(&mut rng));
+        }
+
+        //Unsupported heights
+        assert!(new_ginger_variable_height_merkle_tree(leaves.as_slice(), 0).is_err());
+        assert!(new_ginger_variable_height_merkle_tree(leaves.as_slice(), VARIABLE_HEIGHT_MERKLE_TREE_MAX_HEIGHT + 1).is_err());
+        assert!(new_ginger_variable_height_merkle_tree(leaves.as_slice(), 4).is_err());
+
+        let mt_10 = new_ginger_variable_height_merkle_tree(leaves.as_slice(), 10).unwrap();
+        let mt_20 = new_ginger_variable_height_merkle_tree(leaves.as_slice(), 20).unwrap();
+        let root_10 = get_ginger_variable_height_merkle_root(&mt_10).unwrap();
+        let root_20 = get_ginger_variable_height_merkle_root(&mt_20).unwrap();
+        assert_eq!(root_10.height, 10);
+        assert_eq!(root_20.height, 20);
+
+        //Serialize/deserialize root
+        let mut root_serialized = vec![0u8; VARIABLE_HEIGHT_MERKLE_ROOT_SIZE];
+        serialize_to_buffer(&root_10, &mut root_serialized).unwrap();
+        let root_deserialized: GingerVariableHeightMerkleRoot = deserialize_from_buffer(&root_serialized).unwrap();
+        assert_eq!(root_10, root_deserialized);
+
+        for i in 0..leaves_num {
+            let path_10 = get_ginger_variable_height_merkle_path(&mt_10, i).unwrap();
+            let path_20 = get_ginger_variable_height_merkle_path(&mt_20, i).unwrap();
+            assert!(verify_ginger_variable_height_merkle_path(&path_10, &root_10, &leaves[i]).unwrap());
+            assert!(verify_ginger_variable_height_merkle_path(&path_20, &root_20, &leaves[i]).unwrap());
+            assert!(!verify_ginger_variable_height_merkle_path(&path_10, &root_10, &FieldElement::rand(&mut rng)).unwrap());
+
+            //Heights mismatch
+            assert!(verify_ginger_variable_height_merkle_path(&path_10, &root_20, &leaves[i]).is_err());
+            assert!(verify_ginger_variable_height_merkle_path(&path_20, &root_10, &leaves[i]).is_err());
+
+            //Serialize/deserialize path
+            let mut path_serialized = vec![0u8; get_variable_height_merkle_path_size(10)];
+            serialize_to_buffer(&path_10, &mut path_serialized).unwrap();
+            let path_deserialized = deserialize_from_buffer(&path_serialized).unwrap();
+            assert_eq!(path_10, path_deserialized);
+        }
+        assert!(get_ginger_variable_height_merkle_path(&mt_10, leaves_num).is_err());
+
+        //Negative case: bad height byte
+        let mut root_serialized = vec![0u8; VARIABLE_HEIGHT_MERKLE_ROOT_SIZE];
+        serialize_to_buffer(&root_10, &mut root_serialized).unwrap();
+        root_serialized[0] = (VARIABLE_HEIGHT_MERKLE_TREE_MAX_HEIGHT + 1) as u8;
+        assert!(deserialize_from_buffer::<GingerVariableHeightMerkleRoot>(&root_serialized).is_err());
+    }
+
+    #[test]
+    fn sample_merkle_checked_deserialization(){
+        let mut rng = OsRng;
+        let leaves = (0..21).map(|_| FieldElement::rand(&mut rng)).collect::<Vec<_>>();
+
+        // Replaces the field element at `offset` with the modulus, i.e. a non reduced encoding of zero
+        let with_non_reduced_fe = |bytes: &[u8], offset: usize| {
+            let mut modulus_bytes = vec![];
+            <FieldElement as PrimeField>::Params::MODULUS.write(&mut modulus_bytes).unwrap();
+            let mut bytes = bytes.to_vec();
+            bytes[offset..offset + FIELD_SIZE].copy_from_slice(modulus_bytes.as_slice());
+            bytes
+        };
+        let with_trailing_byte = |bytes: &[u8]| {
+            let mut bytes = bytes.to_vec();
+            bytes.push(0u8);
+            bytes
+        };
+
+        //Merkle path: (sibling, direction)*
+        let mt = new_ginger_merkle_tree(&leaves[..16]).unwrap();
+        let mut path_bytes = vec![0u8; GINGER_MERKLE_PATH_SIZE];
+        serialize_ginger_merkle_path(&get_ginger_merkle_path(&leaves[0], 0, &mt).unwrap(), &mut path_bytes).unwrap();
+        assert!(deserialize_ginger_merkle_path(path_bytes.as_slice()).is_ok());
+        assert_eq!(deserialize_ginger_merkle_path(&path_bytes[1..]).err(), Some(DeserializationError::InvalidSize));
+        assert_eq!(
+            deserialize_ginger_merkle_path(with_trailing_byte(path_bytes.as_slice()).as_slice()).err(),
+            Some(DeserializationError::InvalidSize)
+        );
+        assert_eq!(
+            deserialize_ginger_merkle_path(with_non_reduced_fe(path_bytes.as_slice(), FIELD_SIZE + 1).as_slice()).err(),
+            Some(DeserializationError::NonCanonical)
+        );
+        let mut wrong_direction_path_bytes = path_bytes.clone();
+        wrong_direction_path_bytes[FIELD_SIZE] = 2;
+        assert_eq!(
+            deserialize_ginger_merkle_path(wrong_direction_path_bytes.as_slice()).err(),
+            Some(DeserializationError::NonCanonical)
+        );
+
+        //Variable height merkle root: height || value
+        let vhmt = new_ginger_variable_height_merkle_tree(leaves.as_slice(), 10).unwrap();
+        let root = get_ginger_variable_height_merkle_root(&vhmt).unwrap();
+        let mut root_bytes = vec![0u8; VARIABLE_HEIGHT_MERKLE_ROOT_SIZE];
+        serialize_to_buffer(&root, &mut root_bytes).unwrap();
+        assert_eq!(deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerkleRoot>(&root_bytes).unwrap(), root);
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerkleRoot>(
+                &root_bytes[..root_bytes.len() - 1]
+            ).err(),
+            Some(DeserializationError::InvalidSize)
+        );
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerkleRoot>(
+                with_trailing_byte(root_bytes.as_slice()).as_slice()
+            ).err(),
+            Some(DeserializationError::InvalidSize)
+        );
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerkleRoot>(
+                with_non_reduced_fe(root_bytes.as_slice(), 1).as_slice()
+            ).err(),
+            Some(DeserializationError::NonCanonical)
+        );
+
+        //Variable height merkle path: height || leaf_index || siblings
+        let path = get_ginger_variable_height_merkle_path(&vhmt, 3).unwrap();
+        let mut path_bytes = vec![0u8; get_variable_height_merkle_path_size(10)];
+        serialize_to_buffer(&path, &mut path_bytes).unwrap();
+        assert_eq!(deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerklePath>(&path_bytes).unwrap(), path);
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerklePath>(
+                &path_bytes[..path_bytes.len() - 1]
+            ).err(),
+            Some(DeserializationError::InvalidSize)
+        );
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerklePath>(
+                with_trailing_byte(path_bytes.as_slice()).as_slice()
+            ).err(),
+            Some(DeserializationError::InvalidSize)
+        );
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerklePath>(
+                with_non_reduced_fe(path_bytes.as_slice(), 9 + FIELD_SIZE).as_slice()
+            ).err(),
+            Some(DeserializationError::NonCanonical)
+        );
+
+        //Merkle mountain range: version || leaves_count || leaves
+        let mut mmr = GingerMerkleMountainRange::new();
+        leaves.iter().for_each(|leaf| mmr.append(leaf).unwrap());
+        let mmr_bytes = mmr.serialize().unwrap();
+        assert_eq!(GingerMerkleMountainRange::deserialize_checked(mmr_bytes.as_slice()).unwrap(), mmr);
+        assert_eq!(
+            GingerMerkleMountainRange::deserialize_checked(with_trailing_byte(mmr_bytes.as_slice()).as_slice()).err(),
+            Some(DeserializationError::NonCanonical)
+        );
+        assert_eq!(
+            GingerMerkleMountainRange::deserialize_checked(with_non_reduced_fe(mmr_bytes.as_slice(), 9).as_slice()).err(),
+            Some(DeserializationError::NonCanonical)
+        );
+
+        //Merkle mountain range proof: leaves_count || leaf_index || siblings || other peaks, each list prefixed by its length
+        let proof = mmr.proof(0).unwrap();
+        assert_eq!(proof.siblings.len(), 4);
+        let mut proof_bytes = vec![];
+        proof.write(&mut proof_bytes).unwrap();
+        assert_eq!(deserialize_variable_size_from_buffer_checked::<GingerMerkleMountainRangeProof>(&proof_bytes).unwrap(), proof);
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerMerkleMountainRangeProof>(
+                &proof_bytes[..proof_bytes.len() - 1]
+            ).err(),
+            Some(DeserializationError::InvalidSize)
+        );
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerMerkleMountainRangeProof>(
+                with_trailing_byte(proof_bytes.as_slice()).as_slice()
+            ).err(),
+            Some(DeserializationError::InvalidSize)
+        );
+        assert_eq!(
+            deserialize_variable_size_from_buffer_checked::<GingerMerkleMountainRangeProof>(
+                with_non_reduced_fe(proof_bytes.as_slice(), 17).as_slice()
+            ).err(),
+            Some(DeserializationError::NonCanonical)
+        );
+    }
+
+    //Positions are given by H(key): draw random keys until one satisfies `predicate`
+    fn find_smt_key<R: RngCore>(rng: &mut R, predicate: impl Fn(&FieldElement) -> bool) -> FieldElement {
+        loop {
+            let key = FieldElement::rand(rng);
+            if predicate(&key) { return key }
+        }
+    }
+
+    #[test]
+    fn sample_sparse_merkle_tree(){
+        let height = 8;
+        let mut rng = OsRng;
+        let mut smt = GingerSparseMerkleTree::new(height).unwrap();
+        let empty_root = smt.root();
+        assert_eq!(empty_root, compute_incremental_merkle_root(&[], height));
+
+        //Keys are chosen in order to occupy different positions
+        let mut keys: Vec<FieldElement> = vec![];
+        while keys.len() < 10 {
+            let taken = keys.iter().map(|k| get_smt_leaf_position(k, height).unwrap()).collect::<Vec<_>>();
+            keys.push(find_smt_key(&mut rng, |k| !taken.contains(&get_smt_leaf_position(k, height).unwrap())));
+        }
+        let taken = keys.iter().map(|k| get_smt_leaf_position(k, height).unwrap()).collect::<Vec<_>>();
+        let values = (0..10).map(|_| FieldElement::rand(&mut rng)).collect::<Vec<_>>();
+        for (key, value) in keys.iter().zip(values.iter()) {
+            smt.insert(key, value).unwrap();
+        }
+        assert_eq!(smt.len(), keys.len());
+        let root = smt.root();
+
+        //Root must match the one of a dense tree with the same leaves
+        let mut leaves = vec![FieldElement::zero(); 1 << height];
+        for (key, value) in keys.iter().zip(values.iter()) {
+            leaves[get_smt_leaf_position(key, height).unwrap() as usize] = hash_smt_leaf(key, value).unwrap();
+        }
+        assert_eq!(root, compute_incremental_merkle_root(leaves.as_slice(), height));
+
+        //Membership proofs
+        for (key, value) in keys.iter().zip(values.iter()) {
+            assert_eq!(smt.get(key).unwrap(), Some(*value));
+            let proof = smt.membership_proof(key).unwrap();
+            assert!(proof.verify(&root, key, value, height).unwrap());
+            assert!(!proof.verify(&root, key, &FieldElement::rand(&mut rng), height).unwrap());
+            assert!(smt.non_membership_proof(key).is_err());
+        }
+
+        //Non-membership proof of a key whose position is empty
+        let absent_key = find_smt_key(&mut rng, |k| !taken.contains(&get_smt_leaf_position(k, height).unwrap()));
+        let proof = smt.non_membership_proof(&absent_key).unwrap();
+        assert!(proof.occupant.is_none());
+        assert!(proof.verify(&root, &absent_key, height).unwrap());
+        assert!(!proof.verify(&root, &keys[1], height).unwrap());
+        assert!(smt.membership_proof(&absent_key).is_err());
+
+        //Non-membership proof of a key whose position is taken by another key
+        let colliding_key = find_smt_key(&mut rng, |k| get_smt_leaf_position(k, height).unwrap() == taken[1]);
+        let proof = smt.non_membership_proof(&colliding_key).unwrap();
+        assert_eq!(proof.occupant, Some((keys[1], values[1])));
+        assert!(proof.verify(&root, &colliding_key, height).unwrap());
+        assert!(smt.insert(&colliding_key, &values[0]).is_err());
+
+        //A forged occupant can't prove non-membership of a present key
+        let forged_proof = GingerSmtNonMembershipProof {
+            occupant: Some((keys[1], values[1])),
+            siblings: smt.membership_proof(&keys[1]).unwrap().siblings,
+        };
+        assert!(!forged_proof.verify(&root, &keys[1], height).unwrap());
+
+        //The height is the one of the tree: truncated and extended sibling lists are rejected
+        let mut truncated_proof = smt.membership_proof(&keys[0]).unwrap();
+        truncated_proof.siblings.pop();
+        assert!(!truncated_proof.verify(&root, &keys[0], &values[0], height).unwrap());
+        let mut extended_proof = smt.membership_proof(&keys[0]).unwrap();
+        extended_proof.siblings.push(FieldElement::zero());
+        assert!(!extended_proof.verify(&root, &keys[0], &values[0], height).unwrap());
+        let mut truncated_proof = smt.non_membership_proof(&absent_key).unwrap();
+        truncated_proof.siblings.pop();
+        assert!(!truncated_proof.verify(&root, &absent_key, height).unwrap());
+        let mut extended_proof = smt.non_membership_proof(&absent_key).unwrap();
+        extended_proof.siblings.push(FieldElement::zero());
+        assert!(!extended_proof.verify(&root, &absent_key, height).unwrap());
+        assert!(extended_proof.verify(&root, &absent_key, 0).is_err());
+        assert!(extended_proof.verify(&root, &absent_key, SPARSE_MERKLE_TREE_MAX_HEIGHT + 1).is_err());
+
+        //The children (L, R) of an internal node can't be passed off as a leaf, nor as an occupant, one
+        //level up: without domain separation both proofs would be accepted, L being in position q
+        let siblings = (0..height).map(|_| FieldElement::rand(&mut rng)).collect::<Vec<_>>();
+        let left = FieldElement::rand(&mut rng);
+        let q = get_smt_leaf_position(&left, height - 1).unwrap();
+        let root_with_left = compute_merkle_root_from_path(&left, q << 1, siblings.as_slice()).unwrap();
+        let fake_membership_proof = GingerSmtMembershipProof { siblings: siblings[1..].to_vec() };
+        assert!(!fake_membership_proof.verify(&root_with_left, &left, &siblings[0], height - 1).unwrap());
+        let fake_non_membership_proof = GingerSmtNonMembershipProof {
+            occupant: Some((left, siblings[0])),
+            siblings: siblings[1..].to_vec(),
+        };
+        let key_in_q = find_smt_key(&mut rng, |k| k != &left && get_smt_leaf_position(k, height - 1).unwrap() == q);
+        assert!(!fake_non_membership_proof.verify(&root_with_left, &key_in_q, height - 1).unwrap());
+
+        //Update and remove
+        let new_value = FieldElement::rand(&mut rng);
+        assert!(smt.insert(&keys[0], &new_value).is_err());
+        smt.update(&keys[0], &new_value).unwrap();
+        assert_ne!(smt.root(), root);
+        assert!(smt.membership_proof(&keys[0]).unwrap().verify(&smt.root(), &keys[0], &new_value, height).unwrap());
+        smt.update(&keys[0], &values[0]).unwrap();
+        assert_eq!(smt.root(), root);
+
+        for key in keys.iter() {
+            smt.remove(key).unwrap();
+            assert!(smt.non_membership_proof(key).unwrap().verify(&smt.root(), key, height).unwrap());
         }
+        assert!(smt.remove(&keys[0]).is_err());
+        assert!(smt.update(&keys[0], &new_value).is_err());
+        assert!(smt.is_empty());
+        assert_eq!(smt.root(), empty_root);
+    }
+
+    #[test]
+    fn sample_forger_eligibility(){
+        let mut rng = OsRng;
+        let epoch_randomness = FieldElement::rand(&mut rng);
+        let (pk, sk) = vrf_generate_key();
+        let total_stake = 1_000_000u64;
+
+        //With all the stake, a forger leads 1 slot out of 20 on average
+        let (slot, proof) = (0u32..).find_map(|slot|
+            ouroboros_prove_forger_eligibility(&epoch_randomness, slot, total_stake, total_stake, &sk, &pk)
+                .unwrap()
+                .map(|proof| (slot, proof))
+        ).unwrap();
+
+        //Serialize/deserialize proof
+        let mut proof_serialized = vec![0u8; FORGER_ELIGIBILITY_PROOF_SIZE];
+        serialize_to_buffer(&proof, &mut proof_serialized).unwrap();
+        let proof_deserialized = deserialize_from_buffer_checked::<ForgerEligibilityProof>(&proof_serialized).unwrap();
+
+        let (leader_output, nonce_output) = ouroboros_check_forger_eligibility(
+            &proof_deserialized, &epoch_randomness, slot, total_stake, total_stake, &pk
+        ).unwrap().unwrap();
+        assert_ne!(leader_output.value(), nonce_output.value());
+
+        //Negative cases
+        assert!(ouroboros_check_forger_eligibility(&proof, &epoch_randomness, slot + 1, total_stake, total_stake, &pk).unwrap().is_none());
+        assert!(ouroboros_check_forger_eligibility(&proof, &epoch_randomness, slot, 0, total_stake, &pk).unwrap().is_none());
+        assert!(ouroboros_check_forger_eligibility(&proof, &epoch_randomness, slot, total_stake + 1, total_stake, &pk).is_err());
+        assert!(deserialize_from_buffer_checked::<ForgerEligibilityProof>(&proof_serialized[1..]).is_err());
     }
-}
\ No newline at end of file
+}
@@ -2,30 +2,77 @@ extern crate jni;
 
 use algebra::bytes::{FromBytes, ToBytes};
 
-use std::{ptr::null_mut, any::type_name};
+use std::any::type_name;
 
 use std::panic;
 
 mod ginger_calls;
 use ginger_calls::*;
 
+use demo_circuit::domain_separation::DomainSeparationVersion;
+
 
 fn read_raw_pointer<'a, T>(input: *const T) -> &'a T {
     assert!(!input.is_null());
     unsafe { &*input }
 }
 
+fn read_mut_raw_pointer<'a, T>(input: *mut T) -> &'a mut T {
+    assert!(!input.is_null());
+    unsafe { &mut *input }
+}
+
 fn read_nullable_raw_pointer<'a, T>(input: *const T) -> Option<&'a T> {
     unsafe { input.as_ref() }
 }
 
-fn deserialize_to_raw_pointer<T: FromBytes>(buffer: &[u8]) -> *mut T {
-    match deserialize_from_buffer(buffer) {
-        Ok(t) => Box::into_raw(Box::new(t)),
-        Err(_) => return null_mut(),
+// Throws a DeserializationException telling why the bytes were rejected.
+fn throw_deserialization_exception(_env: &JNIEnv, error: DeserializationError) {
+    let class = _env.find_class("com/horizen/librustsidechains/DeserializationException")
+        .expect("Should be able to find class DeserializationException");
+
+    let message = _env.new_string(error.to_string())
+        .expect("Should be able to create exception message");
+
+    let exception = _env.new_object(class, "(ILjava/lang/String;)V", &[
+        JValue::Int(error as jint),
+        JValue::Object(*message),
+    ]).expect("Should be able to create DeserializationException");
+
+    _env.throw(JThrowable::from(exception))
+        .expect("Should be able to throw DeserializationException");
+}
+
+// Throws an IllegalStateException telling why a native computation failed, so that errors are not
+// mistaken for the negative outcomes returned as null.
+fn throw_illegal_state_exception(_env: &JNIEnv, error: Error) {
+    _env.throw_new("java/lang/IllegalStateException", error.to_string())
+        .expect("Should be able to throw IllegalStateException");
+}
+
+// Returns a new `class_name` object holding a pointer to the deserialized value. If the bytes were
+// rejected, throws a DeserializationException and returns null.
+fn deserialized_to_jobject<T>(_env: &JNIEnv, class_name: &str, value: Result<T, DeserializationError>) -> jobject {
+    match value {
+        Ok(value) => boxed_to_jobject(_env, class_name, Ok(value)),
+        Err(e) => {
+            throw_deserialization_exception(_env, e);
+            std::ptr::null::<jobject>() as jobject
+        }
     }
 }
 
+// Returns a new `class_name` object holding a pointer to the value read from `buffer`. If the bytes
+// don't encode a valid value, throws a DeserializationException and returns null.
+fn deserialize_checked_to_jobject<T: CheckedDeserialize>(_env: &JNIEnv, class_name: &str, buffer: &[u8]) -> jobject {
+    deserialized_to_jobject(_env, class_name, deserialize_from_buffer_checked::<T>(buffer))
+}
+
+fn read_domain_separation_version(version: i32) -> Option<DomainSeparationVersion> {
+    if version < 0 || version > u8::max_value() as i32 { return None }
+    DomainSeparationVersion::from_u8(version as u8)
+}
+
 fn serialize_from_raw_pointer<T: ToBytes>(
     to_write: *const T,
     buffer: &mut [u8],
@@ -35,7 +82,7 @@ fn serialize_from_raw_pointer<T: ToBytes>(
 }
 
 use jni::JNIEnv;
-use jni::objects::{JClass, JString, JObject, JValue};
+use jni::objects::{JClass, JString, JObject, JThrowable, JValue};
 use jni::sys::{jbyteArray, jboolean, jint, jlong, jobject, jobjectArray};
 use jni::sys::{JNI_TRUE, JNI_FALSE};
 
@@ -74,18 +121,7 @@ pub extern "system" fn Java_com_horizen_librustsidechains_FieldElement_nativeDes
     let fe_bytes = _env.convert_byte_array(_field_element_bytes)
         .expect("Should be able to convert to Rust byte array");
 
-    let fe_ptr: *const FieldElement = deserialize_to_raw_pointer(fe_bytes.as_slice());
-
-    let fe: jlong = jlong::from(fe_ptr as i64);
-
-    let fe_class = _env.find_class("com/horizen/librustsidechains/FieldElement")
-        .expect("Cannot find FieldElement class.");
-
-    let fe_object = _env.new_object(fe_class, "(J)V",
-                                            &[JValue::Long(fe)])
-        .expect("Cannot create FieldElement object.");
-
-    *fe_object
+    deserialize_checked_to_jobject::<FieldElement>(&_env, "com/horizen/librustsidechains/FieldElement", fe_bytes.as_slice())
 }
 
 #[no_mangle]
@@ -220,18 +256,71 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeDes
     let pk_bytes = _env.convert_byte_array(_public_key_bytes)
         .expect("Cannot read public key bytes.");
 
-    let public_key_pointer: *const SchnorrPk = deserialize_to_raw_pointer(pk_bytes.as_slice());
+    deserialize_checked_to_jobject::<SchnorrPk>(&_env, "com/horizen/schnorrnative/SchnorrPublicKey", pk_bytes.as_slice())
+}
 
-    let public_key: jlong = jlong::from(public_key_pointer as i64);
+// Returns `bytes` as a jbyteArray, or null if the compressed serialization failed.
+fn compressed_bytes_to_jbytearray(_env: &JNIEnv, bytes: Result<Vec<u8>, Error>) -> jbyteArray {
+    match bytes {
+        Ok(bytes) => _env.byte_array_from_slice(bytes.as_slice())
+            .expect("Should be able to convert to jbyteArray"),
+        Err(_) => std::ptr::null::<jobject>() as jbyteArray,
+    }
+}
 
-    let public_key_class = _env.find_class("com/horizen/schnorrnative/SchnorrPublicKey")
-        .expect("Cannot find SchnorrPublicKey class.");
+// Returns a new `class_name` object holding a pointer to `value`, or null if `value` is an error.
+// Invalid encodings also throw a DeserializationException.
+fn boxed_to_jobject<T>(_env: &JNIEnv, class_name: &str, value: Result<T, Error>) -> jobject {
+    let value = match value {
+        Ok(value) => value,
+        Err(e) => {
+            if let Some(e) = e.downcast_ref::<DeserializationError>() {
+                throw_deserialization_exception(_env, *e);
+            }
+            return std::ptr::null::<jobject>() as jobject
+        },
+    };
+
+    let pointer: jlong = jlong::from(Box::into_raw(Box::new(value)) as i64);
+
+    let class = _env.find_class(class_name)
+        .expect("Should be able to find class");
+
+    let result = _env.new_object(class, "(J)V", &[JValue::Long(pointer)])
+        .expect("Should be able to create new object");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeSerializePublicKeyCompressed(
+    _env: JNIEnv,
+    _schnorr_public_key: JObject,
+) -> jbyteArray
+{
+    let public_key_pointer = _env.get_field(_schnorr_public_key, "publicKeyPointer", "J")
+        .expect("Cannot get public key pointer.");
+
+    let public_key = read_raw_pointer({public_key_pointer.j().unwrap() as *const SchnorrPk});
+
+    compressed_bytes_to_jbytearray(&_env, serialize_schnorr_pk_compressed(public_key))
+}
 
-    let public_key_object = _env.new_object(public_key_class, "(J)V",
-                                            &[JValue::Long(public_key)])
-        .expect("Cannot create public key object.");
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeDeserializePublicKeyCompressed(
+    _env: JNIEnv,
+    _schnorr_public_key_class: JClass,
+    _public_key_bytes: jbyteArray,
+) -> jobject
+{
+    let pk_bytes = _env.convert_byte_array(_public_key_bytes)
+        .expect("Cannot read public key bytes.");
 
-    *public_key_object
+    boxed_to_jobject(
+        &_env,
+        "com/horizen/schnorrnative/SchnorrPublicKey",
+        deserialize_schnorr_pk_compressed(pk_bytes.as_slice())
+    )
 }
 
 #[no_mangle]
@@ -267,11 +356,10 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeSer
 
     let secret_key = read_raw_pointer({secret_key_pointer.j().unwrap() as *const SchnorrSk});
 
-    let mut sk = [0u8; SCHNORR_SK_SIZE];
-    serialize_from_raw_pointer(secret_key, &mut sk[..]);
-
-    _env.byte_array_from_slice(sk.as_ref())
+    //The temporary buffer is wiped as soon as it has been copied to the Java array
+    with_serialized_secret_key(secret_key, |sk| _env.byte_array_from_slice(sk)
         .expect("Cannot write secret key.")
+    ).expect("unable to write secret key to buffer")
 }
 
 #[no_mangle]
@@ -282,20 +370,12 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeDes
     _secret_key_bytes: jbyteArray,
 ) -> jobject
 {
-    let sk_bytes = _env.convert_byte_array(_secret_key_bytes)
+    let mut sk_bytes = _env.convert_byte_array(_secret_key_bytes)
         .expect("Cannot read public key bytes.");
-    let secret_key_pointer: *const SchnorrSk = deserialize_to_raw_pointer(sk_bytes.as_slice());
-
-    let secret_key: jlong = jlong::from(secret_key_pointer as i64);
-
-    let secret_key_class = _env.find_class("com/horizen/schnorrnative/SchnorrSecretKey")
-        .expect("Cannot find SchnorrSecretKey class.");
-
-    let secret_key_object = _env.new_object(secret_key_class, "(J)V",
-                                            &[JValue::Long(secret_key)])
-        .expect("Cannot create secret key object.");
+    let secret_key = deserialize_checked_to_jobject::<SchnorrSk>(&_env, "com/horizen/schnorrnative/SchnorrSecretKey", sk_bytes.as_slice());
+    wipe(sk_bytes.as_mut_slice());
 
-    *secret_key_object
+    secret_key
 }
 
 #[no_mangle]
@@ -310,6 +390,7 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeFre
     let secret_key = secret_key_pointer.j().unwrap() as *mut SchnorrSk;
 
     if secret_key.is_null()  { return }
+    //Dropping the secret key wipes it from memory
     drop(unsafe { Box::from_raw(secret_key) });
 }
 
@@ -349,18 +430,38 @@ pub extern "system" fn Java_com_horizen_vrfnative_VRFPublicKey_nativeDeserialize
     let pk_bytes = _env.convert_byte_array(_public_key_bytes)
         .expect("Cannot read public key bytes.");
 
-    let public_key_pointer: *mut VRFPk = deserialize_to_raw_pointer(pk_bytes.as_slice());
+    deserialize_checked_to_jobject::<VRFPk>(&_env, "com/horizen/vrfnative/VRFPublicKey", pk_bytes.as_slice())
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_vrfnative_VRFPublicKey_nativeSerializePublicKeyCompressed(
+    _env: JNIEnv,
+    _vrf_public_key: JObject,
+) -> jbyteArray
+{
+    let public_key_pointer = _env.get_field(_vrf_public_key, "publicKeyPointer", "J")
+        .expect("Cannot get public key pointer.");
 
-    let public_key: jlong = jlong::from(public_key_pointer as i64);
+    let public_key = read_raw_pointer({public_key_pointer.j().unwrap() as *const VRFPk});
 
-    let public_key_class = _env.find_class("com/horizen/vrfnative/VRFPublicKey")
-        .expect("Cannot find SchnorrPublicKey class.");
+    compressed_bytes_to_jbytearray(&_env, serialize_vrf_pk_compressed(public_key))
+}
 
-    let public_key_object = _env.new_object(public_key_class, "(J)V",
-                                            &[JValue::Long(public_key)])
-        .expect("Cannot create public key object.");
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_vrfnative_VRFPublicKey_nativeDeserializePublicKeyCompressed(
+    _env: JNIEnv,
+    _vrf_public_key_class: JClass,
+    _public_key_bytes: jbyteArray,
+) -> jobject
+{
+    let pk_bytes = _env.convert_byte_array(_public_key_bytes)
+        .expect("Cannot read public key bytes.");
 
-    *public_key_object
+    boxed_to_jobject(
+        &_env,
+        "com/horizen/vrfnative/VRFPublicKey",
+        deserialize_vrf_pk_compressed(pk_bytes.as_slice())
+    )
 }
 
 #[no_mangle]
@@ -396,11 +497,10 @@ pub extern "system" fn Java_com_horizen_vrfnative_VRFSecretKey_nativeSerializeSe
 
     let secret_key = read_raw_pointer({secret_key_pointer.j().unwrap() as *const VRFSk});
 
-    let mut sk = [0u8; VRF_SK_SIZE];
-    serialize_from_raw_pointer(secret_key, &mut sk[..]);
-
-    _env.byte_array_from_slice(sk.as_ref())
+    //The temporary buffer is wiped as soon as it has been copied to the Java array
+    with_serialized_secret_key(secret_key, |sk| _env.byte_array_from_slice(sk)
         .expect("Cannot write secret key.")
+    ).expect("unable to write secret key to buffer")
 }
 
 #[no_mangle]
@@ -410,21 +510,12 @@ pub extern "system" fn Java_com_horizen_vrfnative_VRFSecretKey_nativeDeserialize
     _secret_key_bytes: jbyteArray,
 ) -> jobject
 {
-    let sk_bytes = _env.convert_byte_array(_secret_key_bytes)
+    let mut sk_bytes = _env.convert_byte_array(_secret_key_bytes)
         .expect("Cannot read public key bytes.");
+    let secret_key = deserialize_checked_to_jobject::<VRFSk>(&_env, "com/horizen/vrfnative/VRFSecretKey", sk_bytes.as_slice());
+    wipe(sk_bytes.as_mut_slice());
 
-    let secret_key_pointer: *mut SchnorrSk = deserialize_to_raw_pointer(sk_bytes.as_slice());
-
-    let secret_key: jlong = jlong::from(secret_key_pointer as i64);
-
-    let secret_key_class = _env.find_class("com/horizen/vrfnative/VRFSecretKey")
-        .expect("Cannot find SchnorrSecretKey class.");
-
-    let secret_key_object = _env.new_object(secret_key_class, "(J)V",
-                                            &[JValue::Long(secret_key)])
-        .expect("Cannot create secret key object.");
-
-    *secret_key_object
+    secret_key
 }
 
 #[no_mangle]
@@ -439,6 +530,7 @@ pub extern "system" fn Java_com_horizen_vrfnative_VRFSecretKey_nativeFreeSecretK
     let secret_key = secret_key_pointer.j().unwrap() as *mut SchnorrSk;
 
     if secret_key.is_null()  { return }
+    //Dropping the secret key wipes it from memory
     drop(unsafe { Box::from_raw(secret_key) });
 }
 
@@ -473,18 +565,7 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSignature_nativeDes
     let sig_bytes = _env.convert_byte_array(_sig_bytes)
         .expect("Should be able to convert to Rust byte array");
 
-    let sig_ptr: *const SchnorrSig = deserialize_to_raw_pointer(sig_bytes.as_slice());
-
-    let sig: jlong = jlong::from(sig_ptr as i64);
-
-    let sig_class = _env.find_class("com/horizen/schnorrnative/SchnorrSignature")
-        .expect("Cannot find SchnorrSignature class.");
-
-    let sig_object = _env.new_object(sig_class, "(J)V",
-                                            &[JValue::Long(sig)])
-        .expect("Cannot create signature object.");
-
-    *sig_object
+    deserialize_checked_to_jobject::<SchnorrSig>(&_env, "com/horizen/schnorrnative/SchnorrSignature", sig_bytes.as_slice())
 }
 
 #[no_mangle]
@@ -540,11 +621,45 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeGener
     *result
 }
 
-#[no_mangle]
-pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeSignMessage(
-    _env: JNIEnv,
+// Reads the FieldElement objects of a Java FieldElement[] array.
+fn read_field_element_array(_env: &JNIEnv, _array: jobjectArray) -> Vec<FieldElement> {
+    let array_len = _env.get_array_length(_array)
+        .expect("Should be able to read field element array size");
+    let mut elements = Vec::with_capacity(array_len as usize);
+
+    for i in 0..array_len {
+        let field_obj = _env.get_object_array_element(_array, i)
+            .expect(format!("Should be able to read elem {} of the field element array", i).as_str());
+
+        let field = {
+
+            let f =_env.get_field(field_obj, "fieldElementPointer", "J")
+                .expect("Should be able to get field fieldElementPointer");
+
+            read_raw_pointer(f.j().unwrap() as *const FieldElement)
+        };
+
+        elements.push(*field);
+    }
+
+    elements
+}
+
+// Messages of the Schnorr signatures exposed to Java: byte messages are signed in their own domain.
+enum SchnorrMessage<'a> {
+    FieldElements(&'a [FieldElement]),
+    Bytes(&'a [u8]),
+}
+
+// Signs `message` with the keys of `_schnorr_key_pair` and returns the SchnorrSignature object,
+// or null on failure. If `_deterministic`, the nonce is derived from keys, message and, if not null,
+// `_extra_randomness`; otherwise it's sampled from the OS rng.
+fn sign_with_schnorr_key_pair(
+    _env: &JNIEnv,
     _schnorr_key_pair: JObject,
-    _message: JObject,
+    message: SchnorrMessage,
+    _deterministic: jboolean,
+    _extra_randomness: jbyteArray,
 ) -> jobject {
 
     //Read sk
@@ -574,17 +689,29 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeSignM
         read_raw_pointer(p.j().unwrap() as *const SchnorrPk)
     };
 
-    //Read message
-    let message = {
-
-        let m =_env.get_field(_message, "fieldElementPointer", "J")
-            .expect("Should be able to get field fieldElementPointer");
-
-        read_raw_pointer(m.j().unwrap() as *const FieldElement)
+    //Read extra randomness, if any: it's only mixed into the nonce of deterministic signatures
+    let extra_randomness = if _extra_randomness.is_null() {
+        None
+    } else {
+        match _env.convert_byte_array(_extra_randomness) {
+            Ok(bytes) => Some(bytes),
+            Err(_) => return std::ptr::null::<jobject>() as jobject,
+        }
     };
 
     //Sign message and return opaque pointer to sig
-    let signature = match schnorr_sign(message, secret_key, public_key) {
+    let extra_randomness = extra_randomness.as_ref().map(|r| r.as_slice());
+    let result = match (message, _deterministic == JNI_TRUE) {
+        (SchnorrMessage::FieldElements(message), true) =>
+            schnorr_sign_field_elements_deterministic(message, secret_key, public_key, extra_randomness),
+        (SchnorrMessage::FieldElements(message), false) =>
+            schnorr_sign_field_elements(message, secret_key, public_key),
+        (SchnorrMessage::Bytes(message), true) =>
+            schnorr_sign_bytes_deterministic(message, secret_key, public_key, extra_randomness),
+        (SchnorrMessage::Bytes(message), false) =>
+            schnorr_sign_bytes(message, secret_key, public_key),
+    };
+    let signature = match result {
         Ok(sig) => Box::into_raw(Box::new(sig)),
         Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
     };
@@ -602,133 +729,2160 @@ pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeSignM
 }
 
 #[no_mangle]
-pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifyKey(
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeSignMessage(
     _env: JNIEnv,
-    _public_key: JObject,
-) -> jboolean
-{
-    let pk = _env.get_field(_public_key, "publicKeyPointer", "J")
-        .expect("Should be able to get field publicKeyPointer").j().unwrap() as *const SchnorrPk;
+    _schnorr_key_pair: JObject,
+    _message: JObject,
+    _deterministic: jboolean,
+    _extra_randomness: jbyteArray,
+) -> jobject {
 
-    if schnorr_verify_public_key(read_raw_pointer(pk)) {
-        JNI_TRUE
-    } else {
-        JNI_FALSE
-    }
+    //Read message
+    let message = {
+
+        let m =_env.get_field(_message, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(m.j().unwrap() as *const FieldElement)
+    };
+
+    sign_with_schnorr_key_pair(
+        &_env, _schnorr_key_pair, SchnorrMessage::FieldElements(&[*message]), _deterministic, _extra_randomness
+    )
 }
 
 #[no_mangle]
-pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeGetPublicKey(
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeSignMessageFieldElements(
     _env: JNIEnv,
-    _secret_key: JObject
+    _schnorr_key_pair: JObject,
+    _message: jobjectArray,
+    _deterministic: jboolean,
+    _extra_randomness: jbyteArray,
 ) -> jobject {
 
-    let sk = _env.get_field(_secret_key, "secretKeyPointer", "J")
-        .expect("Should be able to get field secretKeyPointer").j().unwrap() as *const SchnorrSk;
+    //Read message
+    let message = read_field_element_array(&_env, _message);
 
-    let secret_key = read_raw_pointer(sk);
+    sign_with_schnorr_key_pair(
+        &_env, _schnorr_key_pair, SchnorrMessage::FieldElements(message.as_slice()), _deterministic, _extra_randomness
+    )
+}
 
-    let pk = schnorr_get_public_key(secret_key);
-    let public_key: jlong = jlong::from(Box::into_raw(Box::new(pk)) as i64);
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeSignMessageBytes(
+    _env: JNIEnv,
+    _schnorr_key_pair: JObject,
+    _message: jbyteArray,
+    _deterministic: jboolean,
+    _extra_randomness: jbyteArray,
+) -> jobject {
 
-    let public_key_class =  _env.find_class("com/horizen/schnorrnative/SchnorrPublicKey")
-        .expect("Should be able to find SchnorrPublicKey class");
+    //Read message
+    let message = match _env.convert_byte_array(_message) {
+        Ok(message) => message,
+        Err(_) => return std::ptr::null::<jobject>() as jobject,
+    };
 
-    let result = _env.new_object(public_key_class, "(J)V", &[
-        JValue::Long(public_key)]).expect("Should be able to create new long for SchnorrPk");
+    sign_with_schnorr_key_pair(
+        &_env, _schnorr_key_pair, SchnorrMessage::Bytes(message.as_slice()), _deterministic, _extra_randomness
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrKeyPair_nativeCreateProofOfPossession(
+    _env: JNIEnv,
+    _schnorr_key_pair: JObject,
+) -> jobject {
+
+    //Read sk
+    let sk_object = _env.get_field(_schnorr_key_pair,
+                                   "secretKey",
+                                   "Lcom/horizen/schnorrnative/SchnorrSecretKey;"
+    ).expect("Should be able to get field secretKey").l().unwrap();
+    let secret_key = {
+
+        let s =_env.get_field(sk_object, "secretKeyPointer", "J")
+            .expect("Should be able to get field secretKeyPointer");
+
+        read_raw_pointer(s.j().unwrap() as *const SchnorrSk)
+    };
+
+    //Read pk
+    let pk_object = _env.get_field(_schnorr_key_pair,
+                                   "publicKey",
+                                   "Lcom/horizen/schnorrnative/SchnorrPublicKey;"
+    ).expect("Should be able to get field publicKey").l().unwrap();
+
+    let public_key = {
+
+        let p = _env.get_field(pk_object, "publicKeyPointer", "J")
+            .expect("Should be able to get field publicKeyPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const SchnorrPk)
+    };
+
+    //Create PoP and return opaque pointer to it
+    let pop = match schnorr_create_pop(secret_key, public_key) {
+        Ok(pop) => Box::into_raw(Box::new(pop)),
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    let class = _env.find_class("com/horizen/schnorrnative/SchnorrSignature")
+        .expect("Should be able to find class SchnorrSignature");
+
+    let result =  _env.new_object(class, "(J)V", &[
+        JValue::Long(jlong::from(pop as i64))])
+        .expect("Should be able to create new long for Schnorr proof of possession");
 
     *result
 }
 
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifyProofOfPossession(
+    _env: JNIEnv,
+    _public_key: JObject,
+    _pop: JObject,
+) -> jboolean {
+
+    //Read pk
+    let public_key = {
+
+        let p = _env.get_field(_public_key, "publicKeyPointer", "J")
+            .expect("Should be able to get field publicKeyPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const SchnorrPk)
+    };
+
+    //Read PoP
+    let pop = {
+        let pop = _env.get_field(_pop, "signaturePointer", "J")
+            .expect("Should be able to get field signaturePointer");
+
+        read_raw_pointer(pop.j().unwrap() as *const SchnorrPoP)
+    };
+
+    //Verify PoP
+    match schnorr_verify_pop(public_key, pop) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+//Schnorr multi-signature functions
+
+// Reads the value pointed by the `pointer_field` of `_object`.
+fn read_pointer_field<'a, T>(_env: &JNIEnv, _object: JObject, pointer_field: &str) -> &'a T {
+    let pointer = _env.get_field(_object, pointer_field, "J")
+        .expect(format!("Should be able to get field {}", pointer_field).as_str());
+
+    read_raw_pointer(pointer.j().unwrap() as *const T)
+}
+
+// Reads the values pointed by the `pointer_field` of the objects of a Java array.
+fn read_pointer_array<T: Clone>(_env: &JNIEnv, _array: jobjectArray, pointer_field: &str) -> Vec<T> {
+    let array_len = _env.get_array_length(_array)
+        .expect("Should be able to read array size");
+
+    (0..array_len).map(|i| {
+        let object = _env.get_object_array_element(_array, i)
+            .expect(format!("Should be able to read elem {} of the array", i).as_str());
+
+        read_pointer_field::<T>(_env, object, pointer_field).clone()
+    }).collect()
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigNonce_nativeGenerate(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jobject
+{
+    boxed_to_jobject(&_env, "com/horizen/schnorrnative/SchnorrMultiSigNonce", Ok(musig_generate_nonce()))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigNonce_nativeGetCommitment(
+    _env: JNIEnv,
+    _nonce: JObject,
+) -> jobject
+{
+    let nonce = read_pointer_field::<MuSigSecretNonce>(&_env, _nonce, "noncePointer");
+
+    boxed_to_jobject(&_env, "com/horizen/librustsidechains/FieldElement", musig_nonce_commitment(nonce.public_nonce()))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigNonce_nativeGetPublicNonce(
+    _env: JNIEnv,
+    _nonce: JObject,
+) -> jobject
+{
+    let nonce = read_pointer_field::<MuSigSecretNonce>(&_env, _nonce, "noncePointer");
+
+    boxed_to_jobject(&_env, "com/horizen/schnorrnative/SchnorrMultiSigPublicNonce", Ok(*nonce.public_nonce()))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigNonce_nativePartialSign(
+    _env: JNIEnv,
+    _nonce: JObject,
+    _secret_key: JObject,
+    _signers_pks: jobjectArray,
+    _signer_index: jint,
+    _aggregated_nonce: JObject,
+    _message: JObject,
+) -> jobject
+{
+    //Take ownership of the nonce: it's consumed by signing, whatever the result
+    let nonce = {
+        let n = _env.get_field(_nonce, "noncePointer", "J")
+            .expect("Should be able to get field noncePointer");
+
+        let n = n.j().unwrap() as *mut MuSigSecretNonce;
+        assert!(!n.is_null());
+        *unsafe { Box::from_raw(n) }
+    };
+
+    let secret_key = read_pointer_field::<SchnorrSk>(&_env, _secret_key, "secretKeyPointer");
+    let pks = read_pointer_array::<SchnorrPk>(&_env, _signers_pks, "publicKeyPointer");
+    let aggregated_nonce = read_pointer_field::<MuSigPublicNonce>(&_env, _aggregated_nonce, "publicNoncePointer");
+    let message = read_pointer_field::<FieldElement>(&_env, _message, "fieldElementPointer");
+
+    if _signer_index < 0 {
+        return std::ptr::null::<jobject>() as jobject
+    }
+
+    boxed_to_jobject(
+        &_env,
+        "com/horizen/schnorrnative/SchnorrMultiSigPartialSignature",
+        musig_partial_sign(nonce, secret_key, pks.as_slice(), _signer_index as usize, aggregated_nonce, &[*message])
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigNonce_nativeFreeNonce(
+    _env: JNIEnv,
+    _class: JClass,
+    _nonce: *mut MuSigSecretNonce,
+)
+{
+    if _nonce.is_null()  { return }
+    drop(unsafe { Box::from_raw(_nonce) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPublicNonce_nativeSerializePublicNonce(
+    _env: JNIEnv,
+    _class: JClass,
+    _public_nonce: *const MuSigPublicNonce,
+) -> jbyteArray
+{
+    let mut public_nonce = [0u8; G1_SIZE];
+    serialize_from_raw_pointer(_public_nonce, &mut public_nonce[..]);
+
+    _env.byte_array_from_slice(public_nonce.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPublicNonce_nativeDeserializePublicNonce(
+    _env: JNIEnv,
+    _class: JClass,
+    _public_nonce_bytes: jbyteArray,
+) -> jobject
+{
+    let public_nonce_bytes = _env.convert_byte_array(_public_nonce_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialize_checked_to_jobject::<MuSigPublicNonce>(
+        &_env,
+        "com/horizen/schnorrnative/SchnorrMultiSigPublicNonce",
+        public_nonce_bytes.as_slice()
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPublicNonce_nativeFreePublicNonce(
+    _env: JNIEnv,
+    _class: JClass,
+    _public_nonce: *mut MuSigPublicNonce,
+)
+{
+    if _public_nonce.is_null()  { return }
+    drop(unsafe { Box::from_raw(_public_nonce) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPublicNonce_nativeAggregate(
+    _env: JNIEnv,
+    _class: JClass,
+    _public_nonces: jobjectArray,
+    _commitments: jobjectArray,
+) -> jobject
+{
+    let public_nonces = read_pointer_array::<MuSigPublicNonce>(&_env, _public_nonces, "publicNoncePointer");
+    let commitments = read_field_element_array(&_env, _commitments);
+
+    boxed_to_jobject(
+        &_env,
+        "com/horizen/schnorrnative/SchnorrMultiSigPublicNonce",
+        musig_aggregate_nonces(public_nonces.as_slice(), commitments.as_slice())
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPartialSignature_nativeSerializePartialSignature(
+    _env: JNIEnv,
+    _class: JClass,
+    _partial_sig: *const MuSigPartialSig,
+) -> jbyteArray
+{
+    let mut partial_sig = [0u8; MUSIG_PARTIAL_SIG_SIZE];
+    serialize_from_raw_pointer(_partial_sig, &mut partial_sig[..]);
+
+    _env.byte_array_from_slice(partial_sig.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPartialSignature_nativeDeserializePartialSignature(
+    _env: JNIEnv,
+    _class: JClass,
+    _partial_sig_bytes: jbyteArray,
+) -> jobject
+{
+    let partial_sig_bytes = _env.convert_byte_array(_partial_sig_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialize_checked_to_jobject::<MuSigPartialSig>(
+        &_env,
+        "com/horizen/schnorrnative/SchnorrMultiSigPartialSignature",
+        partial_sig_bytes.as_slice()
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPartialSignature_nativeFreePartialSignature(
+    _env: JNIEnv,
+    _class: JClass,
+    _partial_sig: *mut MuSigPartialSig,
+)
+{
+    if _partial_sig.is_null()  { return }
+    drop(unsafe { Box::from_raw(_partial_sig) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSigPartialSignature_nativeVerify(
+    _env: JNIEnv,
+    _partial_sig: JObject,
+    _public_nonce: JObject,
+    _signers_pks: jobjectArray,
+    _signer_index: jint,
+    _aggregated_nonce: JObject,
+    _message: JObject,
+) -> jboolean
+{
+    let partial_sig = read_pointer_field::<MuSigPartialSig>(&_env, _partial_sig, "partialSignaturePointer");
+    let public_nonce = read_pointer_field::<MuSigPublicNonce>(&_env, _public_nonce, "publicNoncePointer");
+    let pks = read_pointer_array::<SchnorrPk>(&_env, _signers_pks, "publicKeyPointer");
+    let aggregated_nonce = read_pointer_field::<MuSigPublicNonce>(&_env, _aggregated_nonce, "publicNoncePointer");
+    let message = read_pointer_field::<FieldElement>(&_env, _message, "fieldElementPointer");
+
+    if _signer_index < 0 {
+        return JNI_FALSE
+    }
+
+    match musig_verify_partial_signature(
+        partial_sig, public_nonce, pks.as_slice(), _signer_index as usize, aggregated_nonce, &[*message]
+    ) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSignature_nativeSerializeSignature(
+    _env: JNIEnv,
+    _class: JClass,
+    _sig: *const SchnorrMultiSig,
+) -> jbyteArray
+{
+    let mut sig = [0u8; SCHNORR_MULTISIG_SIZE];
+    serialize_from_raw_pointer(_sig, &mut sig[..]);
+
+    _env.byte_array_from_slice(sig.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSignature_nativeDeserializeSignature(
+    _env: JNIEnv,
+    _class: JClass,
+    _sig_bytes: jbyteArray,
+) -> jobject
+{
+    let sig_bytes = _env.convert_byte_array(_sig_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialize_checked_to_jobject::<SchnorrMultiSig>(&_env, "com/horizen/schnorrnative/SchnorrMultiSignature", sig_bytes.as_slice())
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSignature_nativeFreeSignature(
+    _env: JNIEnv,
+    _class: JClass,
+    _sig: *mut SchnorrMultiSig,
+)
+{
+    if _sig.is_null()  { return }
+    drop(unsafe { Box::from_raw(_sig) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSignature_nativeAggregatePublicKeys(
+    _env: JNIEnv,
+    _class: JClass,
+    _signers_pks: jobjectArray,
+) -> jobject
+{
+    let pks = read_pointer_array::<SchnorrPk>(&_env, _signers_pks, "publicKeyPointer");
+
+    boxed_to_jobject(&_env, "com/horizen/schnorrnative/SchnorrPublicKey", musig_aggregate_public_keys(pks.as_slice()))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSignature_nativeCombine(
+    _env: JNIEnv,
+    _class: JClass,
+    _aggregated_nonce: JObject,
+    _partial_sigs: jobjectArray,
+) -> jobject
+{
+    let aggregated_nonce = read_pointer_field::<MuSigPublicNonce>(&_env, _aggregated_nonce, "publicNoncePointer");
+    let partial_sigs = read_pointer_array::<MuSigPartialSig>(&_env, _partial_sigs, "partialSignaturePointer");
+
+    boxed_to_jobject(
+        &_env,
+        "com/horizen/schnorrnative/SchnorrMultiSignature",
+        musig_combine_partial_signatures(aggregated_nonce, partial_sigs.as_slice())
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrMultiSignature_nativeVerify(
+    _env: JNIEnv,
+    _sig: JObject,
+    _aggregated_pk: JObject,
+    _message: JObject,
+) -> jboolean
+{
+    let sig = read_pointer_field::<SchnorrMultiSig>(&_env, _sig, "signaturePointer");
+    let aggregated_pk = read_pointer_field::<SchnorrPk>(&_env, _aggregated_pk, "publicKeyPointer");
+    let message = read_pointer_field::<FieldElement>(&_env, _message, "fieldElementPointer");
+
+    match musig_verify_signature(&[*message], aggregated_pk, sig) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifyKey(
+    _env: JNIEnv,
+    _public_key: JObject,
+) -> jboolean
+{
+    let pk = _env.get_field(_public_key, "publicKeyPointer", "J")
+        .expect("Should be able to get field publicKeyPointer").j().unwrap() as *const SchnorrPk;
+
+    if schnorr_verify_public_key(read_raw_pointer(pk)) {
+        JNI_TRUE
+    } else {
+        JNI_FALSE
+    }
+}
+
+// Derives the secret key of `key_type` at `_path` from `_seed` and returns it as a `secret_key_class`
+// object, or null if the seed or the path are invalid.
+fn derive_secret_key_from_seed(
+    _env: &JNIEnv,
+    _seed: jbyteArray,
+    _path: JString,
+    key_type: HDKeyType,
+    secret_key_class: &str,
+) -> jobject
+{
+    let mut seed = match _env.convert_byte_array(_seed) {
+        Ok(seed) => seed,
+        Err(_) => return std::ptr::null::<jobject>() as jobject,
+    };
+
+    let path: String = match _env.get_string(_path) {
+        Ok(path) => path.into(),
+        Err(_) => {
+            wipe(seed.as_mut_slice());
+            return std::ptr::null::<jobject>() as jobject
+        },
+    };
+
+    let key = parse_hd_path(path.as_str())
+        .and_then(|path| HDExtendedSecretKey::master(key_type, seed.as_slice())?.derive_path(path.as_slice()));
+    wipe(seed.as_mut_slice());
+
+    let secret_key = match key {
+        Ok(key) => key.secret_key,
+        Err(_) => return std::ptr::null::<jobject>() as jobject,
+    };
+
+    let secret_key: jlong = jlong::from(Box::into_raw(Box::new(secret_key)) as i64);
+
+    let class = _env.find_class(secret_key_class)
+        .expect("Should be able to find secret key class");
+
+    let result = _env.new_object(class, "(J)V", &[JValue::Long(secret_key)])
+        .expect("Should be able to create new secret key object");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeDeriveFromSeed(
+    _env: JNIEnv,
+    _class: JClass,
+    _seed: jbyteArray,
+    _path: JString,
+) -> jobject
+{
+    derive_secret_key_from_seed(&_env, _seed, _path, HDKeyType::Schnorr, "com/horizen/schnorrnative/SchnorrSecretKey")
+}
+
+// Encrypts the secret key of `_secret_key` with `_password` and returns the keystore bytes,
+// or null on failure.
+fn encrypt_secret_key_object(
+    _env: &JNIEnv,
+    _secret_key: JObject,
+    _password: jbyteArray,
+    key_type: HDKeyType,
+) -> jbyteArray
+{
+    //SchnorrSk and VRFSk are the same scalar type
+    let sk = _env.get_field(_secret_key, "secretKeyPointer", "J")
+        .expect("Should be able to get field secretKeyPointer").j().unwrap() as *const SchnorrSk;
+
+    let secret_key = read_raw_pointer(sk);
+
+    let mut password = match _env.convert_byte_array(_password) {
+        Ok(password) => password,
+        Err(_) => return std::ptr::null::<jobject>() as jbyteArray,
+    };
+
+    let keystore = encrypt_secret_key(secret_key, key_type, password.as_slice());
+    wipe(password.as_mut_slice());
+
+    match keystore {
+        Ok(keystore) => _env.byte_array_from_slice(keystore.as_slice())
+            .expect("Should be able to convert to jbyteArray"),
+        Err(_) => std::ptr::null::<jobject>() as jbyteArray,
+    }
+}
+
+// Decrypts the secret key stored in `_keystore` with `_password` and returns it as a `secret_key_class`
+// object, or null if the keystore is corrupted, holds another type of key or the password is wrong.
+fn decrypt_secret_key_object(
+    _env: &JNIEnv,
+    _keystore: jbyteArray,
+    _password: jbyteArray,
+    key_type: HDKeyType,
+    secret_key_class: &str,
+) -> jobject
+{
+    let keystore = match _env.convert_byte_array(_keystore) {
+        Ok(keystore) => keystore,
+        Err(_) => return std::ptr::null::<jobject>() as jobject,
+    };
+
+    let mut password = match _env.convert_byte_array(_password) {
+        Ok(password) => password,
+        Err(_) => return std::ptr::null::<jobject>() as jobject,
+    };
+
+    let secret_key = decrypt_secret_key(keystore.as_slice(), key_type, password.as_slice());
+    wipe(password.as_mut_slice());
+
+    let secret_key = match secret_key {
+        Ok(sk) => sk,
+        Err(_) => return std::ptr::null::<jobject>() as jobject,
+    };
+
+    let secret_key: jlong = jlong::from(Box::into_raw(Box::new(secret_key)) as i64);
+
+    let class = _env.find_class(secret_key_class)
+        .expect("Should be able to find secret key class");
+
+    let result = _env.new_object(class, "(J)V", &[JValue::Long(secret_key)])
+        .expect("Should be able to create new secret key object");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeEncrypt(
+    _env: JNIEnv,
+    _secret_key: JObject,
+    _password: jbyteArray,
+) -> jbyteArray
+{
+    encrypt_secret_key_object(&_env, _secret_key, _password, HDKeyType::Schnorr)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeDecrypt(
+    _env: JNIEnv,
+    _class: JClass,
+    _keystore: jbyteArray,
+    _password: jbyteArray,
+) -> jobject
+{
+    decrypt_secret_key_object(&_env, _keystore, _password, HDKeyType::Schnorr, "com/horizen/schnorrnative/SchnorrSecretKey")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrSecretKey_nativeGetPublicKey(
+    _env: JNIEnv,
+    _secret_key: JObject
+) -> jobject {
+
+    let sk = _env.get_field(_secret_key, "secretKeyPointer", "J")
+        .expect("Should be able to get field secretKeyPointer").j().unwrap() as *const SchnorrSk;
+
+    let secret_key = read_raw_pointer(sk);
+
+    let pk = schnorr_get_public_key(secret_key);
+    let public_key: jlong = jlong::from(Box::into_raw(Box::new(pk)) as i64);
+
+    let public_key_class =  _env.find_class("com/horizen/schnorrnative/SchnorrPublicKey")
+        .expect("Should be able to find SchnorrPublicKey class");
+
+    let result = _env.new_object(public_key_class, "(J)V", &[
+        JValue::Long(public_key)]).expect("Should be able to create new long for SchnorrPk");
+
+    *result
+}
+
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifySignature(
+    _env: JNIEnv,
+    _public_key: JObject,
+    _signature: JObject,
+    _message: JObject,
+) -> jboolean {
+
+    //Read pk
+    let public_key = {
+
+        let p = _env.get_field(_public_key, "publicKeyPointer", "J")
+            .expect("Should be able to get field publicKeyPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const SchnorrPk)
+    };
+
+    //Read message
+    let message = {
+
+        let m =_env.get_field(_message, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(m.j().unwrap() as *const FieldElement)
+    };
+
+    //Read sig
+    let signature = {
+        let sig = _env.get_field(_signature, "signaturePointer", "J")
+            .expect("Should be able to get field signaturePointer");
+
+        read_raw_pointer(sig.j().unwrap() as *const SchnorrSig)
+    };
+
+    //Verify sig
+    match schnorr_verify_signature(message, public_key, signature) {
+        Ok(result) => if result {
+            JNI_TRUE
+        } else {
+            JNI_FALSE
+        },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+// Verifies `signature` on `message` against `_public_key`; false on failure.
+fn verify_with_schnorr_public_key(
+    _env: &JNIEnv,
+    _public_key: JObject,
+    _signature: JObject,
+    message: SchnorrMessage,
+) -> jboolean {
+
+    //Read pk
+    let public_key = {
+
+        let p = _env.get_field(_public_key, "publicKeyPointer", "J")
+            .expect("Should be able to get field publicKeyPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const SchnorrPk)
+    };
+
+    //Read sig
+    let signature = {
+        let sig = _env.get_field(_signature, "signaturePointer", "J")
+            .expect("Should be able to get field signaturePointer");
+
+        read_raw_pointer(sig.j().unwrap() as *const SchnorrSig)
+    };
+
+    //Verify sig
+    let result = match message {
+        SchnorrMessage::FieldElements(message) => schnorr_verify_signature_field_elements(message, public_key, signature),
+        SchnorrMessage::Bytes(message) => schnorr_verify_signature_bytes(message, public_key, signature),
+    };
+    match result {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifySignatureFieldElements(
+    _env: JNIEnv,
+    _public_key: JObject,
+    _signature: JObject,
+    _message: jobjectArray,
+) -> jboolean {
+
+    //Read message
+    let message = read_field_element_array(&_env, _message);
+
+    verify_with_schnorr_public_key(&_env, _public_key, _signature, SchnorrMessage::FieldElements(message.as_slice()))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifySignatureBytes(
+    _env: JNIEnv,
+    _public_key: JObject,
+    _signature: JObject,
+    _message: jbyteArray,
+) -> jboolean {
+
+    //Read message
+    let message = match _env.convert_byte_array(_message) {
+        Ok(message) => message,
+        Err(_) => return JNI_FALSE,
+    };
+
+    verify_with_schnorr_public_key(&_env, _public_key, _signature, SchnorrMessage::Bytes(message.as_slice()))
+}
+
+// Reads the (pk, msg, sig) triples of a parallel verification. Triples whose signature pointer is null
+// can't be verified: their positions are returned separately.
+fn read_schnorr_verification_items(
+    _env: &JNIEnv,
+    _public_keys: jobjectArray,
+    _messages: jobjectArray,
+    _signatures: jobjectArray,
+) -> Option<(Vec<(SchnorrPk, FieldElement, SchnorrSig)>, Vec<usize>)>
+{
+    let batch_size = _env.get_array_length(_public_keys)
+        .expect("Should be able to read public keys array size");
+
+    if _env.get_array_length(_messages).expect("Should be able to read messages array size") != batch_size ||
+        _env.get_array_length(_signatures).expect("Should be able to read signatures array size") != batch_size {
+        return None
+    }
+
+    let mut items = Vec::with_capacity(batch_size as usize);
+    let mut null_sigs = vec![];
+
+    for i in 0..batch_size {
+        let pk_object = _env.get_object_array_element(_public_keys, i)
+            .expect(format!("Should be able to get elem {} of public keys array", i).as_str());
+
+        let msg_object = _env.get_object_array_element(_messages, i)
+            .expect(format!("Should be able to get elem {} of messages array", i).as_str());
+
+        let sig_object = _env.get_object_array_element(_signatures, i)
+            .expect(format!("Should be able to get elem {} of signatures array", i).as_str());
+
+        let public_key = {
+            let pk = _env.get_field(pk_object, "publicKeyPointer", "J")
+                .expect("Should be able to get field publicKeyPointer");
+
+            read_raw_pointer(pk.j().unwrap() as *const SchnorrPk)
+        };
+
+        let message = {
+            let m = _env.get_field(msg_object, "fieldElementPointer", "J")
+                .expect("Should be able to get field fieldElementPointer");
+
+            read_raw_pointer(m.j().unwrap() as *const FieldElement)
+        };
+
+        let signature = {
+            let sig = _env.get_field(sig_object, "signaturePointer", "J")
+                .expect("Should be able to get field signaturePointer");
+
+            read_nullable_raw_pointer(sig.j().unwrap() as *const SchnorrSig)
+        };
+
+        match signature {
+            Some(signature) => items.push((*public_key, *message, *signature)),
+            None => null_sigs.push(i as usize),
+        }
+    }
+
+    Some((items, null_sigs))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifyBatch(
+    _env: JNIEnv,
+    _class: JClass,
+    _public_keys: jobjectArray,
+    _messages: jobjectArray,
+    _signatures: jobjectArray,
+) -> jboolean
+{
+    let (items, null_sigs) = match read_schnorr_verification_items(&_env, _public_keys, _messages, _signatures) {
+        Some(batch) => batch,
+        None => return JNI_FALSE
+    };
+
+    if !null_sigs.is_empty() { return JNI_FALSE }
+
+    //Verify sigs
+    match schnorr_verify_signatures_all(items.as_slice()) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifyBatchPerItem(
+    _env: JNIEnv,
+    _class: JClass,
+    _public_keys: jobjectArray,
+    _messages: jobjectArray,
+    _signatures: jobjectArray,
+) -> jbyteArray
+{
+    let (items, null_sigs) = match read_schnorr_verification_items(&_env, _public_keys, _messages, _signatures) {
+        Some(batch) => batch,
+        None => return std::ptr::null::<jbyteArray>() as jbyteArray
+    };
+
+    //Verify sigs
+    let verdicts = match schnorr_verify_signatures_per_item(items.as_slice()) {
+        Ok(verdicts) => verdicts,
+        Err(_) => return std::ptr::null::<jbyteArray>() as jbyteArray //CRYPTO_ERROR
+    };
+
+    //Return one byte for each item: 1 if the signature is valid, 0 otherwise
+    let batch_size = items.len() + null_sigs.len();
+    let mut result = Vec::with_capacity(batch_size);
+    let mut verdicts = verdicts.into_iter();
+    let mut null_sigs = null_sigs.into_iter().peekable();
+    for i in 0..batch_size {
+        if null_sigs.peek() == Some(&i) {
+            null_sigs.next();
+            result.push(0u8);
+        } else {
+            result.push(verdicts.next().unwrap() as u8);
+        }
+    }
+
+    _env.byte_array_from_slice(result.as_slice())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeComputeHash(
+    _env: JNIEnv,
+    _class: JClass,
+    _input: jobjectArray,
+) -> jobject
+{
+    //Read _input as array of FieldElement
+    let input_len = _env.get_array_length(_input)
+        .expect("Should be able to read input array size");
+    let mut input = vec![];
+
+    for i in 0..input_len {
+        let field_obj = _env.get_object_array_element(_input, i)
+            .expect(format!("Should be able to read elem {} of the input array", i).as_str());
+
+        let field = {
+
+            let f =_env.get_field(field_obj, "fieldElementPointer", "J")
+                .expect("Should be able to get field fieldElementPointer");
+
+            read_raw_pointer(f.j().unwrap() as *const FieldElement)
+        };
+
+        input.push(*field);
+    }
+
+    //Compute hash
+    let hash = match compute_poseidon_hash(input.as_slice()) {
+        Ok(hash) => hash,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return hash
+    let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(hash)) as i64);
+
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object(field_class, "(J)V", &[
+        JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeComputeHashBatch(
+    _env: JNIEnv,
+    _class: JClass,
+    _inputs: jobjectArray,
+) -> jobjectArray
+{
+    //Read _inputs as array of arrays of FieldElement
+    let inputs_len = _env.get_array_length(_inputs)
+        .expect("Should be able to read inputs array size");
+    let mut inputs = Vec::with_capacity(inputs_len as usize);
+
+    for i in 0..inputs_len {
+        let input_obj = _env.get_object_array_element(_inputs, i)
+            .expect(format!("Should be able to read elem {} of the inputs array", i).as_str());
+
+        let input_len = _env.get_array_length(input_obj.into_inner())
+            .expect("Should be able to read input array size");
+        let mut input = Vec::with_capacity(input_len as usize);
+
+        for j in 0..input_len {
+            let field_obj = _env.get_object_array_element(input_obj.into_inner(), j)
+                .expect(format!("Should be able to read elem {} of the input array {}", j, i).as_str());
+
+            let field = {
+
+                let f =_env.get_field(field_obj, "fieldElementPointer", "J")
+                    .expect("Should be able to get field fieldElementPointer");
+
+                read_raw_pointer(f.j().unwrap() as *const FieldElement)
+            };
+
+            input.push(*field);
+        }
+
+        //Big batches may exceed the local references capacity otherwise
+        _env.delete_local_ref(input_obj)
+            .expect("Should be able to delete local reference");
+
+        inputs.push(input);
+    }
+
+    //Compute hashes
+    let hashes = match compute_poseidon_hash_batch(inputs.as_slice()) {
+        Ok(hashes) => hashes,
+        Err(_) => return std::ptr::null::<jobjectArray>() as jobjectArray //CRYPTO_ERROR
+    };
+
+    //Return hashes
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object_array(hashes.len() as i32, field_class, JObject::null())
+        .expect("Should be able to create array of FieldElements");
+
+    for (i, hash) in hashes.into_iter().enumerate() {
+        let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(hash)) as i64);
+
+        let field_element = _env.new_object(field_class, "(J)V", &[
+            JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+        _env.set_object_array_element(result, i as i32, field_element)
+            .expect("Should be able to add FieldElement to the array");
+
+        _env.delete_local_ref(field_element)
+            .expect("Should be able to delete local reference");
+    }
+
+    result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeHashBytes(
+    _env: JNIEnv,
+    _class: JClass,
+    _input: jbyteArray,
+) -> jobject
+{
+    let input = match _env.convert_byte_array(_input) {
+        Ok(input) => input,
+        Err(_) => return std::ptr::null::<jobject>() as jobject
+    };
+
+    //Compute hash
+    let hash = match compute_poseidon_hash_bytes(input.as_slice()) {
+        Ok(hash) => hash,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return hash
+    let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(hash)) as i64);
+
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object(field_class, "(J)V", &[
+        JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeGetPoseidonHash(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jobject
+{
+    let hasher_ptr: jlong = jlong::from(Box::into_raw(Box::new(BufferedPoseidonHasher::new())) as i64);
+
+    let hasher_class =  _env.find_class("com/horizen/poseidonnative/PoseidonHash")
+        .expect("Should be able to find PoseidonHash class");
+
+    let result = _env.new_object(hasher_class, "(J)V", &[
+        JValue::Long(hasher_ptr)]).expect("Should be able to create new long for PoseidonHash");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeUpdate(
+    _env: JNIEnv,
+    _hasher: JObject,
+    _input: JObject,
+)
+{
+    let hasher = {
+
+        let h =_env.get_field(_hasher, "poseidonHashPointer", "J")
+            .expect("Should be able to get field poseidonHashPointer");
+
+        read_mut_raw_pointer(h.j().unwrap() as *mut BufferedPoseidonHasher)
+    };
+
+    //Read input
+    let input = {
+
+        let f =_env.get_field(_input, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
+    };
+
+    hasher.update(input);
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeFinalize(
+    _env: JNIEnv,
+    _hasher: JObject,
+) -> jobject
+{
+    let hasher = {
+
+        let h =_env.get_field(_hasher, "poseidonHashPointer", "J")
+            .expect("Should be able to get field poseidonHashPointer");
+
+        read_raw_pointer(h.j().unwrap() as *const BufferedPoseidonHasher)
+    };
+
+    //Compute hash
+    let hash = match hasher.finalize() {
+        Ok(hash) => hash,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return hash
+    let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(hash)) as i64);
+
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object(field_class, "(J)V", &[
+        JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeReset(
+    _env: JNIEnv,
+    _hasher: JObject,
+)
+{
+    let hasher = {
+
+        let h =_env.get_field(_hasher, "poseidonHashPointer", "J")
+            .expect("Should be able to get field poseidonHashPointer");
+
+        read_mut_raw_pointer(h.j().unwrap() as *mut BufferedPoseidonHasher)
+    };
+
+    hasher.reset();
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeFreePoseidonHash(
+    _env: JNIEnv,
+    _class: JClass,
+    _hasher: *mut BufferedPoseidonHasher,
+)
+{
+    if _hasher.is_null()  { return }
+    drop(unsafe { Box::from_raw(_hasher) });
+}
+
+//Merkle tree functions
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleTree_nativeInit(
+    _env: JNIEnv,
+    // this is the class that owns our
+    // static method. Not going to be
+    // used, but still needs to have
+    // an argument slot
+    _class: JClass,
+    _leaves: jobjectArray,
+) -> jobject
+{
+    //Read _leaves as array of FieldElement
+    let leaves_len = _env.get_array_length(_leaves)
+        .expect("Should be able to read leaves array size");
+    let mut leaves = vec![];
+
+    for i in 0..leaves_len {
+        let field_obj = _env.get_object_array_element(_leaves, i)
+            .expect(format!("Should be able to read elem {} of the leaves array", i).as_str());
+
+        let field = {
+
+            let f =_env.get_field(field_obj, "fieldElementPointer", "J")
+                .expect("Should be able to get field fieldElementPointer");
+
+            read_raw_pointer(f.j().unwrap() as *const FieldElement)
+        };
+
+        leaves.push(*field);
+    }
+
+    //Build the tree
+    let mt = match new_ginger_merkle_tree(leaves.as_slice()) {
+        Ok(mt) => mt,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return the tree
+    let mt_ptr: jlong = jlong::from(Box::into_raw(Box::new(mt)) as i64);
+
+    let mt_class =  _env.find_class("com/horizen/merkletreenative/MerkleTree")
+        .expect("Should be able to find MerkleTree class");
+
+    let result = _env.new_object(mt_class, "(J)V", &[
+        JValue::Long(mt_ptr)]).expect("Should be able to create new long for MerkleTree");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleTree_nativeRoot(
+    _env: JNIEnv,
+    _tree: JObject,
+) -> jobject
+{
+    let tree = {
+
+        let t =_env.get_field(_tree, "merkleTreePointer", "J")
+            .expect("Should be able to get field merkleTreePointer");
+
+        read_raw_pointer(t.j().unwrap() as *const GingerMerkleTree)
+    };
+
+    let root = get_ginger_merkle_root(tree);
+
+    //Return root
+    let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(root)) as i64);
+
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object(field_class, "(J)V", &[
+        JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleTree_nativeGetMerklePath(
+    _env: JNIEnv,
+    _tree: JObject,
+    _leaf: JObject,
+    _leaf_index: jlong,
+) -> jobject
+{
+    let tree = {
+
+        let t =_env.get_field(_tree, "merkleTreePointer", "J")
+            .expect("Should be able to get field merkleTreePointer");
+
+        read_raw_pointer(t.j().unwrap() as *const GingerMerkleTree)
+    };
+
+    //Read leaf
+    let leaf = {
+
+        let f =_env.get_field(_leaf, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
+    };
+
+    //Compute path
+    let path = match get_ginger_merkle_path(leaf, _leaf_index as usize, tree) {
+        Ok(path) => path,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return path
+    let path_ptr: jlong = jlong::from(Box::into_raw(Box::new(path)) as i64);
+
+    let path_class =  _env.find_class("com/horizen/merkletreenative/MerklePath")
+        .expect("Should be able to find MerklePath class");
+
+    let result = _env.new_object(path_class, "(J)V", &[
+        JValue::Long(path_ptr)]).expect("Should be able to create new long for MerklePath");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleTree_nativeFreeMerkleTree(
+    _env: JNIEnv,
+    _class: JClass,
+    _tree: *mut GingerMerkleTree,
+)
+{
+    if _tree.is_null()  { return }
+    drop(unsafe { Box::from_raw(_tree) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerklePath_nativeGetPathSize(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jint { GINGER_MERKLE_PATH_SIZE as jint }
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerklePath_nativeVerify(
+    _env: JNIEnv,
+    _path: JObject,
+    _leaf: JObject,
+    _root: JObject,
+) -> jboolean
+{
+    let path = {
+
+        let p =_env.get_field(_path, "merklePathPointer", "J")
+            .expect("Should be able to get field merklePathPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const GingerMerkleTreePath)
+    };
+
+    //Read leaf
+    let leaf = {
+
+        let f =_env.get_field(_leaf, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
+    };
+
+    //Read root
+    let root = {
+
+        let f =_env.get_field(_root, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
+    };
+
+    //Verify path
+    match verify_ginger_merkle_path(path, root, leaf) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerklePath_nativeSerialize(
+    _env: JNIEnv,
+    _path: JObject,
+) -> jbyteArray
+{
+    let path = {
+
+        let p =_env.get_field(_path, "merklePathPointer", "J")
+            .expect("Should be able to get field merklePathPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const GingerMerkleTreePath)
+    };
+
+    let mut path_bytes = [0u8; GINGER_MERKLE_PATH_SIZE];
+    serialize_ginger_merkle_path(path, &mut path_bytes[..])
+        .expect("Should be able to write merkle path into path_bytes");
+
+    _env.byte_array_from_slice(path_bytes.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerklePath_nativeDeserialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _path_bytes: jbyteArray,
+) -> jobject
+{
+    let path_bytes = _env.convert_byte_array(_path_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialized_to_jobject(
+        &_env, "com/horizen/merkletreenative/MerklePath", deserialize_ginger_merkle_path(path_bytes.as_slice())
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerklePath_nativeFreeMerklePath(
+    _env: JNIEnv,
+    _class: JClass,
+    _path: *mut GingerMerkleTreePath,
+)
+{
+    if _path.is_null()  { return }
+    drop(unsafe { Box::from_raw(_path) });
+}
+
+//Merkle multi path functions
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleTree_nativeGetMerkleMultiPath(
+    _env: JNIEnv,
+    _tree: JObject,
+    _leaves: jobjectArray,
+    _leaf_indices: jbyteArray,
+) -> jobject
+{
+    let tree = {
+
+        let t =_env.get_field(_tree, "merkleTreePointer", "J")
+            .expect("Should be able to get field merkleTreePointer");
+
+        read_raw_pointer(t.j().unwrap() as *const GingerMerkleTree)
+    };
+
+    //Read _leaves as array of FieldElement
+    let leaves_len = _env.get_array_length(_leaves)
+        .expect("Should be able to read leaves array size");
+    let mut leaves = vec![];
+
+    for i in 0..leaves_len {
+        let field_obj = _env.get_object_array_element(_leaves, i)
+            .expect(format!("Should be able to read elem {} of the leaves array", i).as_str());
+
+        let field = {
+
+            let f =_env.get_field(field_obj, "fieldElementPointer", "J")
+                .expect("Should be able to get field fieldElementPointer");
+
+            read_raw_pointer(f.j().unwrap() as *const FieldElement)
+        };
+
+        leaves.push(*field);
+    }
+
+    //Read _leaf_indices as a sequence of 8 bytes little endian integers
+    let leaf_indices_bytes = _env.convert_byte_array(_leaf_indices)
+        .expect("Should be able to convert to Rust byte array");
+
+    if leaf_indices_bytes.len() % 8 != 0 { return std::ptr::null::<jobject>() as jobject }
+
+    let mut leaf_indices = vec![];
+    for chunk in leaf_indices_bytes.chunks(8) {
+        let leaf_index = match u64::read(chunk) {
+            Ok(leaf_index) => leaf_index,
+            Err(_) => return std::ptr::null::<jobject>() as jobject //I/O ERROR
+        };
+        leaf_indices.push(leaf_index as usize);
+    }
+
+    //Compute multi path
+    let path = match get_ginger_merkle_multi_path(leaves.as_slice(), leaf_indices.as_slice(), tree) {
+        Ok(path) => path,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return multi path
+    let path_ptr: jlong = jlong::from(Box::into_raw(Box::new(path)) as i64);
+
+    let path_class =  _env.find_class("com/horizen/merkletreenative/MerkleMultiPath")
+        .expect("Should be able to find MerkleMultiPath class");
+
+    let result = _env.new_object(path_class, "(J)V", &[
+        JValue::Long(path_ptr)]).expect("Should be able to create new long for MerkleMultiPath");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMultiPath_nativeGetLeavesCount(
+    _env: JNIEnv,
+    _path: JObject,
+) -> jint
+{
+    let path = {
+
+        let p =_env.get_field(_path, "merkleMultiPathPointer", "J")
+            .expect("Should be able to get field merkleMultiPathPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const GingerMerkleTreeMultiPath)
+    };
+
+    path.leaf_indices.len() as jint
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMultiPath_nativeVerify(
+    _env: JNIEnv,
+    _path: JObject,
+    _leaves: jobjectArray,
+    _root: JObject,
+) -> jboolean
+{
+    let path = {
+
+        let p =_env.get_field(_path, "merkleMultiPathPointer", "J")
+            .expect("Should be able to get field merkleMultiPathPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const GingerMerkleTreeMultiPath)
+    };
+
+    //Read _leaves as array of FieldElement
+    let leaves_len = _env.get_array_length(_leaves)
+        .expect("Should be able to read leaves array size");
+    let mut leaves = vec![];
+
+    for i in 0..leaves_len {
+        let field_obj = _env.get_object_array_element(_leaves, i)
+            .expect(format!("Should be able to read elem {} of the leaves array", i).as_str());
+
+        let field = {
+
+            let f =_env.get_field(field_obj, "fieldElementPointer", "J")
+                .expect("Should be able to get field fieldElementPointer");
+
+            read_raw_pointer(f.j().unwrap() as *const FieldElement)
+        };
+
+        leaves.push(*field);
+    }
+
+    //Read root
+    let root = {
+
+        let f =_env.get_field(_root, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
+    };
+
+    //Verify multi path
+    match verify_ginger_merkle_multi_path(path, root, leaves.as_slice()) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMultiPath_nativeSerialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _path: *const GingerMerkleTreeMultiPath,
+) -> jbyteArray
+{
+    let size = get_ginger_merkle_multi_path_size(read_raw_pointer(_path));
+
+    let mut path = vec![0u8; size];
+    serialize_from_raw_pointer(_path, &mut path[..]);
+
+    _env.byte_array_from_slice(path.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMultiPath_nativeDeserialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _path_bytes: jbyteArray,
+) -> jobject
+{
+    let path_bytes = _env.convert_byte_array(_path_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialized_to_jobject(
+        &_env,
+        "com/horizen/merkletreenative/MerkleMultiPath",
+        deserialize_variable_size_from_buffer_checked::<GingerMerkleTreeMultiPath>(path_bytes.as_slice())
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMultiPath_nativeFreeMerkleMultiPath(
+    _env: JNIEnv,
+    _class: JClass,
+    _path: *mut GingerMerkleTreeMultiPath,
+)
+{
+    if _path.is_null()  { return }
+    drop(unsafe { Box::from_raw(_path) });
+}
+
+//Variable height merkle tree functions
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleTree_nativeGetMinHeight(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jint { VARIABLE_HEIGHT_MERKLE_TREE_MIN_HEIGHT as jint }
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleTree_nativeGetMaxHeight(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jint { VARIABLE_HEIGHT_MERKLE_TREE_MAX_HEIGHT as jint }
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleTree_nativeInit(
+    _env: JNIEnv,
+    // this is the class that owns our
+    // static method. Not going to be
+    // used, but still needs to have
+    // an argument slot
+    _class: JClass,
+    _leaves: jobjectArray,
+    _height: jint,
+) -> jobject
+{
+    //Read _leaves as array of FieldElement
+    let leaves_len = _env.get_array_length(_leaves)
+        .expect("Should be able to read leaves array size");
+    let mut leaves = vec![];
+
+    for i in 0..leaves_len {
+        let field_obj = _env.get_object_array_element(_leaves, i)
+            .expect(format!("Should be able to read elem {} of the leaves array", i).as_str());
+
+        let field = {
+
+            let f =_env.get_field(field_obj, "fieldElementPointer", "J")
+                .expect("Should be able to get field fieldElementPointer");
+
+            read_raw_pointer(f.j().unwrap() as *const FieldElement)
+        };
+
+        leaves.push(*field);
+    }
+
+    //Build the tree
+    let mt = match new_ginger_variable_height_merkle_tree(leaves.as_slice(), _height as usize) {
+        Ok(mt) => mt,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return the tree
+    let mt_ptr: jlong = jlong::from(Box::into_raw(Box::new(mt)) as i64);
+
+    let mt_class =  _env.find_class("com/horizen/merkletreenative/VariableHeightMerkleTree")
+        .expect("Should be able to find VariableHeightMerkleTree class");
+
+    let result = _env.new_object(mt_class, "(J)V", &[
+        JValue::Long(mt_ptr)]).expect("Should be able to create new long for VariableHeightMerkleTree");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleTree_nativeRoot(
+    _env: JNIEnv,
+    _tree: JObject,
+) -> jobject
+{
+    let tree = {
+
+        let t =_env.get_field(_tree, "merkleTreePointer", "J")
+            .expect("Should be able to get field merkleTreePointer");
+
+        read_raw_pointer(t.j().unwrap() as *const GingerVariableHeightMerkleTree)
+    };
+
+    let root = match get_ginger_variable_height_merkle_root(tree) {
+        Ok(root) => root,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return root
+    let root_ptr: jlong = jlong::from(Box::into_raw(Box::new(root)) as i64);
+
+    let root_class =  _env.find_class("com/horizen/merkletreenative/VariableHeightMerkleRoot")
+        .expect("Should be able to find VariableHeightMerkleRoot class");
+
+    let result = _env.new_object(root_class, "(J)V", &[
+        JValue::Long(root_ptr)]).expect("Should be able to create new long for VariableHeightMerkleRoot");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleTree_nativeGetMerklePath(
+    _env: JNIEnv,
+    _tree: JObject,
+    _leaf_index: jlong,
+) -> jobject
+{
+    let tree = {
+
+        let t =_env.get_field(_tree, "merkleTreePointer", "J")
+            .expect("Should be able to get field merkleTreePointer");
+
+        read_raw_pointer(t.j().unwrap() as *const GingerVariableHeightMerkleTree)
+    };
+
+    //Compute path
+    let path = match get_ginger_variable_height_merkle_path(tree, _leaf_index as usize) {
+        Ok(path) => path,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return path
+    let path_ptr: jlong = jlong::from(Box::into_raw(Box::new(path)) as i64);
+
+    let path_class =  _env.find_class("com/horizen/merkletreenative/VariableHeightMerklePath")
+        .expect("Should be able to find VariableHeightMerklePath class");
+
+    let result = _env.new_object(path_class, "(J)V", &[
+        JValue::Long(path_ptr)]).expect("Should be able to create new long for VariableHeightMerklePath");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleTree_nativeFreeMerkleTree(
+    _env: JNIEnv,
+    _class: JClass,
+    _tree: *mut GingerVariableHeightMerkleTree,
+)
+{
+    if _tree.is_null()  { return }
+    drop(unsafe { Box::from_raw(_tree) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleRoot_nativeGetRootSize(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jint { VARIABLE_HEIGHT_MERKLE_ROOT_SIZE as jint }
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleRoot_nativeGetHeight(
+    _env: JNIEnv,
+    _root: JObject,
+) -> jint
+{
+    let root = {
+
+        let r =_env.get_field(_root, "merkleRootPointer", "J")
+            .expect("Should be able to get field merkleRootPointer");
+
+        read_raw_pointer(r.j().unwrap() as *const GingerVariableHeightMerkleRoot)
+    };
+
+    root.height as jint
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleRoot_nativeGetValue(
+    _env: JNIEnv,
+    _root: JObject,
+) -> jobject
+{
+    let root = {
+
+        let r =_env.get_field(_root, "merkleRootPointer", "J")
+            .expect("Should be able to get field merkleRootPointer");
+
+        read_raw_pointer(r.j().unwrap() as *const GingerVariableHeightMerkleRoot)
+    };
+
+    //Return a copy of the root value
+    let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(root.value)) as i64);
+
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object(field_class, "(J)V", &[
+        JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleRoot_nativeSerialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _root: *const GingerVariableHeightMerkleRoot,
+) -> jbyteArray
+{
+    let mut root = [0u8; VARIABLE_HEIGHT_MERKLE_ROOT_SIZE];
+    serialize_from_raw_pointer(_root, &mut root[..]);
+
+    _env.byte_array_from_slice(root.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleRoot_nativeDeserialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _root_bytes: jbyteArray,
+) -> jobject
+{
+    let root_bytes = _env.convert_byte_array(_root_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialized_to_jobject(
+        &_env,
+        "com/horizen/merkletreenative/VariableHeightMerkleRoot",
+        deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerkleRoot>(root_bytes.as_slice())
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerkleRoot_nativeFreeMerkleRoot(
+    _env: JNIEnv,
+    _class: JClass,
+    _root: *mut GingerVariableHeightMerkleRoot,
+)
+{
+    if _root.is_null()  { return }
+    drop(unsafe { Box::from_raw(_root) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerklePath_nativeGetHeight(
+    _env: JNIEnv,
+    _path: JObject,
+) -> jint
+{
+    let path = {
+
+        let p =_env.get_field(_path, "merklePathPointer", "J")
+            .expect("Should be able to get field merklePathPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const GingerVariableHeightMerklePath)
+    };
+
+    path.height as jint
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerklePath_nativeVerify(
+    _env: JNIEnv,
+    _path: JObject,
+    _leaf: JObject,
+    _root: JObject,
+) -> jboolean
+{
+    let path = {
+
+        let p =_env.get_field(_path, "merklePathPointer", "J")
+            .expect("Should be able to get field merklePathPointer");
+
+        read_raw_pointer(p.j().unwrap() as *const GingerVariableHeightMerklePath)
+    };
+
+    //Read leaf
+    let leaf = {
+
+        let f =_env.get_field(_leaf, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
+    };
+
+    //Read root
+    let root = {
+
+        let r =_env.get_field(_root, "merkleRootPointer", "J")
+            .expect("Should be able to get field merkleRootPointer");
+
+        read_raw_pointer(r.j().unwrap() as *const GingerVariableHeightMerkleRoot)
+    };
+
+    //Verify path
+    match verify_ginger_variable_height_merkle_path(path, root, leaf) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerklePath_nativeSerialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _path: *const GingerVariableHeightMerklePath,
+) -> jbyteArray
+{
+    let height = read_raw_pointer(_path).height;
+
+    let mut path = vec![0u8; get_variable_height_merkle_path_size(height)];
+    serialize_from_raw_pointer(_path, &mut path[..]);
+
+    _env.byte_array_from_slice(path.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerklePath_nativeDeserialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _path_bytes: jbyteArray,
+) -> jobject
+{
+    let path_bytes = _env.convert_byte_array(_path_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialized_to_jobject(
+        &_env,
+        "com/horizen/merkletreenative/VariableHeightMerklePath",
+        deserialize_variable_size_from_buffer_checked::<GingerVariableHeightMerklePath>(path_bytes.as_slice())
+    )
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_VariableHeightMerklePath_nativeFreeMerklePath(
+    _env: JNIEnv,
+    _class: JClass,
+    _path: *mut GingerVariableHeightMerklePath,
+)
+{
+    if _path.is_null()  { return }
+    drop(unsafe { Box::from_raw(_path) });
+}
+
+//Merkle mountain range functions
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeInit(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jobject
+{
+    let mmr_ptr: jlong = jlong::from(Box::into_raw(Box::new(GingerMerkleMountainRange::new())) as i64);
+
+    let mmr_class =  _env.find_class("com/horizen/merkletreenative/MerkleMountainRange")
+        .expect("Should be able to find MerkleMountainRange class");
+
+    let result = _env.new_object(mmr_class, "(J)V", &[
+        JValue::Long(mmr_ptr)]).expect("Should be able to create new long for MerkleMountainRange");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeAppend(
+    _env: JNIEnv,
+    _mmr: JObject,
+    _leaf: JObject,
+) -> jboolean
+{
+    let mmr = {
+
+        let m =_env.get_field(_mmr, "merkleMountainRangePointer", "J")
+            .expect("Should be able to get field merkleMountainRangePointer");
+
+        read_mut_raw_pointer(m.j().unwrap() as *mut GingerMerkleMountainRange)
+    };
+
+    //Read leaf
+    let leaf = {
+
+        let f =_env.get_field(_leaf, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
+    };
+
+    match mmr.append(leaf) {
+        Ok(_) => JNI_TRUE,
+        Err(_) => JNI_FALSE //CRYPTO_ERROR
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeGetLeavesCount(
+    _env: JNIEnv,
+    _mmr: JObject,
+) -> jlong
+{
+    let mmr = {
+
+        let m =_env.get_field(_mmr, "merkleMountainRangePointer", "J")
+            .expect("Should be able to get field merkleMountainRangePointer");
+
+        read_raw_pointer(m.j().unwrap() as *const GingerMerkleMountainRange)
+    };
+
+    mmr.leaves_count() as jlong
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeGetPeaks(
+    _env: JNIEnv,
+    _mmr: JObject,
+) -> jobjectArray
+{
+    let mmr = {
+
+        let m =_env.get_field(_mmr, "merkleMountainRangePointer", "J")
+            .expect("Should be able to get field merkleMountainRangePointer");
+
+        read_raw_pointer(m.j().unwrap() as *const GingerMerkleMountainRange)
+    };
+
+    let peaks = mmr.peaks();
+
+    //Return peaks
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object_array(peaks.len() as i32, field_class, JObject::null())
+        .expect("Should be able to create array of FieldElements");
+
+    for (i, peak) in peaks.into_iter().enumerate() {
+        let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(peak)) as i64);
+
+        let field_element = _env.new_object(field_class, "(J)V", &[
+            JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+        _env.set_object_array_element(result, i as i32, field_element)
+            .expect("Should be able to add FieldElement to the array");
+    }
+
+    result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeRoot(
+    _env: JNIEnv,
+    _mmr: JObject,
+) -> jobject
+{
+    let mmr = {
+
+        let m =_env.get_field(_mmr, "merkleMountainRangePointer", "J")
+            .expect("Should be able to get field merkleMountainRangePointer");
+
+        read_raw_pointer(m.j().unwrap() as *const GingerMerkleMountainRange)
+    };
+
+    let root = match mmr.root() {
+        Ok(root) => root,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return root
+    let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(root)) as i64);
+
+    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
+        .expect("Should be able to find FieldElement class");
+
+    let result = _env.new_object(field_class, "(J)V", &[
+        JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeGetProof(
+    _env: JNIEnv,
+    _mmr: JObject,
+    _leaf_index: jlong,
+) -> jobject
+{
+    let mmr = {
+
+        let m =_env.get_field(_mmr, "merkleMountainRangePointer", "J")
+            .expect("Should be able to get field merkleMountainRangePointer");
+
+        read_raw_pointer(m.j().unwrap() as *const GingerMerkleMountainRange)
+    };
+
+    let proof = match mmr.proof(_leaf_index as u64) {
+        Ok(proof) => proof,
+        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
+    };
+
+    //Return proof
+    let proof_ptr: jlong = jlong::from(Box::into_raw(Box::new(proof)) as i64);
+
+    let proof_class =  _env.find_class("com/horizen/merkletreenative/MerkleMountainRangeProof")
+        .expect("Should be able to find MerkleMountainRangeProof class");
+
+    let result = _env.new_object(proof_class, "(J)V", &[
+        JValue::Long(proof_ptr)]).expect("Should be able to create new long for MerkleMountainRangeProof");
+
+    *result
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeSerialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _mmr: *const GingerMerkleMountainRange,
+) -> jbyteArray
+{
+    let mmr_bytes = read_raw_pointer(_mmr).serialize()
+        .expect("Should be able to serialize merkle mountain range");
+
+    _env.byte_array_from_slice(mmr_bytes.as_slice())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeDeserialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _mmr_bytes: jbyteArray,
+) -> jobject
+{
+    let mmr_bytes = _env.convert_byte_array(_mmr_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialized_to_jobject(
+        &_env,
+        "com/horizen/merkletreenative/MerkleMountainRange",
+        GingerMerkleMountainRange::deserialize_checked(mmr_bytes.as_slice())
+    )
+}
 
 #[no_mangle]
-pub extern "system" fn Java_com_horizen_schnorrnative_SchnorrPublicKey_nativeVerifySignature(
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRange_nativeFreeMerkleMountainRange(
     _env: JNIEnv,
-    _public_key: JObject,
-    _signature: JObject,
-    _message: JObject,
-) -> jboolean {
+    _class: JClass,
+    _mmr: *mut GingerMerkleMountainRange,
+)
+{
+    if _mmr.is_null()  { return }
+    drop(unsafe { Box::from_raw(_mmr) });
+}
 
-    //Read pk
-    let public_key = {
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRangeProof_nativeVerify(
+    _env: JNIEnv,
+    _proof: JObject,
+    _leaf: JObject,
+    _root: JObject,
+) -> jboolean
+{
+    let proof = {
 
-        let p = _env.get_field(_public_key, "publicKeyPointer", "J")
-            .expect("Should be able to get field publicKeyPointer");
+        let p =_env.get_field(_proof, "merkleMountainRangeProofPointer", "J")
+            .expect("Should be able to get field merkleMountainRangeProofPointer");
 
-        read_raw_pointer(p.j().unwrap() as *const SchnorrPk)
+        read_raw_pointer(p.j().unwrap() as *const GingerMerkleMountainRangeProof)
     };
 
-    //Read message
-    let message = {
+    //Read leaf
+    let leaf = {
 
-        let m =_env.get_field(_message, "fieldElementPointer", "J")
+        let f =_env.get_field(_leaf, "fieldElementPointer", "J")
             .expect("Should be able to get field fieldElementPointer");
 
-        read_raw_pointer(m.j().unwrap() as *const FieldElement)
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
     };
 
-    //Read sig
-    let signature = {
-        let sig = _env.get_field(_signature, "signaturePointer", "J")
-            .expect("Should be able to get field signaturePointer");
+    //Read root
+    let root = {
 
-        read_raw_pointer(sig.j().unwrap() as *const SchnorrSig)
+        let f =_env.get_field(_root, "fieldElementPointer", "J")
+            .expect("Should be able to get field fieldElementPointer");
+
+        read_raw_pointer(f.j().unwrap() as *const FieldElement)
     };
 
-    //Verify sig
-    match schnorr_verify_signature(message, public_key, signature) {
-        Ok(result) => if result {
-            JNI_TRUE
-        } else {
-            JNI_FALSE
-        },
+    //Verify proof
+    match proof.verify(root, leaf) {
+        Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
         Err(_) => JNI_FALSE //CRYPTO_ERROR
     }
 }
 
 #[no_mangle]
-pub extern "system" fn Java_com_horizen_poseidonnative_PoseidonHash_nativeComputeHash(
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRangeProof_nativeSerialize(
     _env: JNIEnv,
     _class: JClass,
-    _input: jobjectArray,
-) -> jobject
+    _proof: *const GingerMerkleMountainRangeProof,
+) -> jbyteArray
 {
-    //Read _input as array of FieldElement
-    let input_len = _env.get_array_length(_input)
-        .expect("Should be able to read input array size");
-    let mut input = vec![];
-
-    for i in 0..input_len {
-        let field_obj = _env.get_object_array_element(_input, i)
-            .expect(format!("Should be able to read elem {} of the input array", i).as_str());
-
-        let field = {
+    let mut proof_bytes = vec![];
+    read_raw_pointer(_proof).write(&mut proof_bytes)
+        .expect("Should be able to serialize merkle mountain range proof");
 
-            let f =_env.get_field(field_obj, "fieldElementPointer", "J")
-                .expect("Should be able to get field fieldElementPointer");
-
-            read_raw_pointer(f.j().unwrap() as *const FieldElement)
-        };
-
-        input.push(*field);
-    }
-
-    //Compute hash
-    let hash = match compute_poseidon_hash(input.as_slice()) {
-        Ok(hash) => hash,
-        Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
-    };
-
-    //Return hash
-    let field_ptr: jlong = jlong::from(Box::into_raw(Box::new(hash)) as i64);
+    _env.byte_array_from_slice(proof_bytes.as_slice())
+        .expect("Should be able to convert to jbyteArray")
+}
 
-    let field_class =  _env.find_class("com/horizen/librustsidechains/FieldElement")
-        .expect("Should be able to find FieldElement class");
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRangeProof_nativeDeserialize(
+    _env: JNIEnv,
+    _class: JClass,
+    _proof_bytes: jbyteArray,
+) -> jobject
+{
+    let proof_bytes = _env.convert_byte_array(_proof_bytes)
+        .expect("Should be able to convert to Rust byte array");
 
-    let result = _env.new_object(field_class, "(J)V", &[
-        JValue::Long(field_ptr)]).expect("Should be able to create new long for FieldElement");
+    deserialized_to_jobject(
+        &_env,
+        "com/horizen/merkletreenative/MerkleMountainRangeProof",
+        deserialize_variable_size_from_buffer_checked::<GingerMerkleMountainRangeProof>(proof_bytes.as_slice())
+    )
+}
 
-    *result
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_merkletreenative_MerkleMountainRangeProof_nativeFreeMerkleMountainRangeProof(
+    _env: JNIEnv,
+    _class: JClass,
+    _proof: *mut GingerMerkleMountainRangeProof,
+)
+{
+    if _proof.is_null()  { return }
+    drop(unsafe { Box::from_raw(_proof) });
 }
 
 //VRF utility functions
@@ -763,18 +2917,34 @@ pub extern "system" fn Java_com_horizen_vrfnative_VRFProof_nativeDeserializeProo
     let proof_bytes = _env.convert_byte_array(_proof_bytes)
         .expect("Should be able to convert to Rust byte array");
 
-    let proof_ptr: *const VRFProof = deserialize_to_raw_pointer(proof_bytes.as_slice());
-
-    let proof: jlong = jlong::from(proof_ptr as i64);
+    deserialize_checked_to_jobject::<VRFProof>(&_env, "com/horizen/vrfnative/VRFProof", proof_bytes.as_slice())
+}
 
-    let proof_class = _env.find_class("com/horizen/vrfnative/VRFProof")
-        .expect("Cannot find VRFProof class.");
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_vrfnative_VRFProof_nativeSerializeProofCompressed(
+    _env: JNIEnv,
+    _class: JClass,
+    _proof: *const VRFProof,
+) -> jbyteArray
+{
+    compressed_bytes_to_jbytearray(&_env, serialize_vrf_proof_compressed(read_raw_pointer(_proof)))
+}
 
-    let proof_object = _env.new_object(proof_class, "(J)V",
-                                     &[JValue::Long(proof)])
-        .expect("Cannot create vrf proof object.");
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_vrfnative_VRFProof_nativeDeserializeProofCompressed(
+    _env: JNIEnv,
+    _class: JClass,
+    _proof_bytes: jbyteArray,
+) -> jobject
+{
+    let proof_bytes = _env.convert_byte_array(_proof_bytes)
+        .expect("Should be able to convert to Rust byte array");
 
-    *proof_object
+    boxed_to_jobject(
+        &_env,
+        "com/horizen/vrfnative/VRFProof",
+        deserialize_vrf_proof_compressed(proof_bytes.as_slice())
+    )
 }
 
 #[no_mangle]
@@ -915,6 +3085,38 @@ pub extern "system" fn Java_com_horizen_vrfnative_VRFKeyPair_nativeProve(
     *result
 }
 
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_vrfnative_VRFSecretKey_nativeDeriveFromSeed(
+    _env: JNIEnv,
+    _class: JClass,
+    _seed: jbyteArray,
+    _path: JString,
+) -> jobject
+{
+    derive_secret_key_from_seed(&_env, _seed, _path, HDKeyType::Vrf, "com/horizen/vrfnative/VRFSecretKey")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_vrfnative_VRFSecretKey_nativeEncrypt(
+    _env: JNIEnv,
+    _secret_key: JObject,
+    _password: jbyteArray,
+) -> jbyteArray
+{
+    encrypt_secret_key_object(&_env, _secret_key, _password, HDKeyType::Vrf)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_vrfnative_VRFSecretKey_nativeDecrypt(
+    _env: JNIEnv,
+    _class: JClass,
+    _keystore: jbyteArray,
+    _password: jbyteArray,
+) -> jobject
+{
+    decrypt_secret_key_object(&_env, _keystore, _password, HDKeyType::Vrf, "com/horizen/vrfnative/VRFSecretKey")
+}
+
 #[no_mangle]
 pub extern "system" fn Java_com_horizen_vrfnative_VRFSecretKey_nativeGetPublicKey(
     _env: JNIEnv,
@@ -1007,6 +3209,142 @@ pub extern "system" fn Java_com_horizen_vrfnative_VRFPublicKey_nativeProofToHash
     *result
 }
 
+//Ouroboros consensus functions
+
+// Reads the epoch randomness, the slot number, the forger stake and the total forgers stake from a
+// ForgerEligibilityParameters object. Their ranges are checked on the Java side.
+fn read_forger_eligibility_parameters<'a>(_env: &JNIEnv, _params: JObject) -> (&'a FieldElement, u32, u64, u64) {
+    let epoch_randomness_object = _env.get_field(_params,
+                                                 "epochRandomness",
+                                                 "Lcom/horizen/librustsidechains/FieldElement;"
+    ).expect("Should be able to get field epochRandomness").l().unwrap();
+    let epoch_randomness = read_pointer_field::<FieldElement>(_env, epoch_randomness_object, "fieldElementPointer");
+
+    let slot_number = _env.get_field(_params, "slotNumber", "I")
+        .expect("Should be able to get field slotNumber").i().unwrap() as u32;
+
+    let forger_stake = _env.get_field(_params, "forgerStake", "J")
+        .expect("Should be able to get field forgerStake").j().unwrap() as u64;
+
+    let total_forgers_stake = _env.get_field(_params, "totalForgersStake", "J")
+        .expect("Should be able to get field totalForgersStake").j().unwrap() as u64;
+
+    (epoch_randomness, slot_number, forger_stake, total_forgers_stake)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_consensusnative_ForgerEligibilityProof_nativeSerializeProof(
+    _env: JNIEnv,
+    _class: JClass,
+    _proof: *const ForgerEligibilityProof,
+) -> jbyteArray
+{
+    let mut proof = [0u8; FORGER_ELIGIBILITY_PROOF_SIZE];
+    serialize_from_raw_pointer(_proof, &mut proof[..]);
+
+    _env.byte_array_from_slice(proof.as_ref())
+        .expect("Should be able to convert to jbyteArray")
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_consensusnative_ForgerEligibilityProof_nativeDeserializeProof(
+    _env: JNIEnv,
+    _class: JClass,
+    _proof_bytes: jbyteArray,
+) -> jobject
+{
+    let proof_bytes = _env.convert_byte_array(_proof_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    deserialize_checked_to_jobject::<ForgerEligibilityProof>(&_env, "com/horizen/consensusnative/ForgerEligibilityProof", proof_bytes.as_slice())
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_consensusnative_ForgerEligibilityProof_nativeFreeProof(
+    _env: JNIEnv,
+    _class: JClass,
+    _proof: *mut ForgerEligibilityProof,
+)
+{
+    if _proof.is_null()  { return }
+    drop(unsafe { Box::from_raw(_proof) });
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_consensusnative_ForgerEligibilityProof_nativeProve(
+    _env: JNIEnv,
+    _class: JClass,
+    _secret_key: JObject,
+    _public_key: JObject,
+    _params: JObject,
+) -> jobject
+{
+    let secret_key = read_pointer_field::<VRFSk>(&_env, _secret_key, "secretKeyPointer");
+    let public_key = read_pointer_field::<VRFPk>(&_env, _public_key, "publicKeyPointer");
+    let (epoch_randomness, slot_number, forger_stake, total_forgers_stake) = read_forger_eligibility_parameters(&_env, _params);
+
+    match ouroboros_prove_forger_eligibility(
+        epoch_randomness,
+        slot_number,
+        forger_stake,
+        total_forgers_stake,
+        secret_key,
+        public_key
+    ) {
+        Ok(Some(proof)) => boxed_to_jobject(&_env, "com/horizen/consensusnative/ForgerEligibilityProof", Ok(proof)),
+        Ok(None) => std::ptr::null::<jobject>() as jobject, //Not eligible
+        Err(e) => {
+            throw_illegal_state_exception(&_env, e); //CRYPTO_ERROR
+            std::ptr::null::<jobject>() as jobject
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_consensusnative_ForgerEligibilityProof_nativeCheck(
+    _env: JNIEnv,
+    _proof: JObject,
+    _public_key: JObject,
+    _params: JObject,
+) -> jobject
+{
+    let proof = read_pointer_field::<ForgerEligibilityProof>(&_env, _proof, "proofPointer");
+    let public_key = read_pointer_field::<VRFPk>(&_env, _public_key, "publicKeyPointer");
+    let (epoch_randomness, slot_number, forger_stake, total_forgers_stake) = read_forger_eligibility_parameters(&_env, _params);
+
+    let (leader_output, nonce_output) = match ouroboros_check_forger_eligibility(
+        proof,
+        epoch_randomness,
+        slot_number,
+        forger_stake,
+        total_forgers_stake,
+        public_key
+    ) {
+        Ok(Some(outputs)) => outputs,
+        Ok(None) => return std::ptr::null::<jobject>() as jobject, //Invalid proof or not eligible
+        Err(e) => {
+            throw_illegal_state_exception(&_env, e); //CRYPTO_ERROR
+            return std::ptr::null::<jobject>() as jobject
+        }
+    };
+
+    //Create FieldElement instances
+    let leader_output_object = boxed_to_jobject(&_env, "com/horizen/librustsidechains/FieldElement", Ok(leader_output.value()));
+    let nonce_output_object = boxed_to_jobject(&_env, "com/horizen/librustsidechains/FieldElement", Ok(nonce_output.value()));
+
+    //Create and return ForgerEligibilityResult instance
+    let class = _env.find_class("com/horizen/consensusnative/ForgerEligibilityResult")
+        .expect("Should be able to find ForgerEligibilityResult class");
+
+    let result = _env.new_object(
+        class,
+        "(Lcom/horizen/librustsidechains/FieldElement;Lcom/horizen/librustsidechains/FieldElement;)V",
+        &[JValue::Object(JObject::from(leader_output_object)), JValue::Object(JObject::from(nonce_output_object))]
+    ).expect("Should be able to create new ForgerEligibilityResult object");
+
+    *result
+}
+
 //Naive threshold signature proof functions
 #[no_mangle]
 pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_nativeGetConstant(
@@ -1018,6 +3356,8 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
     _class: JClass,
     _schnorr_pks_list: jobjectArray,
     _threshold: jlong,
+    _version: jint,
+    _schnorr_pops_list: jobjectArray,
 ) -> jobject
 {
     //Extract Schnorr pks
@@ -1040,8 +3380,41 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
     //Extract threshold
     let threshold = _threshold as u64;
 
-    //Compute constant
-    let constant = match compute_pks_threshold_hash(pks.as_slice(), threshold){
+    //Extract domain separation version
+    let version = match read_domain_separation_version(_version) {
+        Some(version) => version,
+        None => return std::ptr::null::<jobject>() as jobject
+    };
+
+    //Extract proofs of possession, if any
+    let pops = if _schnorr_pops_list.is_null() {
+        None
+    } else {
+        let pops_list_size = _env.get_array_length(_schnorr_pops_list)
+            .expect("Should be able to get schnorr_pops_list size");
+
+        let mut pops = vec![];
+        for i in 0..pops_list_size {
+            let pop_object = _env.get_object_array_element(_schnorr_pops_list, i)
+                .expect(format!("Should be able to get elem {} of schnorr_pops_list", i).as_str());
+
+            let pop = _env.get_field(pop_object, "signaturePointer", "J")
+                .expect("Should be able to get field signaturePointer");
+
+            match read_nullable_raw_pointer(pop.j().unwrap() as *const SchnorrPoP) {
+                Some(pop) => pops.push(*pop),
+                None => return std::ptr::null::<jobject>() as jobject,
+            }
+        }
+        Some(pops)
+    };
+
+    //Compute constant, checking the proofs of possession if provided
+    let constant = match pops {
+        Some(pops) => compute_pks_threshold_hash_with_pops(pks.as_slice(), pops.as_slice(), threshold, version),
+        None => compute_pks_threshold_hash(pks.as_slice(), threshold, version),
+    };
+    let constant = match constant {
         Ok(constant) => constant,
         Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
     };
@@ -1070,6 +3443,7 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
     _bt_list: jobjectArray,
     _end_epoch_block_hash: jbyteArray,
     _prev_end_epoch_block_hash: jbyteArray,
+    _version: jint,
 ) -> jobject
 {
     //Extract backward transfers
@@ -1133,11 +3507,18 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
             .expect("Should be able to read a FieldElement from a 32 byte array")
     };
 
+    //Extract domain separation version
+    let version = match read_domain_separation_version(_version) {
+        Some(version) => version,
+        None => return std::ptr::null::<jobject>() as jobject
+    };
+
     //Compute message to sign:
     let msg = match compute_msg_to_sign(
         &end_epoch_block_hash,
         &prev_end_epoch_block_hash,
-        bt_list.as_slice()
+        bt_list.as_slice(),
+        version,
     ){
         Ok((_, msg)) => msg,
         Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
@@ -1169,7 +3550,8 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
     _schnorr_sigs_list: jobjectArray,
     _schnorr_pks_list:  jobjectArray,
     _threshold: jlong,
-    _proving_key_path: JString
+    _proving_key_path: JString,
+    _version: jint,
 ) -> jobject
 {
     //Extract backward transfers
@@ -1279,6 +3661,12 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
         .expect("Should be able to read jstring as Rust String");
 
 
+    //Extract domain separation version
+    let version = match read_domain_separation_version(_version) {
+        Some(version) => version,
+        None => return std::ptr::null::<jobject>() as jobject
+    };
+
     //create proof
     let (proof, quality) = match create_naive_threshold_sig_proof(
         pks.as_slice(),
@@ -1287,7 +3675,8 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
         &prev_end_epoch_block_hash,
         bt_list.as_slice(),
         threshold,
-        proving_key_path.to_str().unwrap()
+        proving_key_path.to_str().unwrap(),
+        version,
     ) {
         Ok(proof) => proof,
         Err(_) => return std::ptr::null::<jobject>() as jobject //CRYPTO_ERROR
@@ -1315,6 +3704,46 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
     *result
 }
 
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_nativeCompressProof(
+    _env: JNIEnv,
+    _class: JClass,
+    _sc_proof_bytes: jbyteArray,
+) -> jbyteArray
+{
+    let proof_bytes = _env.convert_byte_array(_sc_proof_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    let compressed = deserialize_from_buffer::<SCProof>(proof_bytes.as_slice())
+        .map_err(|e| -> Error { e.into() })
+        .and_then(|proof| serialize_sc_proof_compressed(&proof));
+
+    compressed_bytes_to_jbytearray(&_env, compressed)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_nativeDecompressProof(
+    _env: JNIEnv,
+    _class: JClass,
+    _sc_proof_compressed_bytes: jbyteArray,
+) -> jbyteArray
+{
+    let proof_bytes = _env.convert_byte_array(_sc_proof_compressed_bytes)
+        .expect("Should be able to convert to Rust byte array");
+
+    let proof = match deserialize_sc_proof_compressed(proof_bytes.as_slice()) {
+        Ok(proof) => proof,
+        Err(_) => return std::ptr::null::<jobject>() as jbyteArray,
+    };
+
+    let mut proof_bytes = [0u8; ZK_PROOF_SIZE];
+    proof.write(&mut proof_bytes[..])
+        .expect("Should be able to write proof into proof_bytes");
+
+    _env.byte_array_from_slice(proof_bytes.as_ref())
+        .expect("Should be able to convert Rust slice into jbytearray")
+}
+
 //Test functions
 #[no_mangle]
 pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_nativeVerifyProof(
@@ -1330,7 +3759,8 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
     _constant: JObject,
     _quality: jlong,
     _sc_proof_bytes: jbyteArray,
-    _verification_key_path: JString
+    _verification_key_path: JString,
+    _version: jint,
 ) -> jboolean {
 
     //Extract backward transfers
@@ -1415,6 +3845,12 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
     let vk_path = _env.get_string(_verification_key_path)
         .expect("Should be able to read jstring as Rust String");
 
+    //Extract domain separation version
+    let version = match read_domain_separation_version(_version) {
+        Some(version) => version,
+        None => return JNI_FALSE
+    };
+
     //Verify proof
     match verify_naive_threshold_sig_proof(
         constant,
@@ -1423,7 +3859,8 @@ pub extern "system" fn Java_com_horizen_sigproofnative_NaiveThresholdSigProof_na
         bt_list.as_slice(),
         quality,
         &proof,
-        vk_path.to_str().unwrap()
+        vk_path.to_str().unwrap(),
+        version,
     ) {
         Ok(result) => if result { JNI_TRUE } else { JNI_FALSE },
         Err(_) => JNI_FALSE // CRYPTO_ERROR
@@ -0,0 +1,262 @@
+use algebra::{fields::mnt4753::Fr as MNT4Fr, BigInteger768, PrimeField};
+use primitives::crh::{FieldBasedHash, MNT4PoseidonHash};
+use r1cs_crypto::crh::{MNT4PoseidonHashGadget, FieldBasedHashGadget};
+use r1cs_std::fields::{fp::FpGadget, FieldGadget};
+use r1cs_core::{ConstraintSystem, SynthesisError};
+
+type MNT4FrGadget = FpGadget<MNT4Fr>;
+
+/// Versions of the domain separation applied to the Poseidon hashes of the protocol.
+/// Versions are never modified once released, so that constants, messages and proofs
+/// created with an older version can still be recomputed and verified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DomainSeparationVersion {
+    /// No domain separation: hashes are computed on the plain inputs.
+    V0,
+    /// The input of each hash is prepended with the personalization of its domain.
+    V1,
+}
+
+pub const CURRENT_DOMAIN_SEPARATION_VERSION: DomainSeparationVersion = DomainSeparationVersion::V1;
+
+impl DomainSeparationVersion {
+    pub fn from_u8(version: u8) -> Option<Self> {
+        match version {
+            0 => Some(DomainSeparationVersion::V0),
+            1 => Some(DomainSeparationVersion::V1),
+            _ => None,
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            DomainSeparationVersion::V0 => 0,
+            DomainSeparationVersion::V1 => 1,
+        }
+    }
+}
+
+/// The different uses of the Poseidon hash in the protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashDomain {
+    /// H(pks)
+    PksHash,
+    /// H(H(pks), threshold)
+    PksThresholdHash,
+    /// H(MR(BT), BH(i-1), BH(i))
+    MsgToSign,
+    /// H(valid_signatures, MR(BT), BH(i-1), BH(i))
+    WCertSysDataHash,
+    /// H(pks_threshold_hash, wcert_sysdata_hash)
+    AggregatedInput,
+    /// H(sk, pk, msg[, H(extra randomness)]), seed of the deterministic Schnorr nonce
+    SchnorrNonce,
+    /// H(key type, H(seed), selector), master key and chain code of the HD key derivation
+    HdMasterKey,
+    /// H(key type, chain code, parent key, index, selector), child key and chain code of the HD key derivation
+    HdChildKey,
+    /// Schnorr signature on (personalization, pk), proving the possession of the secret key of pk
+    ProofOfPossession,
+    /// H(n, pk_1, ..., pk_n), commitment to the signer set of a Schnorr multi-signature
+    MuSigKeyList,
+    /// H(H(signer set), pk_i), coefficient of pk_i in the aggregated key of a Schnorr multi-signature
+    MuSigKeyCoefficient,
+    /// H(R_i), commitment to the nonce of a signer of a Schnorr multi-signature
+    MuSigNonceCommitment,
+    /// H(R, aggregated pk, msg), challenge of a Schnorr multi-signature
+    MuSigChallenge,
+    /// H(key, value), leaf of the Sparse Merkle Tree, kept apart from its internal nodes H(left, right)
+    SmtLeaf,
+    /// H(key), whose least significant bits give the position of key in the Sparse Merkle Tree
+    SmtLeafPosition,
+    /// Schnorr signature on (personalization, len, packed bytes), kept apart from field element messages
+    SchnorrBytesMessage,
+    /// H(acc, nonce output), accumulator of the nonce VRF outputs of the blocks of an epoch
+    EpochNonceFold,
+    /// H(acc), randomness of the next epoch, given the accumulated nonce VRF outputs
+    EpochRandomness,
+    /// VRF input (epoch randomness, slot, personalization) of the leader election of a slot
+    VrfLeader,
+    /// VRF input (epoch randomness, slot, personalization) of the epoch nonce contribution of a slot
+    VrfNonce,
+}
+
+impl HashDomain {
+    fn tag(&self) -> &'static [u8] {
+        match self {
+            HashDomain::PksHash          => b"ZenPksHash",
+            HashDomain::PksThresholdHash => b"ZenPksThrHash",
+            HashDomain::MsgToSign        => b"ZenMsgToSign",
+            HashDomain::WCertSysDataHash => b"ZenWCertSysData",
+            HashDomain::AggregatedInput  => b"ZenAggInput",
+            HashDomain::SchnorrNonce     => b"ZenSchnorrNonce",
+            HashDomain::HdMasterKey      => b"ZenHdMasterKey",
+            HashDomain::HdChildKey       => b"ZenHdChildKey",
+            HashDomain::ProofOfPossession => b"ZenSchnorrPoP",
+            HashDomain::MuSigKeyList     => b"ZenMuSigKeys",
+            HashDomain::MuSigKeyCoefficient => b"ZenMuSigKeyCoef",
+            HashDomain::MuSigNonceCommitment => b"ZenMuSigNonce",
+            HashDomain::MuSigChallenge   => b"ZenMuSigChal",
+            HashDomain::SmtLeaf          => b"ZenSmtLeaf",
+            HashDomain::SmtLeafPosition  => b"ZenSmtLeafPos",
+            HashDomain::SchnorrBytesMessage => b"ZenSchnorrBytes",
+            HashDomain::EpochNonceFold   => b"ZenEpochNonce",
+            HashDomain::EpochRandomness  => b"ZenEpochRand",
+            HashDomain::VrfLeader        => b"ZenVrfLeader",
+            HashDomain::VrfNonce         => b"ZenVrfNonce",
+        }
+    }
+}
+
+/// Returns the field element to be prepended to the input of a hash of `domain`,
+/// or None if `version` doesn't apply any domain separation. The personalization is
+/// the little endian integer given by the tag of the domain followed by the version byte.
+pub fn get_personalization(domain: HashDomain, version: DomainSeparationVersion) -> Option<MNT4Fr> {
+    match version {
+        DomainSeparationVersion::V0 => None,
+        _ => {
+            let mut bytes = domain.tag().to_vec();
+            bytes.push(version.to_u8());
+
+            let mut limbs = [0u64; 12];
+            for (i, &b) in bytes.iter().enumerate() {
+                limbs[i / 8] |= (b as u64) << (8 * (i % 8));
+            }
+            Some(MNT4Fr::from_repr(BigInteger768(limbs)))
+        }
+    }
+}
+
+/// Computes the Poseidon hash of `input` in `domain`, according to `version`.
+pub fn domain_separated_hash(
+    domain:  HashDomain,
+    version: DomainSeparationVersion,
+    input:   &[MNT4Fr],
+) -> Result<MNT4Fr, Box<dyn std::error::Error>>
+{
+    match get_personalization(domain, version) {
+        Some(personalization) => {
+            let mut personalized_input = Vec::with_capacity(input.len() + 1);
+            personalized_input.push(personalization);
+            personalized_input.extend_from_slice(input);
+            MNT4PoseidonHash::evaluate(personalized_input.as_slice())
+        },
+        None => MNT4PoseidonHash::evaluate(input),
+    }
+}
+
+/// Enforces the computation of the Poseidon hash of `input` in `domain`, according to `version`.
+/// The personalization is hardcoded in the circuit.
+pub fn domain_separated_hash_gadget<CS: ConstraintSystem<MNT4Fr>>(
+    mut cs:  CS,
+    domain:  HashDomain,
+    version: DomainSeparationVersion,
+    input:   &[MNT4FrGadget],
+) -> Result<MNT4FrGadget, SynthesisError>
+{
+    match get_personalization(domain, version) {
+        Some(personalization) => {
+            let personalization_g = MNT4FrGadget::zero(cs.ns(|| "hardcode personalization zero"))?
+                .add_constant(cs.ns(|| "hardcode personalization"), &personalization)?;
+
+            let mut personalized_input = Vec::with_capacity(input.len() + 1);
+            personalized_input.push(personalization_g);
+            personalized_input.extend_from_slice(input);
+            MNT4PoseidonHashGadget::check_evaluation_gadget(
+                cs.ns(|| "personalized hash"),
+                personalized_input.as_slice(),
+            )
+        },
+        None => MNT4PoseidonHashGadget::check_evaluation_gadget(cs.ns(|| "hash"), input),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use r1cs_std::{alloc::AllocGadget, test_constraint_system::TestConstraintSystem};
+    use rand::{Rng, rngs::OsRng};
+
+    const DOMAINS: [HashDomain; 20] = [
+        HashDomain::PksHash,
+        HashDomain::PksThresholdHash,
+        HashDomain::MsgToSign,
+        HashDomain::WCertSysDataHash,
+        HashDomain::AggregatedInput,
+        HashDomain::SchnorrNonce,
+        HashDomain::HdMasterKey,
+        HashDomain::HdChildKey,
+        HashDomain::ProofOfPossession,
+        HashDomain::MuSigKeyList,
+        HashDomain::MuSigKeyCoefficient,
+        HashDomain::MuSigNonceCommitment,
+        HashDomain::MuSigChallenge,
+        HashDomain::SmtLeaf,
+        HashDomain::SmtLeafPosition,
+        HashDomain::SchnorrBytesMessage,
+        HashDomain::EpochNonceFold,
+        HashDomain::EpochRandomness,
+        HashDomain::VrfLeader,
+        HashDomain::VrfNonce,
+    ];
+
+    #[test]
+    fn test_personalizations() {
+        //V0 is the plain hash
+        let mut rng = OsRng::default();
+        let input: Vec<MNT4Fr> = (0..3).map(|_| rng.gen()).collect();
+        for &domain in DOMAINS.iter() {
+            assert!(get_personalization(domain, DomainSeparationVersion::V0).is_none());
+            assert_eq!(
+                domain_separated_hash(domain, DomainSeparationVersion::V0, input.as_slice()).unwrap(),
+                MNT4PoseidonHash::evaluate(input.as_slice()).unwrap()
+            );
+        }
+
+        //V1 personalizations are all distinct, and so are the hashes of the same input
+        let mut hashes = vec![];
+        for (i, &domain) in DOMAINS.iter().enumerate() {
+            let pers_i = get_personalization(domain, DomainSeparationVersion::V1).unwrap();
+            for &other in DOMAINS[i + 1..].iter() {
+                assert_ne!(pers_i, get_personalization(other, DomainSeparationVersion::V1).unwrap());
+            }
+            hashes.push(domain_separated_hash(domain, DomainSeparationVersion::V1, input.as_slice()).unwrap());
+        }
+        for i in 0..hashes.len() {
+            for j in i + 1..hashes.len() {
+                assert_ne!(hashes[i], hashes[j]);
+            }
+        }
+
+        //Versions are round-tripped through their u8 representation
+        for &version in [DomainSeparationVersion::V0, DomainSeparationVersion::V1].iter() {
+            assert_eq!(DomainSeparationVersion::from_u8(version.to_u8()), Some(version));
+        }
+        assert!(DomainSeparationVersion::from_u8(2).is_none());
+    }
+
+    #[test]
+    fn test_domain_separated_hash_gadget() {
+        let mut rng = OsRng::default();
+        let input: Vec<MNT4Fr> = (0..3).map(|_| rng.gen()).collect();
+
+        for &version in [DomainSeparationVersion::V0, DomainSeparationVersion::V1].iter() {
+            for &domain in DOMAINS.iter() {
+                let mut cs = TestConstraintSystem::<MNT4Fr>::new();
+                let input_g = input.iter().enumerate().map(|(i, fe)|
+                    MNT4FrGadget::alloc(cs.ns(|| format!("alloc input {}", i)), || Ok(*fe)).unwrap()
+                ).collect::<Vec<_>>();
+
+                let hash_g = domain_separated_hash_gadget(
+                    cs.ns(|| "domain separated hash"), domain, version, input_g.as_slice()
+                ).unwrap();
+
+                assert!(cs.is_satisfied());
+                assert_eq!(
+                    hash_g.get_value().unwrap(),
+                    domain_separated_hash(domain, version, input.as_slice()).unwrap()
+                );
+            }
+        }
+    }
+}
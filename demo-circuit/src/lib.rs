@@ -33,5 +33,7 @@ unsafe_code
 )]
 #![forbid(unsafe_code)]
 
+pub mod domain_separation;
 pub mod naive_threshold_sig;
+pub mod sparse_merkle_tree;
 pub mod constants;
\ No newline at end of file
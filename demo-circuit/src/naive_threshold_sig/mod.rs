@@ -11,7 +11,7 @@ use r1cs_crypto::{
         schnorr::field_based_schnorr::{FieldBasedSchnorrSigGadget, FieldBasedSchnorrSigVerificationGadget},
         FieldBasedSigGadget,
     },
-    crh::{MNT4PoseidonHashGadget, FieldBasedHashGadget},
+    crh::MNT4PoseidonHashGadget,
 };
 
 use r1cs_std::{groups::curves::short_weierstrass::mnt::mnt6::mnt6753::MNT6G1Gadget, fields::{
@@ -22,7 +22,10 @@ use r1cs_std::{groups::curves::short_weierstrass::mnt::mnt6::mnt6753::MNT6G1Gadg
 
 use r1cs_core::{ConstraintSystem, ConstraintSynthesizer, SynthesisError};
 
-use crate::constants::NaiveThresholdSigParams;
+use crate::{
+    constants::NaiveThresholdSigParams,
+    domain_separation::{DomainSeparationVersion, HashDomain, domain_separated_hash_gadget},
+};
 
 use std::marker::PhantomData;
 use rand::rngs::OsRng;
@@ -54,6 +57,7 @@ pub struct NaiveTresholdSignature<F: PrimeField>{
 
     //Other
     max_pks:                  usize,
+    version:                  DomainSeparationVersion,
     _field:                   PhantomData<F>,
 }
 
@@ -67,6 +71,7 @@ impl<F: PrimeField>NaiveTresholdSignature<F> {
         prev_end_epoch_mc_b_hash: MNT4Fr,
         mr_bt:                    MNT4Fr,
         max_pks:                  usize,
+        version:                  DomainSeparationVersion,
     ) -> Self {
 
         //Convert b to the needed bool vector
@@ -85,6 +90,7 @@ impl<F: PrimeField>NaiveTresholdSignature<F> {
             prev_end_epoch_mc_b_hash: Some(prev_end_epoch_mc_b_hash),
             mr_bt:                    Some(mr_bt),
             max_pks,
+            version,
             _field: PhantomData
         }
     }
@@ -116,8 +122,10 @@ impl<F: PrimeField> ConstraintSynthesizer<MNT4Fr> for NaiveTresholdSignature<F>
         }
 
         //Enforce pks_threshold_hash
-        let mut pks_threshold_hash_g = MNT4PoseidonHashGadget::check_evaluation_gadget(
+        let mut pks_threshold_hash_g = domain_separated_hash_gadget(
             cs.ns(|| "hash public keys"),
+            HashDomain::PksHash,
+            self.version,
             pks_g.iter().map(|pk| pk.x.clone()).collect::<Vec<_>>().as_slice(),
         )?;
 
@@ -127,8 +135,10 @@ impl<F: PrimeField> ConstraintSynthesizer<MNT4Fr> for NaiveTresholdSignature<F>
             || self.threshold.ok_or(SynthesisError::AssignmentMissing)
         )?;
 
-        pks_threshold_hash_g = MNT4PoseidonHashGadget::check_evaluation_gadget(
+        pks_threshold_hash_g = domain_separated_hash_gadget(
             cs.ns(|| "H(H(pks), threshold)"),
+            HashDomain::PksThresholdHash,
+            self.version,
             &[pks_threshold_hash_g, t_g.clone()],
         )?;
 
@@ -150,8 +160,10 @@ impl<F: PrimeField> ConstraintSynthesizer<MNT4Fr> for NaiveTresholdSignature<F>
             || self.end_epoch_mc_b_hash.ok_or(SynthesisError::AssignmentMissing)
         )?;
 
-        let message_g = MNT4PoseidonHashGadget::check_evaluation_gadget(
+        let message_g = domain_separated_hash_gadget(
             cs.ns(|| "H(MR(BT), BH(i-1), BH(i))"),
+            HashDomain::MsgToSign,
+            self.version,
             &[mr_bt_g.clone(), prev_end_epoch_mc_block_hash_g.clone(), end_epoch_mc_block_hash_g.clone()],
         )?;
 
@@ -192,15 +204,19 @@ impl<F: PrimeField> ConstraintSynthesizer<MNT4Fr> for NaiveTresholdSignature<F>
         }
 
         //Enforce wcert_sysdata_hash
-        let wcert_sysdata_hash_g = MNT4PoseidonHashGadget::check_evaluation_gadget(
+        let wcert_sysdata_hash_g = domain_separated_hash_gadget(
             cs.ns(|| "H(valid_signatures, MR(BT), BH(i-1), BH(i))"),
+            HashDomain::WCertSysDataHash,
+            self.version,
             &[valid_signatures.clone(), mr_bt_g, prev_end_epoch_mc_block_hash_g, end_epoch_mc_block_hash_g]
         )?;
 
         //Check pks_threshold_hash and wcert_sysdata_hash
 
-        let actual_aggregated_input = MNT4PoseidonHashGadget::check_evaluation_gadget(
+        let actual_aggregated_input = domain_separated_hash_gadget(
             cs.ns(|| "H(pks_threshold_hash, wcert_sysdata_hash)"),
+            HashDomain::AggregatedInput,
+            self.version,
             &[pks_threshold_hash_g, wcert_sysdata_hash_g]
         )?;
 
@@ -246,7 +262,7 @@ use algebra::curves::mnt4753::MNT4;
 use proof_systems::groth16::{Parameters, generator::generate_random_parameters};
 
 #[allow(dead_code)]
-pub fn generate_parameters(max_pks: usize) -> Result<Parameters<MNT4>, SynthesisError> {
+pub fn generate_parameters(max_pks: usize, version: DomainSeparationVersion) -> Result<Parameters<MNT4>, SynthesisError> {
 
     //Istantiating rng
     let mut rng = OsRng::default();
@@ -264,6 +280,7 @@ pub fn generate_parameters(max_pks: usize) -> Result<Parameters<MNT4>, Synthesis
         prev_end_epoch_mc_b_hash: None,
         mr_bt:                    None,
         max_pks,
+        version,
         _field:                   PhantomData
     };
 
@@ -276,7 +293,6 @@ mod test {
     use super::*;
     use algebra::{curves::mnt4753::MNT4, BigInteger768, ProjectiveCurve};
     use primitives::{
-        crh::FieldBasedHash,
         signature::{
             FieldBasedSignatureScheme, schnorr::field_based_schnorr::FieldBasedSchnorrSignatureScheme,
         },
@@ -289,6 +305,7 @@ mod test {
     use rand::{
         Rng, rngs::OsRng
     };
+    use crate::domain_separation::{domain_separated_hash, CURRENT_DOMAIN_SEPARATION_VERSION};
 
     type SchnorrSig = FieldBasedSchnorrSignatureScheme<MNT4Fr, MNT6G1Projective, MNT4PoseidonHash>;
 
@@ -298,6 +315,7 @@ mod test {
         threshold:                usize,
         wrong_pks_threshold_hash: bool,
         wrong_wcert_sysdata_hash: bool,
+        version:                  DomainSeparationVersion,
         params:                   Parameters<MNT4>,
     ) -> Result<(Proof<MNT4>, Vec<MNT4Fr>), SynthesisError> {
 
@@ -308,7 +326,9 @@ mod test {
         let mr_bt: MNT4Fr = rng.gen();
         let prev_end_epoch_mc_b_hash: MNT4Fr = rng.gen();
         let end_epoch_mc_b_hash: MNT4Fr = rng.gen();
-        let message = MNT4PoseidonHash::evaluate(&[mr_bt, prev_end_epoch_mc_b_hash, end_epoch_mc_b_hash]).unwrap();
+        let message = domain_separated_hash(
+            HashDomain::MsgToSign, version, &[mr_bt, prev_end_epoch_mc_b_hash, end_epoch_mc_b_hash]
+        ).unwrap();
 
         //Generate another random message used to simulate a non-valid signature
         let invalid_message: MNT4Fr = rng.gen();
@@ -345,26 +365,30 @@ mod test {
 
         //Compute pks_threshold_hash
         let pks_hash_input = pks.iter().map(|pk| pk.into_affine().x).collect::<Vec<_>>();
-        let pks_hash = MNT4PoseidonHash::evaluate(pks_hash_input.as_slice()).unwrap();
+        let pks_hash = domain_separated_hash(HashDomain::PksHash, version, pks_hash_input.as_slice()).unwrap();
         let pks_threshold_hash = if !wrong_pks_threshold_hash {
-            MNT4PoseidonHash::evaluate(&[pks_hash, t_field]).unwrap()
+            domain_separated_hash(HashDomain::PksThresholdHash, version, &[pks_hash, t_field]).unwrap()
         } else {
             rng.gen()
         };
 
         //Compute wcert_sysdata_hash
         let wcert_sysdata_hash = if !wrong_wcert_sysdata_hash {
-            MNT4PoseidonHash::evaluate(&[valid_field, mr_bt, prev_end_epoch_mc_b_hash, end_epoch_mc_b_hash]).unwrap()
+            domain_separated_hash(
+                HashDomain::WCertSysDataHash, version, &[valid_field, mr_bt, prev_end_epoch_mc_b_hash, end_epoch_mc_b_hash]
+            ).unwrap()
         } else {
             rng.gen()
         };
 
-        let aggregated_input = MNT4PoseidonHash::evaluate(&[pks_threshold_hash, wcert_sysdata_hash]).unwrap();
+        let aggregated_input = domain_separated_hash(
+            HashDomain::AggregatedInput, version, &[pks_threshold_hash, wcert_sysdata_hash]
+        ).unwrap();
 
         //Create proof for our circuit
         let c = NaiveTresholdSignature::<MNT4Fr>::new(
             pks, sigs, t_field, b_field, end_epoch_mc_b_hash,
-            prev_end_epoch_mc_b_hash, mr_bt, max_pks,
+            prev_end_epoch_mc_b_hash, mr_bt, max_pks, version,
         );
 
         //Return proof and public inputs if success
@@ -380,30 +404,39 @@ mod test {
         proof
     }
 
-    #[test]
-    fn test_naive_threshold_circuit() {
+    fn test_naive_threshold_circuit_with_version(version: DomainSeparationVersion) {
         let n = 6;
-        let params = generate_parameters(n).unwrap();
+        let params = generate_parameters(n, version).unwrap();
         let pvk = prepare_verifying_key(&params.vk);
 
         //Generate proof with correct witnesses and v > t
         let (proof, public_inputs) =
-            generate_test_proof(n, 5, 4, false, false, params.clone()).unwrap();
+            generate_test_proof(n, 5, 4, false, false, version, params.clone()).unwrap();
         assert!(verify_proof(&pvk, &proof, public_inputs.as_slice()).unwrap());
 
         //Generate proof with insufficient valid signatures
         let (proof, public_inputs) =
-            generate_test_proof(n, 4, 5, false, false, params.clone()).unwrap();
+            generate_test_proof(n, 4, 5, false, false, version, params.clone()).unwrap();
         assert!(!verify_proof(&pvk, &proof, public_inputs.as_slice()).unwrap());
 
         //Generate proof with bad pks_threshold_hash
         let (proof, public_inputs) =
-            generate_test_proof(n, 5, 4, true, false, params.clone()).unwrap();
+            generate_test_proof(n, 5, 4, true, false, version, params.clone()).unwrap();
         assert!(!verify_proof(&pvk, &proof, public_inputs.as_slice()).unwrap());
 
         //Generate proof with bad wcert_sysdata_hash
         let (proof, public_inputs) =
-            generate_test_proof(n, 5, 4, false, true, params.clone()).unwrap();
+            generate_test_proof(n, 5, 4, false, true, version, params.clone()).unwrap();
         assert!(!verify_proof(&pvk, &proof, public_inputs.as_slice()).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_naive_threshold_circuit() {
+        test_naive_threshold_circuit_with_version(CURRENT_DOMAIN_SEPARATION_VERSION);
+    }
+
+    #[test]
+    fn test_naive_threshold_circuit_legacy() {
+        test_naive_threshold_circuit_with_version(DomainSeparationVersion::V0);
+    }
+}
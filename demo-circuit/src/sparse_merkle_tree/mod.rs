@@ -0,0 +1,295 @@
+use algebra::{fields::mnt4753::Fr as MNT4Fr, Field};
+use r1cs_crypto::crh::{MNT4PoseidonHashGadget, FieldBasedHashGadget};
+use r1cs_std::{
+    fields::{fp::FpGadget, FieldGadget},
+    bits::{boolean::Boolean, ToBitsGadget, FromBitsGadget},
+    alloc::AllocGadget,
+    select::CondSelectGadget,
+    eq::EqGadget,
+    Assignment,
+};
+use r1cs_core::{ConstraintSystem, SynthesisError};
+use crate::domain_separation::{domain_separated_hash_gadget, DomainSeparationVersion, HashDomain};
+
+//Field types
+type MNT4FrGadget = FpGadget<MNT4Fr>;
+
+// Gadgets enforcing (non-)membership proofs of the Poseidon-based Sparse Merkle Tree exposed by
+// the api crate: the position of a key is given by the `height` least significant bits of H(key) in
+// the SmtLeafPosition domain, a leaf is H(key, value) in the SmtLeaf domain and an empty leaf is
+// zero. The height of the tree is the number of siblings, fixed by the circuit.
+
+/// Return the bits of the position of `key` in a tree of height `height`, least significant first.
+pub fn smt_leaf_position_gadget<CS: ConstraintSystem<MNT4Fr>>(
+    mut cs: CS,
+    key:    &MNT4FrGadget,
+    height: usize,
+) -> Result<Vec<Boolean>, SynthesisError>
+{
+    let key_hash = domain_separated_hash_gadget(
+        cs.ns(|| "H(key)"),
+        HashDomain::SmtLeafPosition,
+        DomainSeparationVersion::V1,
+        &[key.clone()],
+    )?;
+
+    // Strict decomposition, otherwise the low bits of H(key) + p could be used instead
+    let key_hash_bits = key_hash.to_bits_strict(cs.ns(|| "H(key) to bits"))?;
+    Ok(key_hash_bits[key_hash_bits.len() - height..].iter().rev().cloned().collect())
+}
+
+/// Compute the root starting from `leaf`, its position bits and the siblings along its path.
+pub fn smt_compute_root_gadget<CS: ConstraintSystem<MNT4Fr>>(
+    mut cs:        CS,
+    leaf:          &MNT4FrGadget,
+    position_bits: &[Boolean],
+    siblings:      &[MNT4FrGadget],
+) -> Result<MNT4FrGadget, SynthesisError>
+{
+    assert_eq!(position_bits.len(), siblings.len());
+
+    let mut node = leaf.clone();
+    for (i, (bit, sibling)) in position_bits.iter().zip(siblings.iter()).enumerate() {
+        // If bit is set the current node is the right child
+        let left = MNT4FrGadget::conditionally_select(
+            cs.ns(|| format!("select left {}", i)), bit, sibling, &node
+        )?;
+        let right = MNT4FrGadget::conditionally_select(
+            cs.ns(|| format!("select right {}", i)), bit, &node, sibling
+        )?;
+        node = MNT4PoseidonHashGadget::check_evaluation_gadget(
+            cs.ns(|| format!("hash level {}", i)),
+            &[left, right],
+        )?;
+    }
+    Ok(node)
+}
+
+/// Enforce that (key, value) belongs to the tree with root `root`.
+pub fn smt_enforce_membership_gadget<CS: ConstraintSystem<MNT4Fr>>(
+    mut cs:   CS,
+    root:     &MNT4FrGadget,
+    key:      &MNT4FrGadget,
+    value:    &MNT4FrGadget,
+    siblings: &[MNT4FrGadget],
+) -> Result<(), SynthesisError>
+{
+    let position_bits = smt_leaf_position_gadget(cs.ns(|| "key position"), key, siblings.len())?;
+
+    let leaf = domain_separated_hash_gadget(
+        cs.ns(|| "H(key, value)"),
+        HashDomain::SmtLeaf,
+        DomainSeparationVersion::V1,
+        &[key.clone(), value.clone()],
+    )?;
+
+    smt_compute_root_gadget(cs.ns(|| "compute root"), &leaf, position_bits.as_slice(), siblings)?
+        .enforce_equal(cs.ns(|| "check root"), root)
+}
+
+/// Enforce that `key` doesn't belong to the tree with root `root`: the leaf at its position is
+/// either empty (`occupant_is_empty` set), or stores (occupant_key, occupant_value) with
+/// occupant_key != key. Occupant key and value are ignored when `occupant_is_empty` is set.
+pub fn smt_enforce_non_membership_gadget<CS: ConstraintSystem<MNT4Fr>>(
+    mut cs:            CS,
+    root:              &MNT4FrGadget,
+    key:               &MNT4FrGadget,
+    occupant_is_empty: &Boolean,
+    occupant_key:      &MNT4FrGadget,
+    occupant_value:    &MNT4FrGadget,
+    siblings:          &[MNT4FrGadget],
+) -> Result<(), SynthesisError>
+{
+    let height = siblings.len();
+    let position_bits = smt_leaf_position_gadget(cs.ns(|| "key position"), key, height)?;
+
+    // If occupied, the occupant must be in the same position of key...
+    let occupant_position_bits = smt_leaf_position_gadget(
+        cs.ns(|| "occupant key position"), occupant_key, height
+    )?;
+    let position = MNT4FrGadget::from_bits(
+        cs.ns(|| "pack key position"),
+        position_bits.iter().rev().cloned().collect::<Vec<_>>().as_slice(),
+    )?;
+    let occupant_position = MNT4FrGadget::from_bits(
+        cs.ns(|| "pack occupant key position"),
+        occupant_position_bits.iter().rev().cloned().collect::<Vec<_>>().as_slice(),
+    )?;
+    MNT4FrGadget::conditionally_select(
+        cs.ns(|| "select occupant position"), occupant_is_empty, &position, &occupant_position
+    )?.enforce_equal(cs.ns(|| "check occupant position"), &position)?;
+
+    // ...and must be different from key: enforce that key - occupant_key is invertible
+    let one = MNT4FrGadget::one(cs.ns(|| "alloc one"))?;
+    let diff = key.sub(cs.ns(|| "key - occupant_key"), occupant_key)?;
+    let diff = MNT4FrGadget::conditionally_select(
+        cs.ns(|| "select key difference"), occupant_is_empty, &one, &diff
+    )?;
+    let diff_inv = MNT4FrGadget::alloc(
+        cs.ns(|| "alloc key difference inverse"),
+        || Ok(diff.get_value().get()?.inverse().unwrap_or(MNT4Fr::zero()))
+    )?;
+    diff.mul(cs.ns(|| "key difference * inverse"), &diff_inv)?
+        .enforce_equal(cs.ns(|| "check key != occupant_key"), &one)?;
+
+    // Compute the leaf, being it empty or not
+    let zero = MNT4FrGadget::zero(cs.ns(|| "alloc zero"))?;
+    let occupant_leaf = domain_separated_hash_gadget(
+        cs.ns(|| "H(occupant_key, occupant_value)"),
+        HashDomain::SmtLeaf,
+        DomainSeparationVersion::V1,
+        &[occupant_key.clone(), occupant_value.clone()],
+    )?;
+    let leaf = MNT4FrGadget::conditionally_select(
+        cs.ns(|| "select leaf"), occupant_is_empty, &zero, &occupant_leaf
+    )?;
+
+    smt_compute_root_gadget(cs.ns(|| "compute root"), &leaf, position_bits.as_slice(), siblings)?
+        .enforce_equal(cs.ns(|| "check root"), root)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain_separation::domain_separated_hash;
+    use algebra::PrimeField;
+    use primitives::crh::{FieldBasedHash, MNT4PoseidonHash};
+    use r1cs_std::test_constraint_system::TestConstraintSystem;
+    use rand::{Rng, rngs::OsRng};
+
+    const HEIGHT: usize = 8;
+
+    fn compute_root(leaf: MNT4Fr, position: u64, siblings: &[MNT4Fr]) -> MNT4Fr {
+        let mut node = leaf;
+        for (i, sibling) in siblings.iter().enumerate() {
+            node = if (position >> i) & 1 == 0 {
+                MNT4PoseidonHash::evaluate(&[node, *sibling]).unwrap()
+            } else {
+                MNT4PoseidonHash::evaluate(&[*sibling, node]).unwrap()
+            };
+        }
+        node
+    }
+
+    fn leaf_position(key: MNT4Fr, height: usize) -> u64 {
+        let key_hash = domain_separated_hash(HashDomain::SmtLeafPosition, DomainSeparationVersion::V1, &[key]).unwrap();
+        key_hash.into_repr().0[0] & ((1 << height) - 1)
+    }
+
+    //Positions are given by H(key): draw random keys until one satisfies `predicate`
+    fn find_key<R: Rng>(rng: &mut R, predicate: impl Fn(MNT4Fr) -> bool) -> MNT4Fr {
+        loop {
+            let key: MNT4Fr = rng.gen();
+            if predicate(key) { return key }
+        }
+    }
+
+    fn hash_leaf(key: MNT4Fr, value: MNT4Fr) -> MNT4Fr {
+        domain_separated_hash(HashDomain::SmtLeaf, DomainSeparationVersion::V1, &[key, value]).unwrap()
+    }
+
+    fn alloc_siblings<CS: ConstraintSystem<MNT4Fr>>(mut cs: CS, siblings: &[MNT4Fr]) -> Vec<MNT4FrGadget> {
+        siblings.iter().enumerate().map(|(i, s)|
+            MNT4FrGadget::alloc(cs.ns(|| format!("alloc sibling {}", i)), || Ok(*s)).unwrap()
+        ).collect()
+    }
+
+    fn check_membership(root: MNT4Fr, key: MNT4Fr, value: MNT4Fr, siblings: &[MNT4Fr]) -> bool {
+        let mut cs = TestConstraintSystem::<MNT4Fr>::new();
+        let root_g = MNT4FrGadget::alloc_input(cs.ns(|| "alloc root"), || Ok(root)).unwrap();
+        let key_g = MNT4FrGadget::alloc(cs.ns(|| "alloc key"), || Ok(key)).unwrap();
+        let value_g = MNT4FrGadget::alloc(cs.ns(|| "alloc value"), || Ok(value)).unwrap();
+        let siblings_g = alloc_siblings(cs.ns(|| "alloc siblings"), siblings);
+        smt_enforce_membership_gadget(
+            cs.ns(|| "enforce membership"), &root_g, &key_g, &value_g, siblings_g.as_slice()
+        ).unwrap();
+        cs.is_satisfied()
+    }
+
+    fn check_non_membership(
+        root:     MNT4Fr,
+        key:      MNT4Fr,
+        occupant: Option<(MNT4Fr, MNT4Fr)>,
+        siblings: &[MNT4Fr],
+    ) -> bool {
+        let mut cs = TestConstraintSystem::<MNT4Fr>::new();
+        let root_g = MNT4FrGadget::alloc_input(cs.ns(|| "alloc root"), || Ok(root)).unwrap();
+        let key_g = MNT4FrGadget::alloc(cs.ns(|| "alloc key"), || Ok(key)).unwrap();
+        let is_empty_g = Boolean::alloc(cs.ns(|| "alloc is empty"), || Ok(occupant.is_none())).unwrap();
+        let (occupant_key, occupant_value) = occupant.unwrap_or((MNT4Fr::zero(), MNT4Fr::zero()));
+        let occupant_key_g = MNT4FrGadget::alloc(cs.ns(|| "alloc occupant key"), || Ok(occupant_key)).unwrap();
+        let occupant_value_g = MNT4FrGadget::alloc(cs.ns(|| "alloc occupant value"), || Ok(occupant_value)).unwrap();
+        let siblings_g = alloc_siblings(cs.ns(|| "alloc siblings"), siblings);
+        smt_enforce_non_membership_gadget(
+            cs.ns(|| "enforce non membership"), &root_g, &key_g, &is_empty_g,
+            &occupant_key_g, &occupant_value_g, siblings_g.as_slice()
+        ).unwrap();
+        cs.is_satisfied()
+    }
+
+    #[test]
+    fn test_smt_membership_gadget() {
+        let mut rng = OsRng::default();
+        let siblings = (0..HEIGHT).map(|_| rng.gen()).collect::<Vec<MNT4Fr>>();
+        let key: MNT4Fr = rng.gen();
+        let value: MNT4Fr = rng.gen();
+        let root = compute_root(hash_leaf(key, value), leaf_position(key, HEIGHT), siblings.as_slice());
+
+        assert!(check_membership(root, key, value, siblings.as_slice()));
+
+        //Negative cases
+        assert!(!check_membership(rng.gen(), key, value, siblings.as_slice()));
+        assert!(!check_membership(root, key, rng.gen(), siblings.as_slice()));
+        assert!(!check_membership(root, rng.gen(), value, siblings.as_slice()));
+
+        //The position is the one of H(key), not the one of key
+        let key_position = key.into_repr().0[0] & ((1 << HEIGHT) - 1);
+        if key_position != leaf_position(key, HEIGHT) {
+            let root = compute_root(hash_leaf(key, value), key_position, siblings.as_slice());
+            assert!(!check_membership(root, key, value, siblings.as_slice()));
+        }
+    }
+
+    #[test]
+    fn test_smt_non_membership_gadget() {
+        let mut rng = OsRng::default();
+        let siblings = (0..HEIGHT).map(|_| rng.gen()).collect::<Vec<MNT4Fr>>();
+        let key: MNT4Fr = rng.gen();
+        let position = leaf_position(key, HEIGHT);
+
+        //Empty leaf
+        let empty_root = compute_root(MNT4Fr::zero(), position, siblings.as_slice());
+        assert!(check_non_membership(empty_root, key, None, siblings.as_slice()));
+
+        //Leaf taken by a different key in the same position
+        let occupant_key = find_key(&mut rng, |k| k != key && leaf_position(k, HEIGHT) == position);
+        let occupant_value: MNT4Fr = rng.gen();
+        let root = compute_root(
+            hash_leaf(occupant_key, occupant_value), position, siblings.as_slice()
+        );
+        assert!(check_non_membership(root, key, Some((occupant_key, occupant_value)), siblings.as_slice()));
+
+        //Negative cases
+        assert!(!check_non_membership(root, key, None, siblings.as_slice()));
+        assert!(!check_non_membership(empty_root, occupant_key, Some((occupant_key, occupant_value)), siblings.as_slice()));
+
+        //Occupant in a different position
+        let other_key = find_key(&mut rng, |k| leaf_position(k, HEIGHT) != position);
+        let other_root = compute_root(
+            hash_leaf(other_key, occupant_value), position, siblings.as_slice()
+        );
+        assert!(!check_non_membership(other_root, key, Some((other_key, occupant_value)), siblings.as_slice()));
+
+        //Membership of the occupant key
+        assert!(check_membership(root, occupant_key, occupant_value, siblings.as_slice()));
+
+        //The children (L, R) of an internal node can't be passed off as a leaf, nor as an occupant, in
+        //a circuit one level shorter: L is in position q of the shorter tree
+        let left: MNT4Fr = rng.gen();
+        let q = leaf_position(left, HEIGHT - 1);
+        let root_with_left = compute_root(left, q << 1, siblings.as_slice());
+        assert!(!check_membership(root_with_left, left, siblings[0], &siblings[1..]));
+        let key_in_q = find_key(&mut rng, |k| k != left && leaf_position(k, HEIGHT - 1) == q);
+        assert!(!check_non_membership(root_with_left, key_in_q, Some((left, siblings[0])), &siblings[1..]));
+    }
+}
@@ -0,0 +1,43 @@
+package com.horizen.consensusnative;
+
+import com.horizen.librustsidechains.FieldElement;
+
+// Slot, stake and epoch randomness a forger eligibility is proved and checked against.
+public class ForgerEligibilityParameters {
+    private FieldElement epochRandomness;
+    private int slotNumber;
+    private long forgerStake;
+    private long totalForgersStake;
+
+    public ForgerEligibilityParameters(FieldElement epochRandomness, int slotNumber, long forgerStake, long totalForgersStake) {
+        if (slotNumber < 0)
+            throw new IllegalArgumentException(String.format("Slot number must not be negative, %d found", slotNumber));
+        if (forgerStake < 0)
+            throw new IllegalArgumentException(String.format("Forger stake must not be negative, %d found", forgerStake));
+        if (totalForgersStake <= 0)
+            throw new IllegalArgumentException(String.format("Total forgers stake must be positive, %d found", totalForgersStake));
+        if (forgerStake > totalForgersStake)
+            throw new IllegalArgumentException(String.format("Forger stake %d exceeds the total forgers stake %d", forgerStake, totalForgersStake));
+
+        this.epochRandomness = epochRandomness;
+        this.slotNumber = slotNumber;
+        this.forgerStake = forgerStake;
+        this.totalForgersStake = totalForgersStake;
+    }
+
+    public FieldElement getEpochRandomness() {
+        return this.epochRandomness;
+    }
+
+    public int getSlotNumber() {
+        return this.slotNumber;
+    }
+
+    public long getForgerStake() {
+        return this.forgerStake;
+    }
+
+    public long getTotalForgersStake() {
+        return this.totalForgersStake;
+    }
+}
@@ -0,0 +1,73 @@
+package com.horizen.consensusnative;
+
+import com.horizen.librustsidechains.Library;
+import com.horizen.vrfnative.VRFPublicKey;
+import com.horizen.vrfnative.VRFSecretKey;
+
+// Proof that a forger is the leader of a slot, according to its stake: made of the VRF proofs of the
+// leader election and of the epoch nonce evaluations of the slot.
+public class ForgerEligibilityProof
+{
+  public static final int PROOF_LENGTH = 770;
+
+  private long proofPointer;
+
+  static {
+    Library.load();
+  }
+
+  private ForgerEligibilityProof(long proofPointer) {
+    if (proofPointer == 0)
+      throw new IllegalArgumentException("Proof pointer must be not null.");
+    this.proofPointer = proofPointer;
+  }
+
+  private static native byte[] nativeSerializeProof(long proofPointer);
+
+  private static native ForgerEligibilityProof nativeDeserializeProof(byte[] proofBytes);
+
+  private static native void nativeFreeProof(long proofPointer);
+
+  // Throws DeserializationException if the bytes don't encode a valid proof.
+  public static ForgerEligibilityProof deserialize(byte[] proofBytes) {
+    if (proofBytes.length != PROOF_LENGTH)
+      throw new IllegalArgumentException(String.format("Incorrect proof length, %d expected, %d found", PROOF_LENGTH, proofBytes.length));
+
+    return nativeDeserializeProof(proofBytes);
+  }
+
+  public byte[] serializeProof() {
+    if (proofPointer == 0)
+      throw new IllegalArgumentException("Proof was freed.");
+
+    return nativeSerializeProof(this.proofPointer);
+  }
+
+  private static native ForgerEligibilityProof nativeProve(VRFSecretKey secretKey, VRFPublicKey publicKey,
+                                                           ForgerEligibilityParameters params);
+
+  // Returns null if the forger is not the leader of the slot. Throws IllegalStateException if the proof
+  // can't be computed.
+  public static ForgerEligibilityProof prove(VRFSecretKey secretKey, VRFPublicKey publicKey,
+                                             ForgerEligibilityParameters params) {
+    return nativeProve(secretKey, publicKey, params);
+  }
+
+  private native ForgerEligibilityResult nativeCheck(VRFPublicKey publicKey, ForgerEligibilityParameters params);
+
+  // Returns the VRF outputs of the slot, or null if the proof is invalid or doesn't prove that the
+  // forger is the leader of the slot. Throws IllegalStateException if the proof can't be checked.
+  public ForgerEligibilityResult check(VRFPublicKey publicKey, ForgerEligibilityParameters params) {
+    if (proofPointer == 0)
+      throw new IllegalArgumentException("Proof was freed.");
+
+    return nativeCheck(publicKey, params);
+  }
+
+  public void freeProof() {
+    if (proofPointer != 0) {
+      nativeFreeProof(this.proofPointer);
+      proofPointer = 0;
+    }
+  }
+}
@@ -0,0 +1,28 @@
+package com.horizen.consensusnative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+// VRF outputs of a slot, verified by ForgerEligibilityProof.check(): the leader election output, and
+// the epoch nonce output that contributes to the randomness of the next epochs.
+public class ForgerEligibilityResult {
+    private FieldElement leaderOutput;
+    private FieldElement nonceOutput;
+
+    static {
+        Library.load();
+    }
+
+    public ForgerEligibilityResult(FieldElement leaderOutput, FieldElement nonceOutput) {
+        this.leaderOutput = leaderOutput;
+        this.nonceOutput = nonceOutput;
+    }
+
+    public FieldElement getLeaderOutput() {
+        return this.leaderOutput;
+    }
+
+    public FieldElement getNonceOutput() {
+        return this.nonceOutput;
+    }
+}
@@ -0,0 +1,26 @@
+package com.horizen.librustsidechains;
+
+// Thrown when deserializing bytes that don't encode a valid field element, key, signature or proof.
+public class DeserializationException extends RuntimeException {
+
+    // Same order as the codes of the native DeserializationError
+    public enum Reason {
+        INVALID_SIZE,
+        NON_CANONICAL,
+        NOT_ON_CURVE,
+        NOT_IN_SUBGROUP,
+        ZERO
+    }
+
+    private final Reason reason;
+
+    // Called from native code
+    private DeserializationException(int reasonCode, String message) {
+        super(message);
+        this.reason = Reason.values()[reasonCode];
+    }
+
+    public Reason getReason() {
+        return reason;
+    }
+}
@@ -39,6 +39,7 @@ public class FieldElement {
 
     private static native FieldElement nativeDeserializeFieldElement(byte[] fieldElementBytes);
 
+    // Throws DeserializationException if the bytes exceed the field modulus.
     public static FieldElement deserialize(byte[] fieldElementBytes) {
         if (fieldElementBytes.length != FIELD_ELEMENT_LENGTH)
             throw new IllegalArgumentException(String.format("Incorrect field element length, %d expected, %d found",
@@ -0,0 +1,96 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+public class MerkleMountainRange {
+
+    private long merkleMountainRangePointer;
+
+    static {
+        Library.load();
+    }
+
+    private MerkleMountainRange(long merkleMountainRangePointer) {
+        if (merkleMountainRangePointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range pointer must be not null.");
+        this.merkleMountainRangePointer = merkleMountainRangePointer;
+    }
+
+    private static native MerkleMountainRange nativeInit();
+
+    public static MerkleMountainRange init() {
+        return nativeInit();
+    }
+
+    private native boolean nativeAppend(FieldElement leaf);
+
+    public boolean append(FieldElement leaf) {
+        if (merkleMountainRangePointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range was freed.");
+
+        return nativeAppend(leaf);
+    }
+
+    private native long nativeGetLeavesCount();
+
+    public long getLeavesCount() {
+        if (merkleMountainRangePointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range was freed.");
+
+        return nativeGetLeavesCount();
+    }
+
+    private native FieldElement[] nativeGetPeaks();
+
+    // Roots of the perfect subtrees covering the leaves, from the highest to the lowest
+    public FieldElement[] getPeaks() {
+        if (merkleMountainRangePointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range was freed.");
+
+        return nativeGetPeaks();
+    }
+
+    private native FieldElement nativeRoot();
+
+    public FieldElement root() {
+        if (merkleMountainRangePointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range was freed.");
+
+        return nativeRoot();
+    }
+
+    private native MerkleMountainRangeProof nativeGetProof(long leafIndex);
+
+    public MerkleMountainRangeProof getProof(long leafIndex) {
+        if (merkleMountainRangePointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range was freed.");
+
+        return nativeGetProof(leafIndex);
+    }
+
+    private static native byte[] nativeSerialize(long merkleMountainRangePointer);
+
+    public byte[] serialize() {
+        if (merkleMountainRangePointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range was freed.");
+
+        return nativeSerialize(this.merkleMountainRangePointer);
+    }
+
+    private static native MerkleMountainRange nativeDeserialize(byte[] merkleMountainRangeBytes);
+
+    // Throws DeserializationException if the bytes are not the serialization of a merkle mountain range.
+    public static MerkleMountainRange deserialize(byte[] merkleMountainRangeBytes) {
+        return nativeDeserialize(merkleMountainRangeBytes);
+    }
+
+    private static native void nativeFreeMerkleMountainRange(long merkleMountainRangePointer);
+
+    public void freeMerkleMountainRange() {
+        if (merkleMountainRangePointer != 0) {
+            nativeFreeMerkleMountainRange(this.merkleMountainRangePointer);
+            merkleMountainRangePointer = 0;
+        }
+    }
+}
@@ -0,0 +1,54 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+public class MerkleMountainRangeProof {
+
+    private long merkleMountainRangeProofPointer;
+
+    static {
+        Library.load();
+    }
+
+    private MerkleMountainRangeProof(long merkleMountainRangeProofPointer) {
+        if (merkleMountainRangeProofPointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range proof pointer must be not null.");
+        this.merkleMountainRangeProofPointer = merkleMountainRangeProofPointer;
+    }
+
+    private native boolean nativeVerify(FieldElement leaf, FieldElement root);
+
+    public boolean verify(FieldElement leaf, FieldElement root) {
+        if (merkleMountainRangeProofPointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range proof was freed.");
+
+        return nativeVerify(leaf, root);
+    }
+
+    private static native byte[] nativeSerialize(long merkleMountainRangeProofPointer);
+
+    public byte[] serialize() {
+        if (merkleMountainRangeProofPointer == 0)
+            throw new IllegalArgumentException("Merkle mountain range proof was freed.");
+
+        return nativeSerialize(this.merkleMountainRangeProofPointer);
+    }
+
+    private static native MerkleMountainRangeProof nativeDeserialize(byte[] merkleMountainRangeProofBytes);
+
+    // Throws DeserializationException if the bytes are not the serialization of a proof, e.g. they are truncated
+    // or a node is not a canonical field element.
+    public static MerkleMountainRangeProof deserialize(byte[] merkleMountainRangeProofBytes) {
+        return nativeDeserialize(merkleMountainRangeProofBytes);
+    }
+
+    private static native void nativeFreeMerkleMountainRangeProof(long merkleMountainRangeProofPointer);
+
+    public void freeMerkleMountainRangeProof() {
+        if (merkleMountainRangeProofPointer != 0) {
+            nativeFreeMerkleMountainRangeProof(this.merkleMountainRangeProofPointer);
+            merkleMountainRangeProofPointer = 0;
+        }
+    }
+}
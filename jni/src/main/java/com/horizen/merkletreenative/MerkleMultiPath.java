@@ -0,0 +1,70 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+import java.util.List;
+
+public class MerkleMultiPath {
+
+    private long merkleMultiPathPointer;
+
+    static {
+        Library.load();
+    }
+
+    private MerkleMultiPath(long merkleMultiPathPointer) {
+        if (merkleMultiPathPointer == 0)
+            throw new IllegalArgumentException("Merkle multi path pointer must be not null.");
+        this.merkleMultiPathPointer = merkleMultiPathPointer;
+    }
+
+    private native int nativeGetLeavesCount();
+
+    public int getLeavesCount() {
+        if (merkleMultiPathPointer == 0)
+            throw new IllegalArgumentException("Merkle multi path was freed.");
+
+        return nativeGetLeavesCount();
+    }
+
+    private native boolean nativeVerify(FieldElement[] leaves, FieldElement root);
+
+    // Leaves must be passed in the same order of the leaf indices used to create the multi path
+    public boolean verify(List<FieldElement> leaves, FieldElement root) {
+        if (merkleMultiPathPointer == 0)
+            throw new IllegalArgumentException("Merkle multi path was freed.");
+
+        if (leaves.size() != getLeavesCount())
+            throw new IllegalArgumentException(String.format("Expected %d leaves, found %d",
+                    getLeavesCount(), leaves.size()));
+
+        return nativeVerify(leaves.toArray(new FieldElement[0]), root);
+    }
+
+    private static native byte[] nativeSerialize(long merkleMultiPathPointer);
+
+    public byte[] serialize() {
+        if (merkleMultiPathPointer == 0)
+            throw new IllegalArgumentException("Merkle multi path was freed.");
+
+        return nativeSerialize(this.merkleMultiPathPointer);
+    }
+
+    private static native MerkleMultiPath nativeDeserialize(byte[] merkleMultiPathBytes);
+
+    // Throws DeserializationException if the multi path is truncated, has trailing bytes, leaf indices
+    // not strictly increasing or out of range, or a sibling is not a canonical field element.
+    public static MerkleMultiPath deserialize(byte[] merkleMultiPathBytes) {
+        return nativeDeserialize(merkleMultiPathBytes);
+    }
+
+    private static native void nativeFreeMerkleMultiPath(long merkleMultiPathPointer);
+
+    public void freeMerkleMultiPath() {
+        if (merkleMultiPathPointer != 0) {
+            nativeFreeMerkleMultiPath(this.merkleMultiPathPointer);
+            merkleMultiPathPointer = 0;
+        }
+    }
+}
@@ -0,0 +1,63 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+public class MerklePath {
+
+    // Given by the native tree height: (sibling, direction) for each level
+    public static final int MERKLE_PATH_LENGTH;
+
+    private long merklePathPointer;
+
+    static {
+        Library.load();
+        MERKLE_PATH_LENGTH = nativeGetPathSize();
+    }
+
+    private MerklePath(long merklePathPointer) {
+        if (merklePathPointer == 0)
+            throw new IllegalArgumentException("Merkle path pointer must be not null.");
+        this.merklePathPointer = merklePathPointer;
+    }
+
+    private static native int nativeGetPathSize();
+
+    private native boolean nativeVerify(FieldElement leaf, FieldElement root);
+
+    public boolean verify(FieldElement leaf, FieldElement root) {
+        if (merklePathPointer == 0)
+            throw new IllegalArgumentException("Merkle path was freed.");
+
+        return nativeVerify(leaf, root);
+    }
+
+    private native byte[] nativeSerialize();
+
+    public byte[] serialize() {
+        if (merklePathPointer == 0)
+            throw new IllegalArgumentException("Merkle path was freed.");
+
+        return nativeSerialize();
+    }
+
+    private static native MerklePath nativeDeserialize(byte[] merklePathBytes);
+
+    // Throws DeserializationException if a sibling is not a canonical field element, or a direction is not 0 or 1.
+    public static MerklePath deserialize(byte[] merklePathBytes) {
+        if (merklePathBytes.length != MERKLE_PATH_LENGTH)
+            throw new IllegalArgumentException(String.format("Incorrect merkle path length, %d expected, %d found",
+                    MERKLE_PATH_LENGTH, merklePathBytes.length));
+
+        return nativeDeserialize(merklePathBytes);
+    }
+
+    private static native void nativeFreeMerklePath(long merklePathPointer);
+
+    public void freeMerklePath() {
+        if (merklePathPointer != 0) {
+            nativeFreeMerklePath(this.merklePathPointer);
+            merklePathPointer = 0;
+        }
+    }
+}
@@ -0,0 +1,74 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.List;
+
+public class MerkleTree {
+
+    private long merkleTreePointer;
+
+    static {
+        Library.load();
+    }
+
+    private MerkleTree(long merkleTreePointer) {
+        if (merkleTreePointer == 0)
+            throw new IllegalArgumentException("Merkle tree pointer must be not null.");
+        this.merkleTreePointer = merkleTreePointer;
+    }
+
+    private static native MerkleTree nativeInit(FieldElement[] leaves);
+
+    public static MerkleTree init(List<FieldElement> leaves) {
+        return nativeInit(leaves.toArray(new FieldElement[0]));
+    }
+
+    private native FieldElement nativeRoot();
+
+    public FieldElement root() {
+        if (merkleTreePointer == 0)
+            throw new IllegalArgumentException("Merkle tree was freed.");
+
+        return nativeRoot();
+    }
+
+    private native MerklePath nativeGetMerklePath(FieldElement leaf, long leafIndex);
+
+    public MerklePath getMerklePath(FieldElement leaf, long leafIndex) {
+        if (merkleTreePointer == 0)
+            throw new IllegalArgumentException("Merkle tree was freed.");
+
+        return nativeGetMerklePath(leaf, leafIndex);
+    }
+
+    private native MerkleMultiPath nativeGetMerkleMultiPath(FieldElement[] leaves, byte[] leafIndices);
+
+    // Leaf indices must be strictly increasing, and leaves must be passed in the same order
+    public MerkleMultiPath getMerkleMultiPath(List<FieldElement> leaves, List<Long> leafIndices) {
+        if (merkleTreePointer == 0)
+            throw new IllegalArgumentException("Merkle tree was freed.");
+
+        if (leaves.size() != leafIndices.size())
+            throw new IllegalArgumentException(String.format("Expected %d leaves, found %d",
+                    leafIndices.size(), leaves.size()));
+
+        ByteBuffer leafIndicesBytes = ByteBuffer.allocate(8 * leafIndices.size()).order(ByteOrder.LITTLE_ENDIAN);
+        for (Long leafIndex: leafIndices)
+            leafIndicesBytes.putLong(leafIndex);
+
+        return nativeGetMerkleMultiPath(leaves.toArray(new FieldElement[0]), leafIndicesBytes.array());
+    }
+
+    private static native void nativeFreeMerkleTree(long merkleTreePointer);
+
+    public void freeMerkleTree() {
+        if (merkleTreePointer != 0) {
+            nativeFreeMerkleTree(this.merkleTreePointer);
+            merkleTreePointer = 0;
+        }
+    }
+}
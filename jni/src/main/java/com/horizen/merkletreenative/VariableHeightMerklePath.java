@@ -0,0 +1,75 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+public class VariableHeightMerklePath {
+
+    private long merklePathPointer;
+
+    static {
+        Library.load();
+    }
+
+    private VariableHeightMerklePath(long merklePathPointer) {
+        if (merklePathPointer == 0)
+            throw new IllegalArgumentException("Merkle path pointer must be not null.");
+        this.merklePathPointer = merklePathPointer;
+    }
+
+    // Serialized as height (1 byte) || leaf index (8 bytes) || height siblings
+    public static int getMerklePathLength(int height) {
+        return 1 + 8 + height * FieldElement.FIELD_ELEMENT_LENGTH;
+    }
+
+    private native int nativeGetHeight();
+
+    public int getHeight() {
+        if (merklePathPointer == 0)
+            throw new IllegalArgumentException("Merkle path was freed.");
+
+        return nativeGetHeight();
+    }
+
+    private native boolean nativeVerify(FieldElement leaf, VariableHeightMerkleRoot root);
+
+    public boolean verify(FieldElement leaf, VariableHeightMerkleRoot root) {
+        if (merklePathPointer == 0)
+            throw new IllegalArgumentException("Merkle path was freed.");
+
+        if (getHeight() != root.getHeight())
+            throw new IllegalArgumentException(String.format("Merkle path of height %d can't be verified against a root of height %d",
+                    getHeight(), root.getHeight()));
+
+        return nativeVerify(leaf, root);
+    }
+
+    private static native byte[] nativeSerialize(long merklePathPointer);
+
+    public byte[] serialize() {
+        if (merklePathPointer == 0)
+            throw new IllegalArgumentException("Merkle path was freed.");
+
+        return nativeSerialize(this.merklePathPointer);
+    }
+
+    private static native VariableHeightMerklePath nativeDeserialize(byte[] merklePathBytes);
+
+    // Throws DeserializationException if the height or the leaf index are out of range, or a sibling is not
+    // a canonical field element.
+    public static VariableHeightMerklePath deserialize(byte[] merklePathBytes) {
+        if (merklePathBytes.length == 0 || merklePathBytes.length != getMerklePathLength(merklePathBytes[0] & 0xff))
+            throw new IllegalArgumentException("Incorrect merkle path length");
+
+        return nativeDeserialize(merklePathBytes);
+    }
+
+    private static native void nativeFreeMerklePath(long merklePathPointer);
+
+    public void freeMerklePath() {
+        if (merklePathPointer != 0) {
+            nativeFreeMerklePath(this.merklePathPointer);
+            merklePathPointer = 0;
+        }
+    }
+}
@@ -0,0 +1,70 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+public class VariableHeightMerkleRoot {
+
+    public static final int MERKLE_ROOT_LENGTH = 97;
+
+    private long merkleRootPointer;
+
+    static {
+        Library.load();
+    }
+
+    private VariableHeightMerkleRoot(long merkleRootPointer) {
+        if (merkleRootPointer == 0)
+            throw new IllegalArgumentException("Merkle root pointer must be not null.");
+        this.merkleRootPointer = merkleRootPointer;
+    }
+
+    private static native int nativeGetRootSize();
+
+    private native int nativeGetHeight();
+
+    public int getHeight() {
+        if (merkleRootPointer == 0)
+            throw new IllegalArgumentException("Merkle root was freed.");
+
+        return nativeGetHeight();
+    }
+
+    private native FieldElement nativeGetValue();
+
+    public FieldElement getValue() {
+        if (merkleRootPointer == 0)
+            throw new IllegalArgumentException("Merkle root was freed.");
+
+        return nativeGetValue();
+    }
+
+    private static native byte[] nativeSerialize(long merkleRootPointer);
+
+    public byte[] serialize() {
+        if (merkleRootPointer == 0)
+            throw new IllegalArgumentException("Merkle root was freed.");
+
+        return nativeSerialize(this.merkleRootPointer);
+    }
+
+    private static native VariableHeightMerkleRoot nativeDeserialize(byte[] merkleRootBytes);
+
+    // Throws DeserializationException if the height is not supported, or the value is not a canonical field element.
+    public static VariableHeightMerkleRoot deserialize(byte[] merkleRootBytes) {
+        if (merkleRootBytes.length != MERKLE_ROOT_LENGTH)
+            throw new IllegalArgumentException(String.format("Incorrect merkle root length, %d expected, %d found",
+                    MERKLE_ROOT_LENGTH, merkleRootBytes.length));
+
+        return nativeDeserialize(merkleRootBytes);
+    }
+
+    private static native void nativeFreeMerkleRoot(long merkleRootPointer);
+
+    public void freeMerkleRoot() {
+        if (merkleRootPointer != 0) {
+            nativeFreeMerkleRoot(this.merkleRootPointer);
+            merkleRootPointer = 0;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+import java.util.List;
+
+public class VariableHeightMerkleTree {
+
+    private long merkleTreePointer;
+
+    static {
+        Library.load();
+    }
+
+    private VariableHeightMerkleTree(long merkleTreePointer) {
+        if (merkleTreePointer == 0)
+            throw new IllegalArgumentException("Merkle tree pointer must be not null.");
+        this.merkleTreePointer = merkleTreePointer;
+    }
+
+    private static native int nativeGetMinHeight();
+
+    public static int getMinHeight() { return nativeGetMinHeight(); }
+
+    private static native int nativeGetMaxHeight();
+
+    public static int getMaxHeight() { return nativeGetMaxHeight(); }
+
+    private static native VariableHeightMerkleTree nativeInit(FieldElement[] leaves, int height);
+
+    public static VariableHeightMerkleTree init(List<FieldElement> leaves, int height) {
+        if (height < getMinHeight() || height > getMaxHeight())
+            throw new IllegalArgumentException(String.format("Unsupported merkle tree height %d, must be between %d and %d",
+                    height, getMinHeight(), getMaxHeight()));
+
+        return nativeInit(leaves.toArray(new FieldElement[0]), height);
+    }
+
+    private native VariableHeightMerkleRoot nativeRoot();
+
+    public VariableHeightMerkleRoot root() {
+        if (merkleTreePointer == 0)
+            throw new IllegalArgumentException("Merkle tree was freed.");
+
+        return nativeRoot();
+    }
+
+    private native VariableHeightMerklePath nativeGetMerklePath(long leafIndex);
+
+    public VariableHeightMerklePath getMerklePath(long leafIndex) {
+        if (merkleTreePointer == 0)
+            throw new IllegalArgumentException("Merkle tree was freed.");
+
+        return nativeGetMerklePath(leafIndex);
+    }
+
+    private static native void nativeFreeMerkleTree(long merkleTreePointer);
+
+    public void freeMerkleTree() {
+        if (merkleTreePointer != 0) {
+            nativeFreeMerkleTree(this.merkleTreePointer);
+            merkleTreePointer = 0;
+        }
+    }
+}
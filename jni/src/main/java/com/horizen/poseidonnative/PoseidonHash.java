@@ -9,11 +9,83 @@ public class PoseidonHash {
 
     public static final int HASH_LENGTH = 96;
 
+    private long poseidonHashPointer;
+
     static {
         Library.load();
     }
 
+    private PoseidonHash(long poseidonHashPointer) {
+        if (poseidonHashPointer == 0)
+            throw new IllegalArgumentException("Poseidon hash pointer must be not null.");
+        this.poseidonHashPointer = poseidonHashPointer;
+    }
+
+    private static native PoseidonHash nativeGetPoseidonHash();
+
+    public static PoseidonHash getInstance() {
+        return nativeGetPoseidonHash();
+    }
+
+    private native void nativeUpdate(FieldElement input);
+
+    // Appends input to the ones received so far: they are buffered natively, and only hashed by finalizeHash.
+    // This is not a sponge: the native Poseidon implementation can't absorb inputs incrementally, so the
+    // buffered inputs take memory until reset, and finalizeHash costs the same of computeHash on all of them.
+    public void update(FieldElement input) {
+        if (poseidonHashPointer == 0)
+            throw new IllegalArgumentException("Poseidon hash was freed.");
+
+        nativeUpdate(input);
+    }
+
+    private native FieldElement nativeFinalize();
+
+    // Returns the hash of all the inputs received since the creation or the last reset,
+    // computed from scratch, without modifying the state.
+    public FieldElement finalizeHash() {
+        if (poseidonHashPointer == 0)
+            throw new IllegalArgumentException("Poseidon hash was freed.");
+
+        return nativeFinalize();
+    }
+
+    private native void nativeReset();
+
+    public void reset() {
+        if (poseidonHashPointer == 0)
+            throw new IllegalArgumentException("Poseidon hash was freed.");
+
+        nativeReset();
+    }
+
+    private static native void nativeFreePoseidonHash(long poseidonHashPointer);
+
+    public void freePoseidonHash() {
+        if (poseidonHashPointer != 0) {
+            nativeFreePoseidonHash(this.poseidonHashPointer);
+            poseidonHashPointer = 0;
+        }
+    }
+
     private static native FieldElement nativeComputeHash(FieldElement[] fieldElement); // jni call to Rust impl
 
     public static FieldElement computeHash(FieldElement[] fieldElement) {return nativeComputeHash(fieldElement);}
+
+    private static native FieldElement[] nativeComputeHashBatch(FieldElement[][] inputs);
+
+    // Computes, in parallel, the hash of each one of the independent inputs, returning them in the same order.
+    public static FieldElement[] computeHashBatch(FieldElement[][] inputs) {
+        return nativeComputeHashBatch(inputs);
+    }
+
+    private static native FieldElement nativeHashBytes(byte[] input);
+
+    // Packs input into field elements and returns the Poseidon hash of its length followed by them.
+    public static FieldElement hashBytes(byte[] input) {
+        if (input == null)
+            throw new IllegalArgumentException("Input must be not null.");
+
+        return nativeHashBytes(input);
+    }
 }
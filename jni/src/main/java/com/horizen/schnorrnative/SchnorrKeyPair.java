@@ -3,6 +3,8 @@ package com.horizen.schnorrnative;
 import com.horizen.librustsidechains.FieldElement;
 import com.horizen.librustsidechains.Library;
 
+import java.util.List;
+
 public class SchnorrKeyPair {
     private SchnorrSecretKey secretKey;
     private SchnorrPublicKey publicKey;
@@ -27,10 +29,58 @@ public class SchnorrKeyPair {
         return nativeGenerate();
     }
 
-    private native SchnorrSignature nativeSignMessage(FieldElement message);
+    private native SchnorrSignature nativeSignMessage(FieldElement message, boolean deterministic, byte[] extraRandomness);
 
+    // Signs the message with a nonce sampled from the OS random number generator.
     public SchnorrSignature signMessage(FieldElement message) {
-        return nativeSignMessage(message);
+        return nativeSignMessage(message, false, null);
+    }
+
+    // If deterministic, signs the message with a nonce derived from the secret key and the message,
+    // so that the same message always gets the same signature; otherwise behaves like signMessage(message).
+    public SchnorrSignature signMessage(FieldElement message, boolean deterministic) {
+        return nativeSignMessage(message, deterministic, null);
+    }
+
+    // Signs the message with a nonce derived from the secret key, the message and extraRandomness.
+    public SchnorrSignature signMessage(FieldElement message, byte[] extraRandomness) {
+        if (extraRandomness == null)
+            throw new IllegalArgumentException("Extra randomness must not be null");
+        return nativeSignMessage(message, true, extraRandomness);
+    }
+
+    private native SchnorrSignature nativeSignMessageFieldElements(FieldElement[] message, boolean deterministic,
+                                                                   byte[] extraRandomness);
+
+    // Signs a message made of multiple field elements, without pre-hashing it. The message must not be empty.
+    // Returns null if the message starts with the personalization of a dedicated signing domain, e.g. proofs of possession.
+    public SchnorrSignature signMessage(List<FieldElement> message) {
+        return signMessage(message, false);
+    }
+
+    public SchnorrSignature signMessage(List<FieldElement> message, boolean deterministic) {
+        if (message.isEmpty())
+            throw new IllegalArgumentException("Message to sign must not be empty");
+        return nativeSignMessageFieldElements(message.toArray(new FieldElement[0]), deterministic, null);
+    }
+
+    private native SchnorrSignature nativeSignMessageBytes(byte[] message, boolean deterministic, byte[] extraRandomness);
+
+    // Signs a byte message, canonically encoded as its length followed by its bytes packed into field elements,
+    // in its own domain: the signature is not valid for any field element message.
+    public SchnorrSignature signMessageBytes(byte[] message) {
+        return signMessageBytes(message, false);
+    }
+
+    public SchnorrSignature signMessageBytes(byte[] message, boolean deterministic) {
+        return nativeSignMessageBytes(message, deterministic, null);
+    }
+
+    private native SchnorrSignature nativeCreateProofOfPossession();
+
+    // Signs the public key itself, proving the possession of the secret key when registering the public key.
+    public SchnorrSignature createProofOfPossession() {
+        return nativeCreateProofOfPossession();
     }
 
     public SchnorrSecretKey getSecretKey() {
@@ -0,0 +1,78 @@
+package com.horizen.schnorrnative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+import java.util.List;
+
+// Secret nonce of a signer of a Schnorr multi-signature. A fresh nonce must be generated for each
+// multi-signature: it's consumed by partialSign.
+public class SchnorrMultiSigNonce
+{
+  private long noncePointer;
+
+  static {
+    Library.load();
+  }
+
+  private SchnorrMultiSigNonce(long noncePointer) {
+    if (noncePointer == 0)
+      throw new IllegalArgumentException("Nonce pointer must be not null.");
+    this.noncePointer = noncePointer;
+  }
+
+  private static native SchnorrMultiSigNonce nativeGenerate();
+
+  // Round 1: generates the nonce, whose commitment is sent to the other signers.
+  public static SchnorrMultiSigNonce generate() {
+    return nativeGenerate();
+  }
+
+  private native FieldElement nativeGetCommitment();
+
+  public FieldElement getCommitment() {
+    if (noncePointer == 0)
+      throw new IllegalArgumentException("Nonce was freed.");
+
+    return nativeGetCommitment();
+  }
+
+  private native SchnorrMultiSigPublicNonce nativeGetPublicNonce();
+
+  // Round 2: the public nonce is sent to the other signers once all the commitments have been received.
+  public SchnorrMultiSigPublicNonce getPublicNonce() {
+    if (noncePointer == 0)
+      throw new IllegalArgumentException("Nonce was freed.");
+
+    return nativeGetPublicNonce();
+  }
+
+  private native SchnorrMultiSigPartialSignature nativePartialSign(SchnorrSecretKey secretKey, SchnorrPublicKey[] signersPublicKeys,
+                                                                   int signerIndex, SchnorrMultiSigPublicNonce aggregatedNonce,
+                                                                   FieldElement message);
+
+  // Signs message as the signer at signerIndex in signersPublicKeys. The nonce is consumed, even on
+  // failure. Returns null if secretKey doesn't match the public key of the signer.
+  public SchnorrMultiSigPartialSignature partialSign(SchnorrSecretKey secretKey, List<SchnorrPublicKey> signersPublicKeys,
+                                                     int signerIndex, SchnorrMultiSigPublicNonce aggregatedNonce,
+                                                     FieldElement message) {
+    if (noncePointer == 0)
+      throw new IllegalArgumentException("Nonce was freed.");
+
+    try {
+      return nativePartialSign(secretKey, signersPublicKeys.toArray(new SchnorrPublicKey[0]), signerIndex,
+              aggregatedNonce, message);
+    } finally {
+      noncePointer = 0;
+    }
+  }
+
+  private static native void nativeFreeNonce(long noncePointer);
+
+  public void freeNonce() {
+    if (noncePointer != 0) {
+      nativeFreeNonce(this.noncePointer);
+      noncePointer = 0;
+    }
+  }
+}
@@ -0,0 +1,64 @@
+package com.horizen.schnorrnative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+import java.util.List;
+
+public class SchnorrMultiSigPartialSignature
+{
+  public static final int PARTIAL_SIGNATURE_LENGTH = 96;
+
+  private long partialSignaturePointer;
+
+  static {
+    Library.load();
+  }
+
+  private SchnorrMultiSigPartialSignature(long partialSignaturePointer) {
+    if (partialSignaturePointer == 0)
+      throw new IllegalArgumentException("Partial signature pointer must be not null.");
+    this.partialSignaturePointer = partialSignaturePointer;
+  }
+
+  private static native byte[] nativeSerializePartialSignature(long partialSignaturePointer);
+
+  private static native SchnorrMultiSigPartialSignature nativeDeserializePartialSignature(byte[] partialSignatureBytes);
+
+  private static native void nativeFreePartialSignature(long partialSignaturePointer);
+
+  // Throws DeserializationException if the bytes don't encode a scalar.
+  public static SchnorrMultiSigPartialSignature deserialize(byte[] partialSignatureBytes) {
+    if (partialSignatureBytes.length != PARTIAL_SIGNATURE_LENGTH)
+      throw new IllegalArgumentException(String.format("Incorrect partial signature length, %d expected, %d found", PARTIAL_SIGNATURE_LENGTH, partialSignatureBytes.length));
+
+    return nativeDeserializePartialSignature(partialSignatureBytes);
+  }
+
+  public byte[] serializePartialSignature() {
+    if (partialSignaturePointer == 0)
+      throw new IllegalArgumentException("Partial signature was freed.");
+
+    return nativeSerializePartialSignature(this.partialSignaturePointer);
+  }
+
+  private native boolean nativeVerify(SchnorrMultiSigPublicNonce publicNonce, SchnorrPublicKey[] signersPublicKeys,
+                                      int signerIndex, SchnorrMultiSigPublicNonce aggregatedNonce, FieldElement message);
+
+  // Checks the partial signature of the signer at signerIndex, whose public nonce is publicNonce,
+  // in order to find out which signer made the multi-signature invalid.
+  public boolean verify(SchnorrMultiSigPublicNonce publicNonce, List<SchnorrPublicKey> signersPublicKeys,
+                        int signerIndex, SchnorrMultiSigPublicNonce aggregatedNonce, FieldElement message) {
+    if (partialSignaturePointer == 0)
+      throw new IllegalArgumentException("Partial signature was freed.");
+
+    return nativeVerify(publicNonce, signersPublicKeys.toArray(new SchnorrPublicKey[0]), signerIndex, aggregatedNonce, message);
+  }
+
+  public void freePartialSignature() {
+    if (partialSignaturePointer != 0) {
+      nativeFreePartialSignature(this.partialSignaturePointer);
+      partialSignaturePointer = 0;
+    }
+  }
+}
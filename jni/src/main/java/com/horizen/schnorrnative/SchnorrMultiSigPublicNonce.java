@@ -0,0 +1,63 @@
+package com.horizen.schnorrnative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+import java.util.List;
+
+public class SchnorrMultiSigPublicNonce
+{
+  public static final int PUBLIC_NONCE_LENGTH = 193;
+
+  private long publicNoncePointer;
+
+  static {
+    Library.load();
+  }
+
+  private SchnorrMultiSigPublicNonce(long publicNoncePointer) {
+    if (publicNoncePointer == 0)
+      throw new IllegalArgumentException("Public nonce pointer must be not null.");
+    this.publicNoncePointer = publicNoncePointer;
+  }
+
+  private static native byte[] nativeSerializePublicNonce(long publicNoncePointer);
+
+  private static native SchnorrMultiSigPublicNonce nativeDeserializePublicNonce(byte[] publicNonceBytes);
+
+  private static native void nativeFreePublicNonce(long publicNoncePointer);
+
+  // Throws DeserializationException unless the bytes encode a non zero point in the prime order subgroup.
+  public static SchnorrMultiSigPublicNonce deserialize(byte[] publicNonceBytes) {
+    if (publicNonceBytes.length != PUBLIC_NONCE_LENGTH)
+      throw new IllegalArgumentException(String.format("Incorrect public nonce length, %d expected, %d found", PUBLIC_NONCE_LENGTH, publicNonceBytes.length));
+
+    return nativeDeserializePublicNonce(publicNonceBytes);
+  }
+
+  public byte[] serializePublicNonce() {
+    if (publicNoncePointer == 0)
+      throw new IllegalArgumentException("Public nonce was freed.");
+
+    return nativeSerializePublicNonce(this.publicNoncePointer);
+  }
+
+  private static native SchnorrMultiSigPublicNonce nativeAggregate(SchnorrMultiSigPublicNonce[] publicNonces, FieldElement[] commitments);
+
+  // Round 2: checks the public nonce of each signer against the commitment it sent in round 1, and
+  // returns the aggregated nonce, or null if any of the nonces doesn't match its commitment.
+  public static SchnorrMultiSigPublicNonce aggregate(List<SchnorrMultiSigPublicNonce> publicNonces, List<FieldElement> commitments) {
+    if (publicNonces.size() != commitments.size())
+      throw new IllegalArgumentException(String.format("Expected a commitment for each one of the %d public nonces, found %d",
+              publicNonces.size(), commitments.size()));
+
+    return nativeAggregate(publicNonces.toArray(new SchnorrMultiSigPublicNonce[0]), commitments.toArray(new FieldElement[0]));
+  }
+
+  public void freePublicNonce() {
+    if (publicNoncePointer != 0) {
+      nativeFreePublicNonce(this.publicNoncePointer);
+      publicNoncePointer = 0;
+    }
+  }
+}
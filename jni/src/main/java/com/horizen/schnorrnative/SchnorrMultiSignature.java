@@ -0,0 +1,80 @@
+package com.horizen.schnorrnative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.librustsidechains.Library;
+
+import java.util.List;
+
+// Schnorr multi-signature of a set of signers, verifiable against their aggregated public key.
+// It's created in two rounds, each signer using a fresh SchnorrMultiSigNonce:
+// 1. each signer generates a nonce and sends its commitment to the other ones;
+// 2. each signer sends its public nonce, and the nonces are aggregated once checked against the commitments;
+// then each signer sends its partial signature, and the partial signatures are combined.
+public class SchnorrMultiSignature
+{
+  public static final int SIGNATURE_LENGTH = 289;
+
+  private long signaturePointer;
+
+  static {
+    Library.load();
+  }
+
+  private SchnorrMultiSignature(long signaturePointer) {
+    if (signaturePointer == 0)
+      throw new IllegalArgumentException("Signature pointer must be not null.");
+    this.signaturePointer = signaturePointer;
+  }
+
+  private static native byte[] nativeSerializeSignature(long signaturePointer);
+
+  private static native SchnorrMultiSignature nativeDeserializeSignature(byte[] signatureBytes);
+
+  private static native void nativeFreeSignature(long signaturePointer);
+
+  // Throws DeserializationException if the bytes don't encode a valid multi-signature.
+  public static SchnorrMultiSignature deserialize(byte[] signatureBytes) {
+    if (signatureBytes.length != SIGNATURE_LENGTH)
+      throw new IllegalArgumentException(String.format("Incorrect signature length, %d expected, %d found", SIGNATURE_LENGTH, signatureBytes.length));
+
+    return nativeDeserializeSignature(signatureBytes);
+  }
+
+  public byte[] serializeSignature() {
+    if (signaturePointer == 0)
+      throw new IllegalArgumentException("Signature was freed.");
+
+    return nativeSerializeSignature(this.signaturePointer);
+  }
+
+  private static native SchnorrPublicKey nativeAggregatePublicKeys(SchnorrPublicKey[] signersPublicKeys);
+
+  // The order of the signers matters: all of them must use the same one.
+  public static SchnorrPublicKey aggregatePublicKeys(List<SchnorrPublicKey> signersPublicKeys) {
+    return nativeAggregatePublicKeys(signersPublicKeys.toArray(new SchnorrPublicKey[0]));
+  }
+
+  private static native SchnorrMultiSignature nativeCombine(SchnorrMultiSigPublicNonce aggregatedNonce,
+                                                            SchnorrMultiSigPartialSignature[] partialSignatures);
+
+  public static SchnorrMultiSignature combine(SchnorrMultiSigPublicNonce aggregatedNonce,
+                                              List<SchnorrMultiSigPartialSignature> partialSignatures) {
+    return nativeCombine(aggregatedNonce, partialSignatures.toArray(new SchnorrMultiSigPartialSignature[0]));
+  }
+
+  private native boolean nativeVerify(SchnorrPublicKey aggregatedPublicKey, FieldElement message);
+
+  public boolean verify(SchnorrPublicKey aggregatedPublicKey, FieldElement message) {
+    if (signaturePointer == 0)
+      throw new IllegalArgumentException("Signature was freed.");
+
+    return nativeVerify(aggregatedPublicKey, message);
+  }
+
+  public void freeSignature() {
+    if (signaturePointer != 0) {
+      nativeFreeSignature(this.signaturePointer);
+      signaturePointer = 0;
+    }
+  }
+}
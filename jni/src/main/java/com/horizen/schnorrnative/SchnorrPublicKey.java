@@ -2,6 +2,8 @@ package com.horizen.schnorrnative;
 
 import com.horizen.librustsidechains.*;
 
+import java.util.List;
+
 public class SchnorrPublicKey
 {
 
@@ -23,6 +25,7 @@ public class SchnorrPublicKey
 
   private static native SchnorrPublicKey nativeDeserializePublicKey(byte[] publicKeyBytes);
 
+  // Throws DeserializationException unless the bytes encode a non zero point in the prime order subgroup.
   public static SchnorrPublicKey deserialize(byte[] publicKeyBytes) {
     if (publicKeyBytes.length != PUBLIC_KEY_LENGTH)
       throw new IllegalArgumentException(String.format("Incorrect public key length, %d expected, %d found", PUBLIC_KEY_LENGTH, publicKeyBytes.length));
@@ -39,6 +42,28 @@ public class SchnorrPublicKey
     return nativeSerializePublicKey();
   }
 
+  // Size of the compressed encoding: the x coordinate and a flag for the sign of y.
+  public static final int PUBLIC_KEY_COMPRESSED_LENGTH = 96;
+
+  private static native SchnorrPublicKey nativeDeserializePublicKeyCompressed(byte[] publicKeyBytes);
+
+  // Throws DeserializationException unless the bytes encode a non zero point in the prime order subgroup.
+  public static SchnorrPublicKey deserializeCompressed(byte[] publicKeyBytes) {
+    if (publicKeyBytes.length != PUBLIC_KEY_COMPRESSED_LENGTH)
+      throw new IllegalArgumentException(String.format("Incorrect compressed public key length, %d expected, %d found", PUBLIC_KEY_COMPRESSED_LENGTH, publicKeyBytes.length));
+
+    return nativeDeserializePublicKeyCompressed(publicKeyBytes);
+  }
+
+  private native byte[] nativeSerializePublicKeyCompressed();
+
+  public byte[] serializePublicKeyCompressed() {
+    if (publicKeyPointer == 0)
+      throw new IllegalArgumentException("Public key was freed.");
+
+    return nativeSerializePublicKeyCompressed();
+  }
+
   private native void nativeFreePublicKey();
 
   public void freePublicKey() {
@@ -59,6 +84,77 @@ public class SchnorrPublicKey
     return nativeVerifySignature(signature, message);
   }
 
+  private native boolean nativeVerifySignatureFieldElements(SchnorrSignature signature, FieldElement[] message);
+
+  // Verifies a signature on a message made of multiple field elements.
+  public boolean verifySignature(SchnorrSignature signature, List<FieldElement> message) {
+    if (publicKeyPointer == 0)
+      throw new IllegalArgumentException("Public key was freed.");
+
+    return nativeVerifySignatureFieldElements(signature, message.toArray(new FieldElement[0]));
+  }
+
+  private native boolean nativeVerifySignatureBytes(SchnorrSignature signature, byte[] message);
+
+  // Verifies a signature on a byte message, created by SchnorrKeyPair.signMessageBytes.
+  public boolean verifySignatureBytes(SchnorrSignature signature, byte[] message) {
+    if (publicKeyPointer == 0)
+      throw new IllegalArgumentException("Public key was freed.");
+
+    return nativeVerifySignatureBytes(signature, message);
+  }
+
+  private static native boolean nativeVerifyBatch(SchnorrPublicKey[] publicKeys, FieldElement[] messages,
+                                                  SchnorrSignature[] signatures);
+
+  private static native byte[] nativeVerifyBatchPerItem(SchnorrPublicKey[] publicKeys, FieldElement[] messages,
+                                                        SchnorrSignature[] signatures);
+
+  private static void checkBatch(List<SchnorrPublicKey> publicKeys, List<FieldElement> messages,
+                                 List<SchnorrSignature> signatures) {
+    if (publicKeys.size() != messages.size() || publicKeys.size() != signatures.size())
+      throw new IllegalArgumentException(String.format("Batch sizes must be equal: %d public keys, %d messages, %d signatures",
+              publicKeys.size(), messages.size(), signatures.size()));
+  }
+
+  // Verifies in parallel the i-th signature on the i-th message against the i-th public key, one by one:
+  // this is not a randomized batch verification. Returns true iff all the signatures are valid.
+  public static boolean verifyBatch(List<SchnorrPublicKey> publicKeys, List<FieldElement> messages,
+                                    List<SchnorrSignature> signatures) {
+    checkBatch(publicKeys, messages, signatures);
+
+    return nativeVerifyBatch(publicKeys.toArray(new SchnorrPublicKey[0]), messages.toArray(new FieldElement[0]),
+            signatures.toArray(new SchnorrSignature[0]));
+  }
+
+  // Like verifyBatch, but returns the verdict of each signature, or null if the verification failed.
+  public static boolean[] verifyBatchPerItem(List<SchnorrPublicKey> publicKeys, List<FieldElement> messages,
+                                             List<SchnorrSignature> signatures) {
+    checkBatch(publicKeys, messages, signatures);
+
+    byte[] verdictBytes = nativeVerifyBatchPerItem(publicKeys.toArray(new SchnorrPublicKey[0]),
+            messages.toArray(new FieldElement[0]), signatures.toArray(new SchnorrSignature[0]));
+
+    if (verdictBytes == null)
+      return null;
+
+    boolean[] verdicts = new boolean[verdictBytes.length];
+    for (int i = 0; i < verdictBytes.length; i++)
+      verdicts[i] = verdictBytes[i] == 1;
+
+    return verdicts;
+  }
+
+  private native boolean nativeVerifyProofOfPossession(SchnorrSignature proofOfPossession);
+
+  // Verifies a proof of possession created by SchnorrKeyPair.createProofOfPossession.
+  public boolean verifyProofOfPossession(SchnorrSignature proofOfPossession) {
+    if (publicKeyPointer == 0)
+      throw new IllegalArgumentException("Public key was freed.");
+
+    return nativeVerifyProofOfPossession(proofOfPossession);
+  }
+
   public boolean verifyKey() {
     if (publicKeyPointer == 0)
       throw new IllegalArgumentException("Public key was freed.");
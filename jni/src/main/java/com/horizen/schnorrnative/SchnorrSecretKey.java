@@ -24,6 +24,7 @@ public class SchnorrSecretKey
 
     private static native SchnorrSecretKey nativeDeserializeSecretKey(byte[] secretKeyBytes);
 
+    // Throws DeserializationException if the bytes don't encode a non zero scalar.
     public static SchnorrSecretKey deserialize(byte[] secretKeyBytes) {
         if (secretKeyBytes.length != SECRET_KEY_LENGTH)
             throw new IllegalArgumentException(String.format("Incorrect secret key length, %d expected, %d found", SECRET_KEY_LENGTH, secretKeyBytes.length));
@@ -31,6 +32,22 @@ public class SchnorrSecretKey
         return nativeDeserializeSecretKey(secretKeyBytes);
     }
 
+    // Seed length bounds, in bytes, of the hierarchical deterministic key derivation.
+    public static final int SEED_MIN_LENGTH = 16;
+    public static final int SEED_MAX_LENGTH = 64;
+
+    private static native SchnorrSecretKey nativeDeriveFromSeed(byte[] seed, String path);
+
+    // Derives the secret key at path, e.g. "m/0'/1", from seed: ' (or h) marks hardened indices.
+    // Returns null if the derived key is invalid; the next index should be used in that case.
+    public static SchnorrSecretKey deriveFromSeed(byte[] seed, String path) {
+        if (seed.length < SEED_MIN_LENGTH || seed.length > SEED_MAX_LENGTH)
+            throw new IllegalArgumentException(String.format("Incorrect seed length, between %d and %d expected, %d found",
+                    SEED_MIN_LENGTH, SEED_MAX_LENGTH, seed.length));
+
+        return nativeDeriveFromSeed(seed, path);
+    }
+
     private native byte[] nativeSerializeSecretKey();
 
     public byte[] serializeSecretKey() {
@@ -40,6 +57,30 @@ public class SchnorrSecretKey
         return nativeSerializeSecretKey();
     }
 
+    // Size, in bytes, of the password-encrypted keystore of a secret key.
+    public static final int KEYSTORE_LENGTH = 171;
+
+    private native byte[] nativeEncrypt(byte[] password);
+
+    // Encrypts the secret key with a key derived from password, returning a keystore suitable to be stored.
+    public byte[] encrypt(byte[] password) {
+        if (secretKeyPointer == 0)
+            throw new IllegalArgumentException("Secret key was freed.");
+
+        return nativeEncrypt(password);
+    }
+
+    private static native SchnorrSecretKey nativeDecrypt(byte[] keystore, byte[] password);
+
+    // Decrypts a keystore created by encrypt. Returns null if the keystore is corrupted, holds another
+    // type of key, or the password is wrong.
+    public static SchnorrSecretKey decrypt(byte[] keystore, byte[] password) {
+        if (keystore.length != KEYSTORE_LENGTH)
+            throw new IllegalArgumentException(String.format("Incorrect keystore length, %d expected, %d found", KEYSTORE_LENGTH, keystore.length));
+
+        return nativeDecrypt(keystore, password);
+    }
+
     private native void nativeFreeSecretKey();
 
     public void freeSecretKey() {
@@ -29,6 +29,7 @@ public class SchnorrSignature
 
   private static native void nativefreeSignature(long signaturePointer);
 
+  // Throws DeserializationException if the bytes don't canonically encode a signature.
   public static SchnorrSignature deserialize(byte[] signatureBytes) {
     if (signatureBytes.length != SIGNATURE_LENGTH)
       throw new IllegalArgumentException(String.format("Incorrect signature length, %d expected, %d found", SIGNATURE_LENGTH, signatureBytes.length));
@@ -8,44 +8,118 @@ import java.util.List;
 
 public class NaiveThresholdSigProof {
 
-    private static native FieldElement nativeGetConstant(SchnorrPublicKey[] schnorrPublicKeys, long threshold);
+    // Versions of the domain separation applied to the hashes computed by the protocol.
+    // Constants, messages and proofs must be computed and verified with the same version.
+    public static final int LEGACY_DOMAIN_SEPARATION_VERSION = 0;
+    public static final int CURRENT_DOMAIN_SEPARATION_VERSION = 1;
+
+    public static final int PROOF_LENGTH = 771;
+    public static final int PROOF_COMPRESSED_LENGTH = 384;
+
+    private static native byte[] nativeCompressProof(byte[] proof);
+
+    // Converts a proof, as returned by createProof, to its compressed encoding. Returns null if the proof is invalid.
+    public static byte[] compressProof(byte[] proof) {
+        if (proof.length != PROOF_LENGTH)
+            throw new IllegalArgumentException(String.format("Incorrect proof length, %d expected, %d found", PROOF_LENGTH, proof.length));
+
+        return nativeCompressProof(proof);
+    }
+
+    private static native byte[] nativeDecompressProof(byte[] compressedProof);
+
+    // Converts a compressed proof back to the encoding accepted by verifyProof. Returns null if the points
+    // of the proof are not on the curve or not in the prime order subgroups.
+    public static byte[] decompressProof(byte[] compressedProof) {
+        if (compressedProof.length != PROOF_COMPRESSED_LENGTH)
+            throw new IllegalArgumentException(String.format("Incorrect compressed proof length, %d expected, %d found", PROOF_COMPRESSED_LENGTH, compressedProof.length));
+
+        return nativeDecompressProof(compressedProof);
+    }
+
+    private static native FieldElement nativeGetConstant(SchnorrPublicKey[] schnorrPublicKeys, long threshold, int version,
+                                                         SchnorrSignature[] proofsOfPossession);
+
+    public static FieldElement getConstant(List<SchnorrPublicKey> schnorrPublicKeys, long threshold, int version) {
+        return nativeGetConstant(schnorrPublicKeys.toArray(new SchnorrPublicKey[0]), threshold, version, null);
+    }
+
+    // Like getConstant, but returns null unless the i-th proof of possession is valid for the i-th public key.
+    public static FieldElement getConstant(List<SchnorrPublicKey> schnorrPublicKeys, List<SchnorrSignature> proofsOfPossession,
+                                           long threshold, int version) {
+        if (schnorrPublicKeys.size() != proofsOfPossession.size())
+            throw new IllegalArgumentException(String.format("Expected a proof of possession for each one of the %d public keys, found %d",
+                    schnorrPublicKeys.size(), proofsOfPossession.size()));
+
+        return nativeGetConstant(schnorrPublicKeys.toArray(new SchnorrPublicKey[0]), threshold, version,
+                proofsOfPossession.toArray(new SchnorrSignature[0]));
+    }
+
+    public static FieldElement getConstant(List<SchnorrPublicKey> schnorrPublicKeys, List<SchnorrSignature> proofsOfPossession,
+                                           long threshold) {
+        return getConstant(schnorrPublicKeys, proofsOfPossession, threshold, CURRENT_DOMAIN_SEPARATION_VERSION);
+    }
 
     public static FieldElement getConstant(List<SchnorrPublicKey> schnorrPublicKeys, long threshold) {
-        return nativeGetConstant(schnorrPublicKeys.toArray(new SchnorrPublicKey[0]), threshold);
+        return getConstant(schnorrPublicKeys, threshold, CURRENT_DOMAIN_SEPARATION_VERSION);
     }
 
     private static native FieldElement nativeCreateMsgToSign(BackwardTransfer[] bt,
-                                                             byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash);
+                                                             byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
+                                                             int version);
+
+    public static FieldElement createMsgToSign(BackwardTransfer[] bt,
+                                               byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
+                                               int version) {
+        return nativeCreateMsgToSign(bt, endEpochBlockHash, prevEndEpochBlockHash, version);
+    }
 
     public static FieldElement createMsgToSign(BackwardTransfer[] bt,
                                                byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash) {
-        return nativeCreateMsgToSign(bt, endEpochBlockHash, prevEndEpochBlockHash);
+        return createMsgToSign(bt, endEpochBlockHash, prevEndEpochBlockHash, CURRENT_DOMAIN_SEPARATION_VERSION);
     }
 
     private static native CreateProofResult nativeCreateProof(BackwardTransfer[] bt,
                                                    byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
                                                    SchnorrSignature[] schnorrSignatures, SchnorrPublicKey[] schnorrPublicKeys,
-                                                   long threshold, String provingKeyPath);
+                                                   long threshold, String provingKeyPath, int version);
 
     public static CreateProofResult createProof(List<BackwardTransfer> btList,
                                      byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
                                      List<SchnorrSignature> schnorrSignatureList, List<SchnorrPublicKey> schnorrPublicKeyList,
-                                     long threshold, String provingKeyPath) {
+                                     long threshold, String provingKeyPath, int version) {
         return nativeCreateProof(btList.toArray(new BackwardTransfer[0]), endEpochBlockHash, prevEndEpochBlockHash,
                 schnorrSignatureList.toArray(new SchnorrSignature[0]), schnorrPublicKeyList.toArray(new SchnorrPublicKey[0]),
-                threshold, provingKeyPath);
+                threshold, provingKeyPath, version);
+    }
+
+    public static CreateProofResult createProof(List<BackwardTransfer> btList,
+                                     byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
+                                     List<SchnorrSignature> schnorrSignatureList, List<SchnorrPublicKey> schnorrPublicKeyList,
+                                     long threshold, String provingKeyPath) {
+        return createProof(btList, endEpochBlockHash, prevEndEpochBlockHash, schnorrSignatureList, schnorrPublicKeyList,
+                threshold, provingKeyPath, CURRENT_DOMAIN_SEPARATION_VERSION);
     }
 
     private static native boolean nativeVerifyProof(BackwardTransfer[] btList,
                                       byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
-                                      FieldElement constant, long quality, byte[] proof, String verificationKeyPath);
+                                      FieldElement constant, long quality, byte[] proof, String verificationKeyPath,
+                                      int version);
 
     public static boolean verifyProof(List<BackwardTransfer> btList,
                                       byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
-                                      FieldElement constant, long quality, byte[] proof, String verificationKeyPath){
+                                      FieldElement constant, long quality, byte[] proof, String verificationKeyPath,
+                                      int version){
         return nativeVerifyProof(
                 btList.toArray(new BackwardTransfer[0]),
                 endEpochBlockHash, prevEndEpochBlockHash,
-                constant, quality, proof, verificationKeyPath);
+                constant, quality, proof, verificationKeyPath, version);
+    }
+
+    public static boolean verifyProof(List<BackwardTransfer> btList,
+                                      byte[] endEpochBlockHash, byte[] prevEndEpochBlockHash,
+                                      FieldElement constant, long quality, byte[] proof, String verificationKeyPath){
+        return verifyProof(btList, endEpochBlockHash, prevEndEpochBlockHash, constant, quality, proof,
+                verificationKeyPath, CURRENT_DOMAIN_SEPARATION_VERSION);
     }
 }
@@ -25,6 +25,7 @@ public class VRFProof
 
   private static native void nativefreeProof(long proofPointer);
 
+  // Throws DeserializationException if gamma is not a valid point, or the bytes aren't canonical.
   public static VRFProof deserialize(byte[] proofBytes) {
     if (proofBytes.length != PROOF_LENGTH)
       throw new IllegalArgumentException(String.format("Incorrect proof length, %d expected, %d found", PROOF_LENGTH, proofBytes.length));
@@ -36,6 +37,27 @@ public class VRFProof
     return nativeSerializeProof(this.proofPointer);
   }
 
+  public static int PROOF_COMPRESSED_LENGTH = 288;
+
+  private static native byte[] nativeSerializeProofCompressed(long proofPointer);
+
+  private static native VRFProof nativeDeserializeProofCompressed(byte[] proofBytes);
+
+  // Throws DeserializationException if the bytes don't encode a valid proof.
+  public static VRFProof deserializeCompressed(byte[] proofBytes) {
+    if (proofBytes.length != PROOF_COMPRESSED_LENGTH)
+      throw new IllegalArgumentException(String.format("Incorrect compressed proof length, %d expected, %d found", PROOF_COMPRESSED_LENGTH, proofBytes.length));
+
+    return nativeDeserializeProofCompressed(proofBytes);
+  }
+
+  public byte[] serializeProofCompressed() {
+    if (proofPointer == 0)
+      throw new IllegalArgumentException("Proof was freed.");
+
+    return nativeSerializeProofCompressed(this.proofPointer);
+  }
+
   public void freeProof() {
     if (proofPointer != 0) {
       nativefreeProof(this.proofPointer);
@@ -24,6 +24,7 @@ public class VRFPublicKey
 
   private static native VRFPublicKey nativeDeserializePublicKey(byte[] publicKeyBytes);
 
+  // Throws DeserializationException unless the bytes encode a non zero point in the prime order subgroup.
   public static VRFPublicKey deserialize(byte[] publicKeyBytes) {
     if (publicKeyBytes.length != PUBLIC_KEY_LENGTH)
       throw new IllegalArgumentException(String.format("Incorrect public key length, %d expected, %d found", PUBLIC_KEY_LENGTH, publicKeyBytes.length));
@@ -40,6 +41,28 @@ public class VRFPublicKey
     return nativeSerializePublicKey();
   }
 
+  // Size of the compressed encoding: the x coordinate and a flag for the sign of y.
+  public static final int PUBLIC_KEY_COMPRESSED_LENGTH = 96;
+
+  private static native VRFPublicKey nativeDeserializePublicKeyCompressed(byte[] publicKeyBytes);
+
+  // Throws DeserializationException unless the bytes encode a non zero point in the prime order subgroup.
+  public static VRFPublicKey deserializeCompressed(byte[] publicKeyBytes) {
+    if (publicKeyBytes.length != PUBLIC_KEY_COMPRESSED_LENGTH)
+      throw new IllegalArgumentException(String.format("Incorrect compressed public key length, %d expected, %d found", PUBLIC_KEY_COMPRESSED_LENGTH, publicKeyBytes.length));
+
+    return nativeDeserializePublicKeyCompressed(publicKeyBytes);
+  }
+
+  private native byte[] nativeSerializePublicKeyCompressed();
+
+  public byte[] serializePublicKeyCompressed() {
+    if (publicKeyPointer == 0)
+      throw new IllegalArgumentException("Public key was freed.");
+
+    return nativeSerializePublicKeyCompressed();
+  }
+
   private native void nativeFreePublicKey();
 
   public void freePublicKey() {
@@ -0,0 +1,92 @@
+package com.horizen.consensusnative;
+
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.vrfnative.VRFKeyPair;
+import org.junit.Test;
+
+import java.lang.reflect.Field;
+
+import static org.junit.Assert.*;
+
+public class ForgerEligibilityProofTest {
+
+    @Test
+    public void testProveCheck() throws Exception {
+
+        VRFKeyPair keyPair = VRFKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+        FieldElement epochRandomness = FieldElement.createRandom();
+        long totalStake = 1000000;
+
+        //With all the stake, a forger leads 1 slot out of 20 on average
+        ForgerEligibilityParameters params = null;
+        ForgerEligibilityProof proof = null;
+        for (int slot = 0; proof == null; slot++) {
+            params = new ForgerEligibilityParameters(epochRandomness, slot, totalStake, totalStake);
+            proof = ForgerEligibilityProof.prove(keyPair.getSecretKey(), keyPair.getPublicKey(), params);
+        }
+
+        byte[] proofBytes = proof.serializeProof();
+        assertEquals(ForgerEligibilityProof.PROOF_LENGTH, proofBytes.length);
+        ForgerEligibilityProof deserializedProof = ForgerEligibilityProof.deserialize(proofBytes);
+
+        ForgerEligibilityResult result = deserializedProof.check(keyPair.getPublicKey(), params);
+        assertNotNull("Forger eligibility must be verified", result);
+        assertNotEquals("Leader and nonce outputs must differ", result.getLeaderOutput(), result.getNonceOutput());
+
+        //Negative cases
+        ForgerEligibilityParameters wrongSlotParams = new ForgerEligibilityParameters(epochRandomness, params.getSlotNumber() + 1, totalStake, totalStake);
+        assertNull("Proof must not be verified for another slot", proof.check(keyPair.getPublicKey(), wrongSlotParams));
+
+        FieldElement wrongEpochRandomness = FieldElement.createRandom();
+        ForgerEligibilityParameters wrongRandomnessParams = new ForgerEligibilityParameters(wrongEpochRandomness, params.getSlotNumber(), totalStake, totalStake);
+        assertNull("Proof must not be verified for another epoch randomness", proof.check(keyPair.getPublicKey(), wrongRandomnessParams));
+
+        ForgerEligibilityParameters noStakeParams = new ForgerEligibilityParameters(epochRandomness, params.getSlotNumber(), 0, totalStake);
+        assertNull("Forgers without stake must never be eligible", proof.check(keyPair.getPublicKey(), noStakeParams));
+        assertNull("Forgers without stake must never be eligible",
+                ForgerEligibilityProof.prove(keyPair.getSecretKey(), keyPair.getPublicKey(), noStakeParams));
+
+        VRFKeyPair otherKeyPair = VRFKeyPair.generate();
+        assertNull("Proof must not be verified for another forger", proof.check(otherKeyPair.getPublicKey(), params));
+
+        try {
+            new ForgerEligibilityParameters(epochRandomness, 0, totalStake + 1, totalStake);
+            fail("Forger stake must not exceed the total forgers stake");
+        } catch (IllegalArgumentException e) {}
+
+        try {
+            new ForgerEligibilityParameters(epochRandomness, -1, 0, totalStake);
+            fail("Slot number must not be negative");
+        } catch (IllegalArgumentException e) {}
+
+        //Native errors are thrown, not returned as null: bypass the checks of the constructor
+        ForgerEligibilityParameters invalidStakeParams = new ForgerEligibilityParameters(epochRandomness, params.getSlotNumber(), totalStake, totalStake);
+        Field forgerStake = ForgerEligibilityParameters.class.getDeclaredField("forgerStake");
+        forgerStake.setAccessible(true);
+        forgerStake.setLong(invalidStakeParams, totalStake + 1);
+
+        try {
+            ForgerEligibilityProof.prove(keyPair.getSecretKey(), keyPair.getPublicKey(), invalidStakeParams);
+            fail("Proving with a forger stake exceeding the total forgers stake must fail");
+        } catch (IllegalStateException e) {}
+
+        try {
+            proof.check(keyPair.getPublicKey(), invalidStakeParams);
+            fail("Checking with a forger stake exceeding the total forgers stake must fail");
+        } catch (IllegalStateException e) {}
+
+        //Free memory
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+        otherKeyPair.getPublicKey().freePublicKey();
+        otherKeyPair.getSecretKey().freeSecretKey();
+        proof.freeProof();
+        deserializedProof.freeProof();
+        result.getLeaderOutput().freeFieldElement();
+        result.getNonceOutput().freeFieldElement();
+        epochRandomness.freeFieldElement();
+        wrongEpochRandomness.freeFieldElement();
+    }
+}
@@ -44,4 +44,18 @@ public class FieldElementTest {
             fieldElementDeserialized2.freeFieldElement();
         }
     }
-}
\ No newline at end of file
+
+    @Test
+    public void testInvalidDeserialization() {
+
+        byte[] nonReducedBytes = new byte[FieldElement.FIELD_ELEMENT_LENGTH];
+        Arrays.fill(nonReducedBytes, (byte) 0xff);
+
+        try {
+            FieldElement.deserialize(nonReducedBytes);
+            fail("Field elements exceeding the modulus must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+    }
+}
@@ -0,0 +1,108 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.DeserializationException;
+import com.horizen.librustsidechains.FieldElement;
+import org.junit.Test;
+
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.List;
+
+import static org.junit.Assert.*;
+
+public class MerkleMountainRangeTest {
+
+    @Test
+    public void testMerkleMountainRange() {
+
+        int leavesNum = 13;
+
+        MerkleMountainRange mmr = MerkleMountainRange.init();
+        assertNotNull("Merkle mountain range creation must not fail", mmr);
+
+        List<FieldElement> leaves = new ArrayList<>();
+        for (int i = 0; i < leavesNum; i++) {
+            FieldElement leaf = FieldElement.createRandom();
+            leaves.add(leaf);
+            assertTrue("Append must not fail", mmr.append(leaf));
+        }
+
+        assertEquals("Leaves count must be " + leavesNum, leavesNum, mmr.getLeavesCount());
+
+        // 13 = 8 + 4 + 1
+        FieldElement[] peaks = mmr.getPeaks();
+        assertEquals("Merkle mountain range must have 3 peaks", 3, peaks.length);
+
+        FieldElement root = mmr.root();
+        FieldElement wrongRoot = FieldElement.createRandom();
+        assertNotNull("Merkle mountain range root computation must not fail", root);
+
+        for (int i = 0; i < leavesNum; i++) {
+            MerkleMountainRangeProof proof = mmr.getProof(i);
+
+            assertNotNull("Merkle mountain range proof creation must not fail", proof);
+            assertTrue("Proof must be verified", proof.verify(leaves.get(i), root));
+            assertFalse("Proof must not be verified against a wrong root", proof.verify(leaves.get(i), wrongRoot));
+
+            //Serialize/deserialize proof
+            MerkleMountainRangeProof proofDeserialized = MerkleMountainRangeProof.deserialize(proof.serialize());
+            assertNotNull("Proof deserialization must not fail", proofDeserialized);
+            assertTrue("Deserialized proof must be verified", proofDeserialized.verify(leaves.get(i), root));
+
+            //Free memory
+            proof.freeMerkleMountainRangeProof();
+            proofDeserialized.freeMerkleMountainRangeProof();
+        }
+
+        assertNull("Proof for a leaf not yet appended must not be created", mmr.getProof(leavesNum));
+
+        //Negative cases: truncated proof, and non canonical sibling after leaves count, leaf index and siblings count
+        MerkleMountainRangeProof proof = mmr.getProof(0);
+        byte[] proofBytes = proof.serialize();
+        try {
+            MerkleMountainRangeProof.deserialize(Arrays.copyOf(proofBytes, proofBytes.length - 1));
+            fail("Truncated merkle mountain range proof must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.INVALID_SIZE, e.getReason());
+        }
+
+        Arrays.fill(proofBytes, 17, 17 + FieldElement.FIELD_ELEMENT_LENGTH, (byte) 0xff);
+        try {
+            MerkleMountainRangeProof.deserialize(proofBytes);
+            fail("Merkle mountain range proof with a sibling exceeding the modulus must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+        proof.freeMerkleMountainRangeProof();
+
+        //Serialize/deserialize range
+        MerkleMountainRange mmrDeserialized = MerkleMountainRange.deserialize(mmr.serialize());
+        assertNotNull("Merkle mountain range deserialization must not fail", mmrDeserialized);
+
+        FieldElement rootDeserialized = mmrDeserialized.root();
+        assertEquals("Roots must be equal", root, rootDeserialized);
+
+        //Negative case: non canonical leaf, after version and leaves count
+        byte[] mmrBytes = mmr.serialize();
+        Arrays.fill(mmrBytes, 9, 9 + FieldElement.FIELD_ELEMENT_LENGTH, (byte) 0xff);
+        try {
+            MerkleMountainRange.deserialize(mmrBytes);
+            fail("Merkle mountain range with a leaf exceeding the modulus must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+
+        //Free memory
+        mmr.freeMerkleMountainRange();
+        mmrDeserialized.freeMerkleMountainRange();
+        root.freeFieldElement();
+        rootDeserialized.freeFieldElement();
+        wrongRoot.freeFieldElement();
+
+        for (FieldElement peak: peaks)
+            peak.freeFieldElement();
+
+        for (FieldElement leaf: leaves)
+            leaf.freeFieldElement();
+    }
+}
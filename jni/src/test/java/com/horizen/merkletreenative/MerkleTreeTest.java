@@ -0,0 +1,140 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.DeserializationException;
+import com.horizen.librustsidechains.FieldElement;
+import org.junit.Test;
+
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.List;
+
+import static org.junit.Assert.*;
+
+public class MerkleTreeTest {
+
+    @Test
+    public void testMerkleTree() {
+
+        int leavesNum = 16;
+
+        List<FieldElement> leaves = new ArrayList<>();
+        for (int i = 0; i < leavesNum; i++)
+            leaves.add(FieldElement.createRandom());
+
+        MerkleTree mt = MerkleTree.init(leaves);
+
+        assertNotNull("Merkle tree creation must not fail", mt);
+
+        FieldElement root = mt.root();
+        FieldElement wrongRoot = FieldElement.createRandom();
+
+        assertNotNull("Merkle root computation must not fail", root);
+
+        for (int i = 0; i < leavesNum; i++) {
+            MerklePath path = mt.getMerklePath(leaves.get(i), i);
+
+            assertNotNull("Merkle path creation must not fail", path);
+            assertTrue("Merkle path must be verified", path.verify(leaves.get(i), root));
+            assertFalse("Merkle path must not be verified against a wrong root", path.verify(leaves.get(i), wrongRoot));
+
+            //Serialize/deserialize path
+            byte[] pathBytes = path.serialize();
+
+            assertEquals("Merkle path size must be - " + MerklePath.MERKLE_PATH_LENGTH,
+                    MerklePath.MERKLE_PATH_LENGTH, pathBytes.length);
+
+            MerklePath pathDeserialized = MerklePath.deserialize(pathBytes);
+
+            assertNotNull("Merkle path deserialization must not fail", pathDeserialized);
+            assertTrue("Deserialized merkle path must be verified", pathDeserialized.verify(leaves.get(i), root));
+
+            //Negative case: non canonical sibling
+            Arrays.fill(pathBytes, 0, FieldElement.FIELD_ELEMENT_LENGTH, (byte) 0xff);
+            try {
+                MerklePath.deserialize(pathBytes);
+                fail("Merkle path with a sibling exceeding the modulus must be rejected");
+            } catch (DeserializationException e) {
+                assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+            }
+
+            //Free memory
+            path.freeMerklePath();
+            pathDeserialized.freeMerklePath();
+        }
+
+        //Free memory
+        mt.freeMerkleTree();
+        root.freeFieldElement();
+        wrongRoot.freeFieldElement();
+
+        for (FieldElement leaf: leaves)
+            leaf.freeFieldElement();
+    }
+
+    @Test
+    public void testMerkleMultiPath() {
+
+        int leavesNum = 16;
+
+        List<FieldElement> leaves = new ArrayList<>();
+        for (int i = 0; i < leavesNum; i++)
+            leaves.add(FieldElement.createRandom());
+
+        MerkleTree mt = MerkleTree.init(leaves);
+
+        assertNotNull("Merkle tree creation must not fail", mt);
+
+        FieldElement root = mt.root();
+        FieldElement wrongRoot = FieldElement.createRandom();
+
+        List<Long> leafIndices = Arrays.asList(1L, 2L, 5L, 15L);
+        List<FieldElement> openedLeaves = new ArrayList<>();
+        for (Long leafIndex: leafIndices)
+            openedLeaves.add(leaves.get(leafIndex.intValue()));
+
+        MerkleMultiPath multiPath = mt.getMerkleMultiPath(openedLeaves, leafIndices);
+
+        assertNotNull("Merkle multi path creation must not fail", multiPath);
+        assertEquals("Merkle multi path must open all the leaves", leafIndices.size(), multiPath.getLeavesCount());
+        assertTrue("Merkle multi path must be verified", multiPath.verify(openedLeaves, root));
+        assertFalse("Merkle multi path must not be verified against a wrong root", multiPath.verify(openedLeaves, wrongRoot));
+
+        //Serialize/deserialize multi path
+        byte[] multiPathBytes = multiPath.serialize();
+        MerkleMultiPath multiPathDeserialized = MerkleMultiPath.deserialize(multiPathBytes);
+
+        assertNotNull("Merkle multi path deserialization must not fail", multiPathDeserialized);
+        assertTrue("Deserialized merkle multi path must be verified", multiPathDeserialized.verify(openedLeaves, root));
+
+        //Negative case: truncated multi path
+        try {
+            MerkleMultiPath.deserialize(Arrays.copyOf(multiPathBytes, multiPathBytes.length - 1));
+            fail("Truncated merkle multi path deserialization must fail");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.INVALID_SIZE, e.getReason());
+        }
+
+        //Negative case: non canonical sibling
+        Arrays.fill(multiPathBytes, multiPathBytes.length - FieldElement.FIELD_ELEMENT_LENGTH, multiPathBytes.length, (byte) 0xff);
+        try {
+            MerkleMultiPath.deserialize(multiPathBytes);
+            fail("Merkle multi path with a sibling exceeding the modulus must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+
+        //Negative case: unsorted leaf indices
+        assertNull("Merkle multi path creation must fail with unsorted leaf indices",
+                mt.getMerkleMultiPath(Arrays.asList(leaves.get(2), leaves.get(1)), Arrays.asList(2L, 1L)));
+
+        //Free memory
+        multiPath.freeMerkleMultiPath();
+        multiPathDeserialized.freeMerkleMultiPath();
+        mt.freeMerkleTree();
+        root.freeFieldElement();
+        wrongRoot.freeFieldElement();
+
+        for (FieldElement leaf: leaves)
+            leaf.freeFieldElement();
+    }
+}
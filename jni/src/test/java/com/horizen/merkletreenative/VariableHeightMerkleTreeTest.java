@@ -0,0 +1,109 @@
+package com.horizen.merkletreenative;
+
+import com.horizen.librustsidechains.DeserializationException;
+import com.horizen.librustsidechains.FieldElement;
+import org.junit.Test;
+
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.List;
+
+import static org.junit.Assert.*;
+
+public class VariableHeightMerkleTreeTest {
+
+    @Test
+    public void testVariableHeightMerkleTree() {
+
+        int leavesNum = 20;
+
+        List<FieldElement> leaves = new ArrayList<>();
+        for (int i = 0; i < leavesNum; i++)
+            leaves.add(FieldElement.createRandom());
+
+        assertNull("Tree creation must fail if leaves don't fit", VariableHeightMerkleTree.init(leaves, 4));
+
+        VariableHeightMerkleTree mt10 = VariableHeightMerkleTree.init(leaves, 10);
+        VariableHeightMerkleTree mt20 = VariableHeightMerkleTree.init(leaves, 20);
+
+        assertNotNull("Merkle tree creation must not fail", mt10);
+        assertNotNull("Merkle tree creation must not fail", mt20);
+
+        VariableHeightMerkleRoot root10 = mt10.root();
+        VariableHeightMerkleRoot root20 = mt20.root();
+
+        assertEquals("Root height must be 10", 10, root10.getHeight());
+        assertEquals("Root height must be 20", 20, root20.getHeight());
+
+        //Serialize/deserialize root
+        byte[] rootBytes = root10.serialize();
+        assertEquals("Merkle root size must be - " + VariableHeightMerkleRoot.MERKLE_ROOT_LENGTH,
+                VariableHeightMerkleRoot.MERKLE_ROOT_LENGTH, rootBytes.length);
+
+        VariableHeightMerkleRoot root10Deserialized = VariableHeightMerkleRoot.deserialize(rootBytes);
+        assertNotNull("Merkle root deserialization must not fail", root10Deserialized);
+
+        //Negative case: non canonical root value
+        byte[] nonCanonicalRootBytes = Arrays.copyOf(rootBytes, rootBytes.length);
+        Arrays.fill(nonCanonicalRootBytes, 1, 1 + FieldElement.FIELD_ELEMENT_LENGTH, (byte) 0xff);
+        try {
+            VariableHeightMerkleRoot.deserialize(nonCanonicalRootBytes);
+            fail("Merkle root with a value exceeding the modulus must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+
+        FieldElement root10Value = root10.getValue();
+        FieldElement root10DeserializedValue = root10Deserialized.getValue();
+        assertEquals("Merkle root values must be equal", root10Value, root10DeserializedValue);
+
+        for (int i = 0; i < leavesNum; i++) {
+            VariableHeightMerklePath path10 = mt10.getMerklePath(i);
+            VariableHeightMerklePath path20 = mt20.getMerklePath(i);
+
+            assertTrue("Merkle path must be verified", path10.verify(leaves.get(i), root10Deserialized));
+            assertTrue("Merkle path must be verified", path20.verify(leaves.get(i), root20));
+
+            try {
+                path10.verify(leaves.get(i), root20);
+                fail("Merkle path must not be checked against a root of a different height");
+            } catch (IllegalArgumentException e) {
+                //Expected
+            }
+
+            //Serialize/deserialize path
+            byte[] pathBytes = path20.serialize();
+            assertEquals("Merkle path size is wrong", VariableHeightMerklePath.getMerklePathLength(20), pathBytes.length);
+
+            VariableHeightMerklePath pathDeserialized = VariableHeightMerklePath.deserialize(pathBytes);
+            assertNotNull("Merkle path deserialization must not fail", pathDeserialized);
+            assertTrue("Deserialized merkle path must be verified", pathDeserialized.verify(leaves.get(i), root20));
+
+            //Negative case: non canonical sibling, after height and leaf index
+            Arrays.fill(pathBytes, 9, 9 + FieldElement.FIELD_ELEMENT_LENGTH, (byte) 0xff);
+            try {
+                VariableHeightMerklePath.deserialize(pathBytes);
+                fail("Merkle path with a sibling exceeding the modulus must be rejected");
+            } catch (DeserializationException e) {
+                assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+            }
+
+            //Free memory
+            path10.freeMerklePath();
+            path20.freeMerklePath();
+            pathDeserialized.freeMerklePath();
+        }
+
+        //Free memory
+        mt10.freeMerkleTree();
+        mt20.freeMerkleTree();
+        root10.freeMerkleRoot();
+        root20.freeMerkleRoot();
+        root10Deserialized.freeMerkleRoot();
+        root10Value.freeFieldElement();
+        root10DeserializedValue.freeFieldElement();
+
+        for (FieldElement leaf: leaves)
+            leaf.freeFieldElement();
+    }
+}
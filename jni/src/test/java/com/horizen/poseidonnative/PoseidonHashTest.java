@@ -3,6 +3,8 @@ package com.horizen.poseidonnative;
 import com.horizen.librustsidechains.FieldElement;
 import org.junit.Test;
 
+import java.util.Arrays;
+
 import static org.junit.Assert.*;
 
 
@@ -61,4 +63,120 @@ public class PoseidonHashTest {
         hash.freeFieldElement();
         expectedHash.freeFieldElement();
     }
+
+    @Test
+    public void testUpdateFinalize() {
+
+        int inputLen = 5;
+
+        FieldElement[] input = new FieldElement[inputLen];
+        for (int i = 0; i < inputLen; i++)
+            input[i] = FieldElement.createRandom();
+
+        FieldElement expectedHash = PoseidonHash.computeHash(input);
+
+        PoseidonHash digest = PoseidonHash.getInstance();
+        assertNotNull("Poseidon hash creation must not fail", digest);
+
+        for (FieldElement fe: input)
+            digest.update(fe);
+
+        FieldElement hash = digest.finalizeHash();
+        assertEquals("Buffered and one-shot hashes must be equal", expectedHash, hash);
+
+        //Finalize doesn't modify the state
+        FieldElement hashAgain = digest.finalizeHash();
+        assertEquals("Finalize must be idempotent", expectedHash, hashAgain);
+
+        //Reset and hash again
+        digest.reset();
+        for (FieldElement fe: input)
+            digest.update(fe);
+
+        FieldElement hashAfterReset = digest.finalizeHash();
+        assertEquals("Hash after reset must be equal to the one-shot hash", expectedHash, hashAfterReset);
+
+        //Finalizing in between, also at the boundaries of the chunks absorbed by each permutation, doesn't change the hash
+        for (int split = 1; split < inputLen; split++) {
+            digest.reset();
+            for (int i = 0; i < split; i++)
+                digest.update(input[i]);
+            digest.finalizeHash().freeFieldElement();
+
+            for (int i = split; i < inputLen; i++)
+                digest.update(input[i]);
+            FieldElement splitHash = digest.finalizeHash();
+            assertEquals("Hash split at " + split + " must be equal to the one-shot hash", expectedHash, splitHash);
+            splitHash.freeFieldElement();
+        }
+
+        //Free memory
+        digest.freePoseidonHash();
+        expectedHash.freeFieldElement();
+        hash.freeFieldElement();
+        hashAgain.freeFieldElement();
+        hashAfterReset.freeFieldElement();
+
+        for (FieldElement fe: input)
+            fe.freeFieldElement();
+    }
+
+    @Test
+    public void testHashBytes() {
+
+        byte[] input = "sidechain".getBytes();
+        byte[] inputWithTrailingZero = Arrays.copyOf(input, input.length + 1);
+
+        FieldElement hash = PoseidonHash.hashBytes(input);
+        FieldElement hashAgain = PoseidonHash.hashBytes(input);
+        FieldElement hashWithTrailingZero = PoseidonHash.hashBytes(inputWithTrailingZero);
+
+        assertNotNull("Hash must be computed", hash);
+        assertEquals("Hash must be deterministic", hash, hashAgain);
+        assertNotEquals("Trailing zeros must change the hash", hash, hashWithTrailingZero);
+
+        //Inputs longer than a field element
+        byte[] longInput = new byte[1000];
+        Arrays.fill(longInput, (byte) 0xff);
+        FieldElement longHash = PoseidonHash.hashBytes(longInput);
+        assertNotNull("Hash of long input must be computed", longHash);
+
+        //Free memory
+        hash.freeFieldElement();
+        hashAgain.freeFieldElement();
+        hashWithTrailingZero.freeFieldElement();
+        longHash.freeFieldElement();
+    }
+
+    @Test
+    public void testComputeHashBatch() {
+
+        int batchSize = 100;
+
+        FieldElement[][] inputs = new FieldElement[batchSize][];
+        for (int i = 0; i < batchSize; i++) {
+            inputs[i] = new FieldElement[(i % 5) + 1];
+            for (int j = 0; j < inputs[i].length; j++)
+                inputs[i][j] = FieldElement.createRandom();
+        }
+
+        FieldElement[] hashes = PoseidonHash.computeHashBatch(inputs);
+
+        assertNotNull("Hashes must be computed", hashes);
+        assertEquals("A hash for each input must be computed", batchSize, hashes.length);
+
+        for (int i = 0; i < batchSize; i++) {
+            FieldElement expectedHash = PoseidonHash.computeHash(inputs[i]);
+            assertEquals("Batch and single hashes must be equal", expectedHash, hashes[i]);
+            expectedHash.freeFieldElement();
+        }
+
+        //Free memory
+        for (FieldElement hash: hashes)
+            hash.freeFieldElement();
+
+        for (FieldElement[] input: inputs)
+            for (FieldElement fe: input)
+                fe.freeFieldElement();
+    }
 }
@@ -3,7 +3,9 @@ package com.horizen.schnorrnative;
 import com.horizen.librustsidechains.FieldElement;
 import org.junit.Test;
 
+import java.util.ArrayList;
 import java.util.Arrays;
+import java.util.List;
 
 import static org.junit.Assert.*;
 
@@ -112,4 +114,90 @@ public class SchnorrKeyPairTest {
             signature.freeSignature();
         }
     }
+
+    @Test
+    public void testDeterministicSignVerify() {
+
+        SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+        FieldElement message = FieldElement.createRandom();
+
+        SchnorrSignature signature = keyPair.signMessage(message, true);
+        SchnorrSignature sameSignature = keyPair.signMessage(message, true);
+        assertNotNull("Attempt to sign message failed.", signature);
+        assertNotNull("Attempt to sign message failed.", sameSignature);
+
+        assertTrue("Signature must be verified", keyPair.getPublicKey().verifySignature(signature, message));
+        assertArrayEquals("Deterministic signatures of the same message must be equal",
+                signature.serializeSignature(), sameSignature.serializeSignature());
+
+        SchnorrSignature hedgedSignature = keyPair.signMessage(message, "extra randomness".getBytes());
+        assertNotNull("Attempt to sign message failed.", hedgedSignature);
+
+        assertTrue("Signature must be verified", keyPair.getPublicKey().verifySignature(hedgedSignature, message));
+        assertFalse("Extra randomness must change the signature",
+                Arrays.equals(signature.serializeSignature(), hedgedSignature.serializeSignature()));
+
+        //Free memory
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+
+        message.freeFieldElement();
+
+        signature.freeSignature();
+        sameSignature.freeSignature();
+        hedgedSignature.freeSignature();
+    }
+
+    @Test
+    public void testMultiElementAndBytesSignVerify() {
+
+        SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+        SchnorrPublicKey publicKey = keyPair.getPublicKey();
+
+        //Multi-element messages
+        List<FieldElement> message = new ArrayList<>();
+        for (int i = 0; i < 5; i++)
+            message.add(FieldElement.createRandom());
+
+        SchnorrSignature signature = keyPair.signMessage(message);
+        assertNotNull("Attempt to sign message failed.", signature);
+        assertTrue("Signature must be verified", publicKey.verifySignature(signature, message));
+        assertFalse("Signature must not be verified", publicKey.verifySignature(signature, message.subList(0, 4)));
+
+        SchnorrSignature deterministicSignature = keyPair.signMessage(message, true);
+        assertNotNull("Attempt to sign message failed.", deterministicSignature);
+        assertTrue("Signature must be verified", publicKey.verifySignature(deterministicSignature, message));
+
+        //Byte messages
+        byte[] messageBytes = "sidechain transaction".getBytes();
+        SchnorrSignature bytesSignature = keyPair.signMessageBytes(messageBytes);
+        assertNotNull("Attempt to sign message failed.", bytesSignature);
+        assertTrue("Signature must be verified", publicKey.verifySignatureBytes(bytesSignature, messageBytes));
+        assertFalse("Signature must not be verified",
+                publicKey.verifySignatureBytes(bytesSignature, Arrays.copyOf(messageBytes, messageBytes.length + 1)));
+
+        SchnorrSignature emptyBytesSignature = keyPair.signMessageBytes(new byte[0], true);
+        assertNotNull("Attempt to sign message failed.", emptyBytesSignature);
+        assertTrue("Signature must be verified", publicKey.verifySignatureBytes(emptyBytesSignature, new byte[0]));
+
+        //Byte messages are signed in their own domain, not as their length followed by their packed bytes
+        FieldElement emptyBytesLength = FieldElement.createFromLong(0);
+        assertFalse("Signature must not be verified", publicKey.verifySignature(emptyBytesSignature, emptyBytesLength));
+
+        //Free memory
+        publicKey.freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+
+        for (FieldElement fe : message)
+            fe.freeFieldElement();
+
+        signature.freeSignature();
+        deterministicSignature.freeSignature();
+        bytesSignature.freeSignature();
+        emptyBytesSignature.freeSignature();
+        emptyBytesLength.freeFieldElement();
+    }
 }
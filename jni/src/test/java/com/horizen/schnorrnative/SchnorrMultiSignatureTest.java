@@ -0,0 +1,115 @@
+package com.horizen.schnorrnative;
+
+import com.horizen.librustsidechains.FieldElement;
+import org.junit.Test;
+
+import java.util.ArrayList;
+import java.util.List;
+
+import static org.junit.Assert.*;
+
+public class SchnorrMultiSignatureTest {
+
+    @Test
+    public void testMultiSignature() {
+
+        int signers = 4;
+        List<SchnorrKeyPair> keyPairs = new ArrayList<>();
+        List<SchnorrPublicKey> publicKeys = new ArrayList<>();
+        for (int i = 0; i < signers; i++) {
+            SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+            assertNotNull("Key pair generation was unsuccessful.", keyPair);
+            keyPairs.add(keyPair);
+            publicKeys.add(keyPair.getPublicKey());
+        }
+        SchnorrPublicKey aggregatedPublicKey = SchnorrMultiSignature.aggregatePublicKeys(publicKeys);
+        assertNotNull("Public key aggregation must not fail", aggregatedPublicKey);
+
+        FieldElement message = FieldElement.createRandom();
+
+        //Round 1: nonce commitment
+        List<SchnorrMultiSigNonce> nonces = new ArrayList<>();
+        List<FieldElement> commitments = new ArrayList<>();
+        for (int i = 0; i < signers; i++) {
+            SchnorrMultiSigNonce nonce = SchnorrMultiSigNonce.generate();
+            assertNotNull("Nonce generation was unsuccessful.", nonce);
+            nonces.add(nonce);
+            commitments.add(nonce.getCommitment());
+        }
+
+        //Round 2: nonce exchange. Public nonces are exchanged as bytes
+        List<SchnorrMultiSigPublicNonce> publicNonces = new ArrayList<>();
+        for (SchnorrMultiSigNonce nonce : nonces) {
+            SchnorrMultiSigPublicNonce publicNonce = nonce.getPublicNonce();
+            byte[] publicNonceBytes = publicNonce.serializePublicNonce();
+            assertEquals(SchnorrMultiSigPublicNonce.PUBLIC_NONCE_LENGTH, publicNonceBytes.length);
+            publicNonces.add(SchnorrMultiSigPublicNonce.deserialize(publicNonceBytes));
+            publicNonce.freePublicNonce();
+        }
+        SchnorrMultiSigPublicNonce aggregatedNonce = SchnorrMultiSigPublicNonce.aggregate(publicNonces, commitments);
+        assertNotNull("Nonce aggregation must not fail", aggregatedNonce);
+
+        List<FieldElement> swappedCommitments = new ArrayList<>(commitments);
+        swappedCommitments.set(0, commitments.get(1));
+        swappedCommitments.set(1, commitments.get(0));
+        assertNull("Nonces must be checked against their commitments",
+                SchnorrMultiSigPublicNonce.aggregate(publicNonces, swappedCommitments));
+
+        //Partial signatures
+        List<SchnorrMultiSigPartialSignature> partialSignatures = new ArrayList<>();
+        for (int i = 0; i < signers; i++) {
+            SchnorrMultiSigPartialSignature partialSignature = nonces.get(i).partialSign(
+                    keyPairs.get(i).getSecretKey(), publicKeys, i, aggregatedNonce, message);
+            assertNotNull("Partial signature must not fail", partialSignature);
+            assertTrue("Partial signature must be verified",
+                    partialSignature.verify(publicNonces.get(i), publicKeys, i, aggregatedNonce, message));
+            partialSignatures.add(partialSignature);
+        }
+
+        //A nonce can't be reused
+        try {
+            nonces.get(0).partialSign(keyPairs.get(0).getSecretKey(), publicKeys, 0, aggregatedNonce, message);
+            fail("Nonces must not be reused");
+        } catch (IllegalArgumentException e) {}
+
+        //A signer can only sign at its own position
+        SchnorrMultiSigNonce otherNonce = SchnorrMultiSigNonce.generate();
+        assertNull("Signing with the key of another signer must fail",
+                otherNonce.partialSign(keyPairs.get(0).getSecretKey(), publicKeys, 1, aggregatedNonce, message));
+
+        //Combination and verification
+        SchnorrMultiSignature signature = SchnorrMultiSignature.combine(aggregatedNonce, partialSignatures);
+        assertNotNull("Partial signature combination must not fail", signature);
+
+        SchnorrMultiSignature deserializedSignature = SchnorrMultiSignature.deserialize(signature.serializeSignature());
+        assertTrue("Multi-signature must be verified", deserializedSignature.verify(aggregatedPublicKey, message));
+
+        FieldElement wrongMessage = FieldElement.createRandom();
+        assertFalse("Multi-signature must not be verified for another message", signature.verify(aggregatedPublicKey, wrongMessage));
+        assertFalse("Multi-signature must not be verified for a single signer",
+                signature.verify(keyPairs.get(0).getPublicKey(), message));
+
+        SchnorrMultiSignature incompleteSignature = SchnorrMultiSignature.combine(aggregatedNonce, partialSignatures.subList(1, signers));
+        assertFalse("Multi-signature must not be verified without all the partial signatures",
+                incompleteSignature.verify(aggregatedPublicKey, message));
+
+        //Free memory
+        for (SchnorrKeyPair keyPair : keyPairs) {
+            keyPair.getPublicKey().freePublicKey();
+            keyPair.getSecretKey().freeSecretKey();
+        }
+        for (int i = 0; i < signers; i++) {
+            commitments.get(i).freeFieldElement();
+            publicNonces.get(i).freePublicNonce();
+            partialSignatures.get(i).freePartialSignature();
+        }
+        aggregatedPublicKey.freePublicKey();
+        aggregatedNonce.freePublicNonce();
+        otherNonce.freeNonce();
+        signature.freeSignature();
+        deserializedSignature.freeSignature();
+        incompleteSignature.freeSignature();
+        message.freeFieldElement();
+        wrongMessage.freeFieldElement();
+    }
+}
@@ -0,0 +1,196 @@
+package com.horizen.schnorrnative;
+
+import com.horizen.librustsidechains.DeserializationException;
+import com.horizen.librustsidechains.FieldElement;
+import com.horizen.sigproofnative.NaiveThresholdSigProof;
+import org.junit.Test;
+
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.List;
+
+import static org.junit.Assert.*;
+
+public class SchnorrPublicKeyTest {
+
+    @Test
+    public void testVerifyBatch() {
+
+        int samples = 10;
+
+        List<SchnorrKeyPair> keyPairs = new ArrayList<>();
+        List<SchnorrPublicKey> publicKeys = new ArrayList<>();
+        List<FieldElement> messages = new ArrayList<>();
+        List<SchnorrSignature> signatures = new ArrayList<>();
+
+        for (int i = 0; i < samples; i++) {
+            SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+            assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+            FieldElement message = FieldElement.createRandom();
+            SchnorrSignature signature = keyPair.signMessage(message);
+            assertNotNull("Attempt to sign message failed.", signature);
+
+            keyPairs.add(keyPair);
+            publicKeys.add(keyPair.getPublicKey());
+            messages.add(message);
+            signatures.add(signature);
+        }
+
+        assertTrue("Batch must be verified", SchnorrPublicKey.verifyBatch(publicKeys, messages, signatures));
+
+        boolean[] verdicts = SchnorrPublicKey.verifyBatchPerItem(publicKeys, messages, signatures);
+        assertNotNull("Batch verification per item must not fail", verdicts);
+        assertEquals(samples, verdicts.length);
+        for (boolean verdict : verdicts)
+            assertTrue("Signature must be verified", verdict);
+
+        // Replace one message: only the corresponding signature must fail
+        FieldElement wrongMessage = FieldElement.createRandom();
+        FieldElement replacedMessage = messages.set(3, wrongMessage);
+
+        assertFalse("Batch must not be verified", SchnorrPublicKey.verifyBatch(publicKeys, messages, signatures));
+
+        verdicts = SchnorrPublicKey.verifyBatchPerItem(publicKeys, messages, signatures);
+        assertNotNull("Batch verification per item must not fail", verdicts);
+        for (int i = 0; i < samples; i++)
+            assertEquals("Unexpected verdict for signature " + i, i != 3, verdicts[i]);
+
+        // Mismatching batch sizes must be rejected
+        try {
+            SchnorrPublicKey.verifyBatch(publicKeys, messages.subList(0, samples - 1), signatures);
+            fail("Mismatching batch sizes must be rejected");
+        } catch (IllegalArgumentException e) {}
+
+        //Free memory
+        for (SchnorrKeyPair keyPair : keyPairs) {
+            keyPair.getPublicKey().freePublicKey();
+            keyPair.getSecretKey().freeSecretKey();
+        }
+        for (FieldElement message : messages)
+            message.freeFieldElement();
+        replacedMessage.freeFieldElement();
+        for (SchnorrSignature signature : signatures)
+            signature.freeSignature();
+    }
+
+    @Test
+    public void testCompressedSerialization() {
+
+        SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+        byte[] publicKeyBytes = keyPair.getPublicKey().serializePublicKeyCompressed();
+        assertEquals(SchnorrPublicKey.PUBLIC_KEY_COMPRESSED_LENGTH, publicKeyBytes.length);
+
+        SchnorrPublicKey publicKey = SchnorrPublicKey.deserializeCompressed(publicKeyBytes);
+        assertNotNull("Compressed public key deserialization must not fail", publicKey);
+        assertTrue("Public key verification failed.", publicKey.verifyKey());
+        assertArrayEquals("Public key must be the same",
+                keyPair.getPublicKey().serializePublicKey(), publicKey.serializePublicKey());
+
+        //Flipping the sign flag gives the opposite point, which is a different valid key
+        byte[] oppositeBytes = publicKeyBytes.clone();
+        oppositeBytes[oppositeBytes.length - 1] ^= 0x40;
+        SchnorrPublicKey oppositePublicKey = SchnorrPublicKey.deserializeCompressed(oppositeBytes);
+        assertNotNull("Opposite public key deserialization must not fail", oppositePublicKey);
+        assertFalse("Opposite public key must be different",
+                Arrays.equals(publicKey.serializePublicKey(), oppositePublicKey.serializePublicKey()));
+
+        //Free memory
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+        publicKey.freePublicKey();
+        oppositePublicKey.freePublicKey();
+    }
+
+    @Test
+    public void testProofOfPossession() {
+
+        SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+        SchnorrKeyPair otherKeyPair = SchnorrKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+        assertNotNull("Key pair generation was unsuccessful.", otherKeyPair);
+
+        SchnorrSignature pop = keyPair.createProofOfPossession();
+        SchnorrSignature otherPop = otherKeyPair.createProofOfPossession();
+        assertNotNull("Proof of possession creation failed.", pop);
+        assertNotNull("Proof of possession creation failed.", otherPop);
+
+        assertTrue("Proof of possession must be verified", keyPair.getPublicKey().verifyProofOfPossession(pop));
+        assertFalse("Proof of possession must not be verified for another key",
+                otherKeyPair.getPublicKey().verifyProofOfPossession(pop));
+
+        //Signer set constant
+        List<SchnorrPublicKey> publicKeys = Arrays.asList(keyPair.getPublicKey(), otherKeyPair.getPublicKey());
+
+        FieldElement constant = NaiveThresholdSigProof.getConstant(publicKeys, Arrays.asList(pop, otherPop), 1);
+        FieldElement constantWithoutPops = NaiveThresholdSigProof.getConstant(publicKeys, 1);
+        assertNotNull("Constant creation must be successfull", constant);
+        assertEquals("Proofs of possession must not change the constant", constantWithoutPops, constant);
+
+        assertNull("Constant creation must fail with invalid proofs of possession",
+                NaiveThresholdSigProof.getConstant(publicKeys, Arrays.asList(otherPop, pop), 1));
+
+        //Free memory
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+        otherKeyPair.getPublicKey().freePublicKey();
+        otherKeyPair.getSecretKey().freeSecretKey();
+
+        pop.freeSignature();
+        otherPop.freeSignature();
+        constant.freeFieldElement();
+        constantWithoutPops.freeFieldElement();
+    }
+
+    private static void assertRejected(byte[] publicKeyBytes, DeserializationException.Reason reason) {
+        try {
+            SchnorrPublicKey.deserialize(publicKeyBytes);
+            fail("Invalid public key must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(reason, e.getReason());
+        }
+    }
+
+    @Test
+    public void testInvalidDeserialization() {
+
+        SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+        byte[] publicKeyBytes = keyPair.getPublicKey().serializePublicKey();
+
+        //Point not on the curve: y is changed by one
+        byte[] offCurveBytes = publicKeyBytes.clone();
+        offCurveBytes[FieldElement.FIELD_ELEMENT_LENGTH] ^= 1;
+        assertRejected(offCurveBytes, DeserializationException.Reason.NOT_ON_CURVE);
+
+        //Non canonical infinity flag
+        byte[] wrongFlagBytes = publicKeyBytes.clone();
+        wrongFlagBytes[SchnorrPublicKey.PUBLIC_KEY_LENGTH - 1] = 2;
+        assertRejected(wrongFlagBytes, DeserializationException.Reason.NON_CANONICAL);
+
+        //Non reduced x coordinate
+        byte[] nonReducedBytes = publicKeyBytes.clone();
+        Arrays.fill(nonReducedBytes, 0, FieldElement.FIELD_ELEMENT_LENGTH, (byte) 0xff);
+        assertRejected(nonReducedBytes, DeserializationException.Reason.NON_CANONICAL);
+
+        //Point at infinity
+        byte[] zeroBytes = new byte[SchnorrPublicKey.PUBLIC_KEY_LENGTH];
+        zeroBytes[SchnorrPublicKey.PUBLIC_KEY_LENGTH - 1] = 1;
+        assertRejected(zeroBytes, DeserializationException.Reason.ZERO);
+
+        //Signatures with non reduced scalars
+        byte[] nonReducedSignatureBytes = new byte[SchnorrSignature.SIGNATURE_LENGTH];
+        Arrays.fill(nonReducedSignatureBytes, (byte) 0xff);
+        try {
+            SchnorrSignature.deserialize(nonReducedSignatureBytes);
+            fail("Invalid signature must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+    }
+}
@@ -1,7 +1,10 @@
 package com.horizen.schnorrnative;
 
+import com.horizen.librustsidechains.DeserializationException;
 import org.junit.Test;
 
+import java.util.Arrays;
+
 import static org.junit.Assert.*;
 
 public class SchnorrSecretKeyTest {
@@ -47,4 +50,92 @@ public class SchnorrSecretKeyTest {
             recreatedPublicKey.freePublicKey();
         }
     }
+
+    @Test
+    public void testDeriveFromSeed() {
+
+        byte[] seed = new byte[32];
+        for (int i = 0; i < seed.length; i++)
+            seed[i] = (byte) i;
+
+        SchnorrSecretKey secretKey = SchnorrSecretKey.deriveFromSeed(seed, "m/0'/1/2'");
+        SchnorrSecretKey sameSecretKey = SchnorrSecretKey.deriveFromSeed(seed, "m/0'/1/2'");
+        SchnorrSecretKey otherSecretKey = SchnorrSecretKey.deriveFromSeed(seed, "m/0'/1/3'");
+
+        assertNotNull("Key derivation must not fail", secretKey);
+        assertNotNull("Key derivation must not fail", sameSecretKey);
+        assertNotNull("Key derivation must not fail", otherSecretKey);
+
+        assertArrayEquals("Derivation must be deterministic",
+                secretKey.serializeSecretKey(), sameSecretKey.serializeSecretKey());
+        assertFalse("Different paths must lead to different keys",
+                Arrays.equals(secretKey.serializeSecretKey(), otherSecretKey.serializeSecretKey()));
+
+        SchnorrPublicKey publicKey = secretKey.getPublicKey();
+        assertTrue("Derived key must be valid.", publicKey.verifyKey());
+
+        assertNull("Invalid paths must be rejected", SchnorrSecretKey.deriveFromSeed(seed, "m/0'/x"));
+        assertNull("Invalid paths must be rejected", SchnorrSecretKey.deriveFromSeed(seed, "0/1"));
+
+        try {
+            SchnorrSecretKey.deriveFromSeed(new byte[SchnorrSecretKey.SEED_MIN_LENGTH - 1], "m");
+            fail("Short seeds must be rejected");
+        } catch (IllegalArgumentException e) {}
+
+        secretKey.freeSecretKey();
+        sameSecretKey.freeSecretKey();
+        otherSecretKey.freeSecretKey();
+        publicKey.freePublicKey();
+    }
+
+    @Test
+    public void testEncryptDecrypt() {
+
+        SchnorrKeyPair keyPair = SchnorrKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+        byte[] password = "correct horse battery staple".getBytes();
+        byte[] secretKeyBytes = keyPair.getSecretKey().serializeSecretKey();
+
+        byte[] keystore = keyPair.getSecretKey().encrypt(password);
+        assertNotNull("Encryption must not fail", keystore);
+        assertEquals(SchnorrSecretKey.KEYSTORE_LENGTH, keystore.length);
+
+        SchnorrSecretKey decryptedSecretKey = SchnorrSecretKey.decrypt(keystore, password);
+        assertNotNull("Decryption must not fail", decryptedSecretKey);
+        assertArrayEquals("Decrypted secret key must be the same", secretKeyBytes, decryptedSecretKey.serializeSecretKey());
+
+        assertNull("Wrong password must be rejected", SchnorrSecretKey.decrypt(keystore, "wrong password".getBytes()));
+
+        byte[] corruptedKeystore = Arrays.copyOf(keystore, keystore.length);
+        corruptedKeystore[keystore.length / 2] ^= 1;
+        assertNull("Corrupted keystore must be rejected", SchnorrSecretKey.decrypt(corruptedKeystore, password));
+
+        assertNull("Keystores of other key types must be rejected",
+                com.horizen.vrfnative.VRFSecretKey.decrypt(keystore, password));
+
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+        decryptedSecretKey.freeSecretKey();
+    }
+
+    @Test
+    public void testInvalidDeserialization() {
+
+        try {
+            SchnorrSecretKey.deserialize(new byte[SchnorrSecretKey.SECRET_KEY_LENGTH]);
+            fail("Zero secret key must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.ZERO, e.getReason());
+        }
+
+        byte[] nonReducedBytes = new byte[SchnorrSecretKey.SECRET_KEY_LENGTH];
+        Arrays.fill(nonReducedBytes, (byte) 0xff);
+        try {
+            SchnorrSecretKey.deserialize(nonReducedBytes);
+            fail("Secret keys exceeding the modulus must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+    }
 }
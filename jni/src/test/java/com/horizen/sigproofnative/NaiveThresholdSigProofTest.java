@@ -25,6 +25,9 @@ public class NaiveThresholdSigProofTest {
     static long threshold = 2;
     static int backwardTransferCout = 10;
 
+    // The sample keys and the hardcoded signatures were generated before the introduction of domain separation
+    static int version = NaiveThresholdSigProof.LEGACY_DOMAIN_SEPARATION_VERSION;
+
     byte[] endEpochBlockHash = new byte[32];
     byte[] prevEndEpochBlockHash = new byte[32];
 
@@ -256,7 +259,7 @@ public class NaiveThresholdSigProofTest {
         for (int i = 0; i<keyCount; i++) {
             if (i < threshold) {
                 FieldElement msgToSign = NaiveThresholdSigProof.createMsgToSign(btList.toArray(new BackwardTransfer[0]),
-                        endEpochBlockHash, prevEndEpochBlockHash);
+                        endEpochBlockHash, prevEndEpochBlockHash, version);
                 signatureList.add(keyPairList.get(i).signMessage(msgToSign));
             } else {
                 signatureList.add(new SchnorrSignature());
@@ -276,7 +279,7 @@ public class NaiveThresholdSigProofTest {
 
         String provingKeyPath = new File(classLoader.getResource("sample_params").getFile()).getAbsolutePath();
         CreateProofResult proofResult = NaiveThresholdSigProof.createProof(btList, endEpochBlockHash, prevEndEpochBlockHash,
-                signatureList, publicKeyList, threshold, provingKeyPath);
+                signatureList, publicKeyList, threshold, provingKeyPath, version);
 
         assertNotNull("Proof creation must be successfull", proofResult);
 
@@ -285,17 +288,24 @@ public class NaiveThresholdSigProofTest {
         byte[] proof = proofResult.getProof();
         long quality = proofResult.getQuality();
 
-        FieldElement constant = NaiveThresholdSigProof.getConstant(publicKeyList, threshold);
+        FieldElement constant = NaiveThresholdSigProof.getConstant(publicKeyList, threshold, version);
         assertNotNull("Constant creation must be successfull", constant);
 
         boolean isProofVerified = NaiveThresholdSigProof.verifyProof(btList, endEpochBlockHash,
-                prevEndEpochBlockHash, constant, quality, proof, verificationKeyPath);
+                prevEndEpochBlockHash, constant, quality, proof, verificationKeyPath, version);
 
         assertTrue("Proof must be verified", isProofVerified);
 
+        byte[] compressedProof = NaiveThresholdSigProof.compressProof(proof);
+        assertNotNull("Proof compression must be successfull", compressedProof);
+        assertEquals(NaiveThresholdSigProof.PROOF_COMPRESSED_LENGTH, compressedProof.length);
+
+        byte[] decompressedProof = NaiveThresholdSigProof.decompressProof(compressedProof);
+        assertArrayEquals("Decompressed proof must be the same", proof, decompressedProof);
+
         quality = threshold - 1;
         isProofVerified = NaiveThresholdSigProof.verifyProof(btList, endEpochBlockHash,
-                prevEndEpochBlockHash, constant, quality, proof, verificationKeyPath);
+                prevEndEpochBlockHash, constant, quality, proof, verificationKeyPath, version);
 
         assertFalse("Proof must not be verified", isProofVerified);
     }
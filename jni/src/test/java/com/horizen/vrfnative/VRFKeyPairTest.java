@@ -1,14 +1,19 @@
 package com.horizen.vrfnative;
 
+import com.horizen.librustsidechains.DeserializationException;
 import com.horizen.librustsidechains.FieldElement;
 import com.horizen.schnorrnative.SchnorrKeyPair;
 import com.horizen.schnorrnative.SchnorrSignature;
 import org.junit.Test;
 
+import java.util.Arrays;
+
+import static org.junit.Assert.assertArrayEquals;
 import static org.junit.Assert.assertNull;
 import static org.junit.Assert.assertNotNull;
 import static org.junit.Assert.assertTrue;
 import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.fail;
 
 public class VRFKeyPairTest {
 
@@ -150,4 +155,94 @@ public class VRFKeyPairTest {
             proofVRFOutputPair.getVRFProof().freeProof();
         }
     }
+
+    @Test
+    public void testCompressedSerialization() {
+
+        VRFKeyPair keyPair = VRFKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+        //Public key
+        byte[] publicKeyBytes = keyPair.getPublicKey().serializePublicKeyCompressed();
+        assertEquals(VRFPublicKey.PUBLIC_KEY_COMPRESSED_LENGTH, publicKeyBytes.length);
+
+        VRFPublicKey publicKey = VRFPublicKey.deserializeCompressed(publicKeyBytes);
+        assertNotNull("Compressed public key deserialization must not fail", publicKey);
+        assertArrayEquals("Public key must be the same",
+                keyPair.getPublicKey().serializePublicKey(), publicKey.serializePublicKey());
+
+        //Proof
+        FieldElement message = FieldElement.createRandom();
+        VRFProveResult proveResult = keyPair.prove(message);
+        assertNotNull("Attempt to create vrf proof and output failed.", proveResult);
+
+        byte[] proofBytes = proveResult.getVRFProof().serializeProofCompressed();
+        assertEquals(VRFProof.PROOF_COMPRESSED_LENGTH, proofBytes.length);
+
+        VRFProof proof = VRFProof.deserializeCompressed(proofBytes);
+        assertNotNull("Compressed proof deserialization must not fail", proof);
+
+        FieldElement vrfOutput = publicKey.proofToHash(proof, message);
+        assertEquals("Decompressed proof must give the same output", proveResult.getVRFOutput(), vrfOutput);
+
+        //Invalid encodings are rejected
+        byte[] invalidBytes = new byte[VRFPublicKey.PUBLIC_KEY_COMPRESSED_LENGTH];
+        Arrays.fill(invalidBytes, (byte) 0x3f);
+        try {
+            VRFPublicKey.deserializeCompressed(invalidBytes);
+            fail("Invalid public key must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NON_CANONICAL, e.getReason());
+        }
+
+        //Free memory
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+        publicKey.freePublicKey();
+
+        message.freeFieldElement();
+        vrfOutput.freeFieldElement();
+        proveResult.getVRFOutput().freeFieldElement();
+        proveResult.getVRFProof().freeProof();
+        proof.freeProof();
+    }
+
+    @Test
+    public void testInvalidDeserialization() {
+
+        VRFKeyPair keyPair = VRFKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+        FieldElement message = FieldElement.createRandom();
+        VRFProveResult proveResult = keyPair.prove(message);
+        assertNotNull("Attempt to create vrf proof and output failed.", proveResult);
+
+        //Gamma not on the curve: its y coordinate is changed by one
+        byte[] proofBytes = proveResult.getVRFProof().serializeProof();
+        proofBytes[FieldElement.FIELD_ELEMENT_LENGTH] ^= 1;
+        try {
+            VRFProof.deserialize(proofBytes);
+            fail("Invalid proof must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.NOT_ON_CURVE, e.getReason());
+        }
+
+        //Point at infinity as public key
+        byte[] zeroBytes = new byte[VRFPublicKey.PUBLIC_KEY_LENGTH];
+        zeroBytes[VRFPublicKey.PUBLIC_KEY_LENGTH - 1] = 1;
+        try {
+            VRFPublicKey.deserialize(zeroBytes);
+            fail("Zero public key must be rejected");
+        } catch (DeserializationException e) {
+            assertEquals(DeserializationException.Reason.ZERO, e.getReason());
+        }
+
+        //Free memory
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+
+        message.freeFieldElement();
+        proveResult.getVRFOutput().freeFieldElement();
+        proveResult.getVRFProof().freeProof();
+    }
 }
@@ -2,6 +2,8 @@ package com.horizen.vrfnative;
 
 import org.junit.Test;
 
+import java.util.Arrays;
+
 import static org.junit.Assert.*;
 
 public class VRFSecretKeyTest {
@@ -51,4 +53,72 @@ public class VRFSecretKeyTest {
             recreatedPublicKey.freePublicKey();
         }
     }
+
+    @Test
+    public void testDeriveFromSeed() {
+
+        byte[] seed = new byte[32];
+        for (int i = 0; i < seed.length; i++)
+            seed[i] = (byte) i;
+
+        VRFSecretKey secretKey = VRFSecretKey.deriveFromSeed(seed, "m/0'/1/2'");
+        VRFSecretKey sameSecretKey = VRFSecretKey.deriveFromSeed(seed, "m/0'/1/2'");
+        VRFSecretKey otherSecretKey = VRFSecretKey.deriveFromSeed(seed, "m/0'/1/3'");
+
+        assertNotNull("Key derivation must not fail", secretKey);
+        assertNotNull("Key derivation must not fail", sameSecretKey);
+        assertNotNull("Key derivation must not fail", otherSecretKey);
+
+        assertArrayEquals("Derivation must be deterministic",
+                secretKey.serializeSecretKey(), sameSecretKey.serializeSecretKey());
+        assertFalse("Different paths must lead to different keys",
+                Arrays.equals(secretKey.serializeSecretKey(), otherSecretKey.serializeSecretKey()));
+
+        VRFPublicKey publicKey = secretKey.getPublicKey();
+        assertTrue("Derived key must be valid.", publicKey.verifyKey());
+
+        assertNull("Invalid paths must be rejected", VRFSecretKey.deriveFromSeed(seed, "m/0'/x"));
+        assertNull("Invalid paths must be rejected", VRFSecretKey.deriveFromSeed(seed, "0/1"));
+
+        try {
+            VRFSecretKey.deriveFromSeed(new byte[VRFSecretKey.SEED_MIN_LENGTH - 1], "m");
+            fail("Short seeds must be rejected");
+        } catch (IllegalArgumentException e) {}
+
+        secretKey.freeSecretKey();
+        sameSecretKey.freeSecretKey();
+        otherSecretKey.freeSecretKey();
+        publicKey.freePublicKey();
+    }
+
+    @Test
+    public void testEncryptDecrypt() {
+
+        VRFKeyPair keyPair = VRFKeyPair.generate();
+        assertNotNull("Key pair generation was unsuccessful.", keyPair);
+
+        byte[] password = "correct horse battery staple".getBytes();
+        byte[] secretKeyBytes = keyPair.getSecretKey().serializeSecretKey();
+
+        byte[] keystore = keyPair.getSecretKey().encrypt(password);
+        assertNotNull("Encryption must not fail", keystore);
+        assertEquals(VRFSecretKey.KEYSTORE_LENGTH, keystore.length);
+
+        VRFSecretKey decryptedSecretKey = VRFSecretKey.decrypt(keystore, password);
+        assertNotNull("Decryption must not fail", decryptedSecretKey);
+        assertArrayEquals("Decrypted secret key must be the same", secretKeyBytes, decryptedSecretKey.serializeSecretKey());
+
+        assertNull("Wrong password must be rejected", VRFSecretKey.decrypt(keystore, "wrong password".getBytes()));
+
+        byte[] corruptedKeystore = Arrays.copyOf(keystore, keystore.length);
+        corruptedKeystore[keystore.length / 2] ^= 1;
+        assertNull("Corrupted keystore must be rejected", VRFSecretKey.decrypt(corruptedKeystore, password));
+
+        assertNull("Keystores of other key types must be rejected",
+                com.horizen.schnorrnative.SchnorrSecretKey.decrypt(keystore, password));
+
+        keyPair.getPublicKey().freePublicKey();
+        keyPair.getSecretKey().freeSecretKey();
+        decryptedSecretKey.freeSecretKey();
+    }
 }
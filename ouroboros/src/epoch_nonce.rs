@@ -0,0 +1,253 @@
+//! Evolution of the epoch randomness.
+//!
+//! The randomness of the next epoch is derived from the randomness of the current one and from the
+//! nonce VRF outputs of the blocks of the current epoch, folded in slot order with Poseidon:
+//! `acc_0 = epoch_randomness`, `acc_i = H(acc_{i-1}, nonce_output_i)` in the EpochNonceFold domain,
+//! and finally `next_epoch_randomness = H(acc_n)` in the EpochRandomness domain. Only the blocks up to the stability cutoff slot contribute,
+//! so that the randomness of the next epoch is fixed, and agreed upon by all the nodes, before the
+//! epoch ends.
+
+use algebra::{fields::mnt4753::Fr, FpParameters, FromBytes, PrimeField, ToBytes};
+use demo_circuit::domain_separation::{domain_separated_hash, DomainSeparationVersion, HashDomain};
+use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult, Write};
+
+use crate::{vrf::NonceOutput, Error};
+
+/// Folds the nonce VRF outputs of the blocks of an epoch into the randomness of the next one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochNonceAccumulator {
+    epoch_randomness: Fr,
+    stability_cutoff_slot: u32,
+    // (slot, accumulator after folding the nonce output of the slot), by increasing slot
+    contributions: Vec<(u32, Fr)>,
+}
+
+impl EpochNonceAccumulator {
+    /// Nonce outputs of slots after `stability_cutoff_slot` are ignored.
+    pub fn new(epoch_randomness: Fr, stability_cutoff_slot: u32) -> Self {
+        Self { epoch_randomness, stability_cutoff_slot, contributions: Vec::new() }
+    }
+
+    pub fn epoch_randomness(&self) -> Fr {
+        self.epoch_randomness
+    }
+
+    pub fn stability_cutoff_slot(&self) -> u32 {
+        self.stability_cutoff_slot
+    }
+
+    /// Returns the slot of the last folded nonce output, if any.
+    pub fn last_slot(&self) -> Option<u32> {
+        self.contributions.last().map(|&(slot, _)| slot)
+    }
+
+    fn accumulator(&self) -> Fr {
+        self.contributions.last().map_or(self.epoch_randomness, |&(_, acc)| acc)
+    }
+
+    fn fold(&mut self, slot_number: u32, nonce: Fr) -> Result<bool, Error> {
+        if let Some(last_slot) = self.last_slot() {
+            if slot_number <= last_slot {
+                Err(format!("Slot {} is not after the last folded slot {}", slot_number, last_slot))?
+            }
+        }
+        if slot_number > self.stability_cutoff_slot {
+            return Ok(false);
+        }
+
+        // Consensus critical: the version of the personalization is fixed.
+        let acc = domain_separated_hash(
+            HashDomain::EpochNonceFold, DomainSeparationVersion::V1, &[self.accumulator(), nonce]
+        )?;
+        self.contributions.push((slot_number, acc));
+        Ok(true)
+    }
+
+    /// Folds the nonce output of the block of `slot_number`, which must come after the slots of the
+    /// blocks already folded. Returns false, leaving the accumulator unchanged, if the slot comes
+    /// after the stability cutoff.
+    pub fn add(&mut self, slot_number: u32, nonce_output: &NonceOutput<Fr>) -> Result<bool, Error> {
+        self.fold(slot_number, nonce_output.value())
+    }
+
+    /// Reverts the accumulator to its state after folding the block of `slot_number`, discarding
+    /// the blocks of the following slots, e.g. on a chain switch.
+    pub fn rollback(&mut self, slot_number: u32) {
+        let kept = self.contributions.iter().take_while(|&&(slot, _)| slot <= slot_number).count();
+        self.contributions.truncate(kept);
+    }
+
+    /// Returns the randomness of the next epoch, given the blocks folded so far.
+    pub fn next_epoch_randomness(&self) -> Result<Fr, Error> {
+        domain_separated_hash(HashDomain::EpochRandomness, DomainSeparationVersion::V1, &[self.accumulator()])
+    }
+}
+
+// epoch_randomness || stability_cutoff_slot || number of contributions || (slot || accumulator)*
+impl ToBytes for EpochNonceAccumulator {
+    fn write<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.epoch_randomness.write(&mut writer)?;
+        self.stability_cutoff_slot.write(&mut writer)?;
+        (self.contributions.len() as u32).write(&mut writer)?;
+        for &(slot, acc) in self.contributions.iter() {
+            slot.write(&mut writer)?;
+            acc.write(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+// Unlike Fr::read, fails if the integer read is not smaller than the modulus, so that each
+// accumulator has a single serialization.
+fn read_canonical_fr<R: Read>(reader: R) -> IoResult<Fr> {
+    let repr = <Fr as PrimeField>::BigInt::read(reader)?;
+    if repr >= <Fr as PrimeField>::Params::MODULUS {
+        return Err(IoError::new(ErrorKind::InvalidData, "Field element exceeds the modulus"));
+    }
+    Ok(Fr::from_repr(repr))
+}
+
+impl FromBytes for EpochNonceAccumulator {
+    fn read<R: Read>(mut reader: R) -> IoResult<Self> {
+        let epoch_randomness = read_canonical_fr(&mut reader)?;
+        let stability_cutoff_slot = u32::read(&mut reader)?;
+        let len = u32::read(&mut reader)?;
+
+        let mut contributions = Vec::new();
+        let mut last_slot = None;
+        for _ in 0..len {
+            let slot = u32::read(&mut reader)?;
+            let acc = read_canonical_fr(&mut reader)?;
+            if slot > stability_cutoff_slot || last_slot.map_or(false, |last| slot <= last) {
+                return Err(IoError::new(ErrorKind::InvalidData, format!("Invalid contribution slot {}", slot)));
+            }
+            last_slot = Some(slot);
+            contributions.push((slot, acc));
+        }
+        Ok(Self { epoch_randomness, stability_cutoff_slot, contributions })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use algebra::{to_bytes, UniformRand};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_epoch_nonce_accumulator() {
+        let rng = &mut thread_rng();
+        let epoch_randomness = Fr::rand(rng);
+        let nonces: Vec<Fr> = (0..5).map(|_| Fr::rand(rng)).collect();
+        let slots = [3u32, 4, 10, 17, 25];
+
+        let mut acc = EpochNonceAccumulator::new(epoch_randomness, 20);
+        assert_eq!(acc.last_slot(), None);
+        let unchanged_randomness = acc.next_epoch_randomness().unwrap();
+        assert_ne!(unchanged_randomness, epoch_randomness);
+
+        for (&slot, &nonce) in slots.iter().zip(nonces.iter()).take(4) {
+            assert!(acc.fold(slot, nonce).unwrap());
+        }
+        assert_eq!(acc.last_slot(), Some(17));
+
+        //Slots after the cutoff are ignored
+        let next_randomness = acc.next_epoch_randomness().unwrap();
+        assert!(!acc.fold(slots[4], nonces[4]).unwrap());
+        assert_eq!(acc.last_slot(), Some(17));
+        assert_eq!(acc.next_epoch_randomness().unwrap(), next_randomness);
+
+        //Slots must be increasing
+        assert!(acc.fold(17, nonces[4]).is_err());
+        assert!(acc.fold(5, nonces[4]).is_err());
+
+        //The result depends on every nonce, and on their order
+        let mut swapped_acc = EpochNonceAccumulator::new(epoch_randomness, 20);
+        for (&slot, &nonce) in slots.iter().zip([nonces[1], nonces[0], nonces[2], nonces[3]].iter()) {
+            swapped_acc.fold(slot, nonce).unwrap();
+        }
+        assert_ne!(swapped_acc.next_epoch_randomness().unwrap(), next_randomness);
+        assert_ne!(next_randomness, unchanged_randomness);
+
+        //Rollback
+        let mut rolled_back_acc = acc.clone();
+        rolled_back_acc.rollback(12);
+        assert_eq!(rolled_back_acc.last_slot(), Some(10));
+        let mut partial_acc = EpochNonceAccumulator::new(epoch_randomness, 20);
+        for (&slot, &nonce) in slots.iter().zip(nonces.iter()).take(3) {
+            partial_acc.fold(slot, nonce).unwrap();
+        }
+        assert_eq!(rolled_back_acc, partial_acc);
+
+        //Folding the same block again gives back the same state
+        rolled_back_acc.fold(slots[3], nonces[3]).unwrap();
+        assert_eq!(rolled_back_acc, acc);
+
+        rolled_back_acc.rollback(0);
+        assert_eq!(rolled_back_acc, EpochNonceAccumulator::new(epoch_randomness, 20));
+        assert_eq!(rolled_back_acc.next_epoch_randomness().unwrap(), unchanged_randomness);
+    }
+
+    #[test]
+    fn test_epoch_nonce_accumulator_serialization() {
+        let rng = &mut thread_rng();
+        let mut acc = EpochNonceAccumulator::new(Fr::rand(rng), 100);
+        for slot in [1u32, 2, 50].iter() {
+            acc.fold(*slot, Fr::rand(rng)).unwrap();
+        }
+
+        let acc_bytes = to_bytes!(acc).unwrap();
+        let deserialized_acc = EpochNonceAccumulator::read(acc_bytes.as_slice()).unwrap();
+        assert_eq!(deserialized_acc, acc);
+        assert_eq!(deserialized_acc.next_epoch_randomness().unwrap(), acc.next_epoch_randomness().unwrap());
+
+        //Truncated bytes
+        assert!(EpochNonceAccumulator::read(&acc_bytes[..acc_bytes.len() - 1]).is_err());
+
+        //Slots not increasing, or after the cutoff
+        let mut invalid_acc = acc.clone();
+        invalid_acc.contributions.swap(0, 1);
+        assert!(EpochNonceAccumulator::read(to_bytes!(invalid_acc).unwrap().as_slice()).is_err());
+
+        let mut invalid_acc = acc.clone();
+        invalid_acc.stability_cutoff_slot = 49;
+        assert!(EpochNonceAccumulator::read(to_bytes!(invalid_acc).unwrap().as_slice()).is_err());
+
+        //Non canonical field elements: the modulus in place of the epoch randomness, or of the last accumulator
+        let modulus_bytes = to_bytes!(<Fr as PrimeField>::Params::MODULUS).unwrap();
+        let mut non_canonical_bytes = acc_bytes.clone();
+        non_canonical_bytes[..modulus_bytes.len()].copy_from_slice(modulus_bytes.as_slice());
+        assert!(EpochNonceAccumulator::read(non_canonical_bytes.as_slice()).is_err());
+        let mut non_canonical_bytes = acc_bytes.clone();
+        let offset = non_canonical_bytes.len() - modulus_bytes.len();
+        non_canonical_bytes[offset..].copy_from_slice(modulus_bytes.as_slice());
+        assert!(EpochNonceAccumulator::read(non_canonical_bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_epoch_nonce_domain_separation() {
+        let rng = &mut thread_rng();
+        let epoch_randomness = Fr::rand(rng);
+        let nonce = Fr::rand(rng);
+
+        //Folding and finalization are domain separated, from each other and from the plain hash
+        let mut acc = EpochNonceAccumulator::new(epoch_randomness, 10);
+        assert_eq!(
+            acc.next_epoch_randomness().unwrap(),
+            domain_separated_hash(HashDomain::EpochRandomness, DomainSeparationVersion::V1, &[epoch_randomness]).unwrap()
+        );
+        acc.fold(1, nonce).unwrap();
+        let folded = domain_separated_hash(
+            HashDomain::EpochNonceFold, DomainSeparationVersion::V1, &[epoch_randomness, nonce]
+        ).unwrap();
+        assert_eq!(acc.accumulator(), folded);
+        assert_ne!(
+            folded,
+            domain_separated_hash(HashDomain::EpochNonceFold, DomainSeparationVersion::V0, &[epoch_randomness, nonce]).unwrap()
+        );
+        assert_ne!(
+            acc.next_epoch_randomness().unwrap(),
+            domain_separated_hash(HashDomain::EpochNonceFold, DomainSeparationVersion::V1, &[folded]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Stake-dependent leader election of Ouroboros Praos.
+//!
+//! A forger holding a relative stake `alpha = stake / total_stake` is the leader of a slot if its
+//! VRF output for the slot, read as a number in `[0, 1)`, is smaller than
+//! `phi(alpha) = 1 - (1 - f)^alpha`, where `f` is the active slot coefficient, i.e. the probability
+//! that a forger holding all the stake is the leader of a slot. `phi` doesn't depend on how the
+//! stake is split between keys: `1 - phi(a + b) = (1 - phi(a)) * (1 - phi(b))`.
+//!
+//! The threshold is computed with fixed point arithmetic over `u128`, with 64 fractional bits, so
+//! that all the nodes get exactly the same result regardless of the platform.
+
+use algebra::PrimeField;
+
+use crate::Error;
+
+const FRACTIONAL_BITS: u32 = 64;
+
+/// The active slot coefficient `f` as the fraction `numerator / denominator`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActiveSlotCoefficient {
+    numerator: u64,
+    denominator: u64,
+}
+
+/// Praos default: 1 slot out of 20 has a leader, on average.
+pub const ACTIVE_SLOT_COEFFICIENT: ActiveSlotCoefficient = ActiveSlotCoefficient { numerator: 1, denominator: 20 };
+
+impl ActiveSlotCoefficient {
+    /// Returns None unless `0 < numerator / denominator <= 1/2`.
+    pub fn new(numerator: u64, denominator: u64) -> Option<Self> {
+        if numerator == 0 || numerator > denominator / 2 {
+            return None;
+        }
+        Some(Self { numerator, denominator })
+    }
+
+    pub fn numerator(&self) -> u64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> u64 {
+        self.denominator
+    }
+
+    // -ln(1 - f) = sum_{k >= 1} f^k / k, in fixed point. Since f <= 1/2, each power of f is at most
+    // half of the previous one, so at most 64 terms are non zero, and the result is below ln(2) < 1.
+    fn minus_ln_complement(&self) -> u128 {
+        let f = ((self.numerator as u128) << FRACTIONAL_BITS) / self.denominator as u128;
+
+        let mut result = 0u128;
+        let mut power = f;
+        let mut k = 1u128;
+        while power > 0 {
+            result += power / k;
+            power = (power * f) >> FRACTIONAL_BITS;
+            k += 1;
+        }
+        result
+    }
+}
+
+/// Returns `phi(stake / total_stake)` as a fraction of 2^64: a leader value is eligible iff it's
+/// strictly smaller than the threshold. The result differs from the exact value by less than 2^-58.
+pub fn leader_threshold(
+    forger_stake: u64,
+    total_forgers_stake: u64,
+    active_slot_coefficient: &ActiveSlotCoefficient,
+) -> Result<u64, Error>
+{
+    if total_forgers_stake == 0 {
+        Err("Total forgers stake must not be zero")?
+    }
+    if forger_stake > total_forgers_stake {
+        Err(format!("Forger stake {} exceeds the total forgers stake {}", forger_stake, total_forgers_stake))?
+    }
+
+    // x = -alpha * ln(1 - f) < 1: the product fits in u128, since both factors are below 2^64
+    let x = forger_stake as u128 * active_slot_coefficient.minus_ln_complement() / total_forgers_stake as u128;
+
+    // phi = 1 - e^(-x) = sum_{k >= 1} (-1)^(k + 1) x^k / k!. The terms are decreasing, so the partial
+    // sums never go below zero, nor above x.
+    let mut threshold = 0u128;
+    let mut term = x;
+    let mut k = 1u128;
+    while term > 0 {
+        if k % 2 == 1 {
+            threshold += term;
+        } else {
+            threshold -= term;
+        }
+        k += 1;
+        term = ((term * x) >> FRACTIONAL_BITS) / k;
+    }
+    Ok(threshold as u64)
+}
+
+/// Reads a VRF output as a number in [0, 1), as a fraction of 2^64. The output is uniformly
+/// distributed modulo a prime much larger than 2^64, so its least significant 64 bits are, too,
+/// up to a negligible bias.
+pub fn leader_value<F: PrimeField>(vrf_output: &F) -> u64 {
+    vrf_output.into_repr().as_ref()[0]
+}
+
+/// Tells if the forger whose VRF output for the slot is `vrf_output` is the slot leader.
+pub fn is_slot_leader<F: PrimeField>(
+    vrf_output: &F,
+    forger_stake: u64,
+    total_forgers_stake: u64,
+    active_slot_coefficient: &ActiveSlotCoefficient,
+) -> Result<bool, Error>
+{
+    let threshold = leader_threshold(forger_stake, total_forgers_stake, active_slot_coefficient)?;
+    Ok(leader_value(vrf_output) < threshold)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use algebra::fields::mnt4753::Fr;
+
+    // Deterministic stand-in for the VRF outputs of consecutive slots
+    fn splitmix64(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    #[test]
+    fn test_active_slot_coefficient() {
+        assert!(ActiveSlotCoefficient::new(0, 20).is_none());
+        assert!(ActiveSlotCoefficient::new(1, 0).is_none());
+        assert!(ActiveSlotCoefficient::new(1, 1).is_none());
+        assert!(ActiveSlotCoefficient::new(2, 3).is_none());
+        assert_eq!(ActiveSlotCoefficient::new(1, 20), Some(ACTIVE_SLOT_COEFFICIENT));
+        assert!(ActiveSlotCoefficient::new(1, 2).is_some());
+        assert!(ActiveSlotCoefficient::new(u64::max_value() / 2, u64::max_value()).is_some());
+    }
+
+    #[test]
+    fn test_leader_threshold() {
+        let f = ACTIVE_SLOT_COEFFICIENT;
+
+        //Known stake distribution, total stake 1_000_000: expected 2^64 * (1 - 0.95^alpha)
+        let expected = [
+            (0, 0u64),
+            (1, 946194249998),
+            (50_000, 47249098766485373),
+            (150_000, 141384537768414895),
+            (300_000, 281685437826016765),
+            (500_000, 467081991932498918),
+            (1_000_000, 922337203685477574),
+        ];
+        for &(stake, threshold) in expected.iter() {
+            assert_eq!(leader_threshold(stake, 1_000_000, &f).unwrap(), threshold);
+        }
+
+        //Only the relative stake matters
+        assert_eq!(
+            leader_threshold(u64::max_value(), u64::max_value(), &f).unwrap(),
+            leader_threshold(1_000_000, 1_000_000, &f).unwrap()
+        );
+        assert_eq!(leader_threshold(1, u64::max_value(), &f).unwrap(), 0);
+
+        //Other coefficients: 2^64 * (1 - 0.5^(1/3)), 2^64 * (1 - 0.9^(12345/67890))
+        let half = ActiveSlotCoefficient::new(1, 2).unwrap();
+        assert_eq!(leader_threshold(1, 1, &half).unwrap(), 9223372036854775798);
+        assert_eq!(leader_threshold(1, 3, &half).unwrap(), 3805553599712205795);
+        let tenth = ActiveSlotCoefficient::new(1, 10).unwrap();
+        assert_eq!(leader_threshold(12345, 67890, &tenth).unwrap(), 350049374666406605);
+
+        //Invalid stakes
+        assert!(leader_threshold(1, 0, &f).is_err());
+        assert!(leader_threshold(2, 1, &f).is_err());
+    }
+
+    #[test]
+    fn test_leader_threshold_properties() {
+        let f = ACTIVE_SLOT_COEFFICIENT;
+        let total = 1_000_000u64;
+        let one = 1u128 << FRACTIONAL_BITS;
+
+        //Monotonic in the stake, and never above f
+        let mut previous = 0;
+        for stake in (0..=total).step_by(10_000) {
+            let threshold = leader_threshold(stake, total, &f).unwrap();
+            assert!(threshold >= previous);
+            previous = threshold;
+        }
+        assert!((previous as u128) <= one / 20);
+
+        //Splitting the stake doesn't change the probability of leading a slot:
+        //1 - phi(a + b) = (1 - phi(a)) * (1 - phi(b)), up to the rounding errors
+        for &(a, b) in [(1u64, 999_999u64), (100_000, 200_000), (250_000, 250_000), (123_456, 654_321)].iter() {
+            let phi_a = leader_threshold(a, total, &f).unwrap() as u128;
+            let phi_b = leader_threshold(b, total, &f).unwrap() as u128;
+            let phi_sum = leader_threshold(a + b, total, &f).unwrap() as u128;
+            let product = ((one - phi_a) * (one - phi_b)) >> FRACTIONAL_BITS;
+            let difference = if product > one - phi_sum { product - (one - phi_sum) } else { (one - phi_sum) - product };
+            assert!(difference < 1 << 7, "phi({}) and phi({}) don't combine into phi({})", a, b, a + b);
+        }
+    }
+
+    #[test]
+    fn test_slot_leaders() {
+        //Stake distribution of 4 forgers, each one evaluating 10_000 slots
+        let stakes = [500_000u64, 300_000, 150_000, 50_000];
+        let expected_leaders = [262, 150, 89, 22];
+        let slots = 10_000;
+
+        for (i, &stake) in stakes.iter().enumerate() {
+            let threshold = leader_threshold(stake, 1_000_000, &ACTIVE_SLOT_COEFFICIENT).unwrap();
+            let mut state = i as u64;
+            let leaders = (0..slots).filter(|_| splitmix64(&mut state) < threshold).count();
+            assert_eq!(leaders, expected_leaders[i]);
+        }
+
+        //Forgers without stake are never leaders, and the leader value is compared strictly
+        assert!(!is_slot_leader(&Fr::from_repr(0u64.into()), 0, 1, &ACTIVE_SLOT_COEFFICIENT).unwrap());
+    }
+}
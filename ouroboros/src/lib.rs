@@ -35,4 +35,8 @@ unsafe_code
 
 //All regarding ouroboros
 
-pub mod vrf;
\ No newline at end of file
+pub type Error = Box<dyn std::error::Error>;
+
+pub mod epoch_nonce;
+pub mod leader_election;
+pub mod vrf;
@@ -1,97 +1,301 @@
-use algebra::curves::mnt6753::G1Projective as MNT6G1Projective;
-//use algebra::curves::mnt6753::G1Affine as MNT6G1Affine;
-use algebra::fields::mnt4753::{Fq as Fs, Fr as Fr};
+use algebra::{
+    curves::mnt6753::G1Projective as MNT6G1Projective,
+    fields::mnt4753::Fr,
+    PrimeField,
+};
 use primitives::{crh::{
     MNT4PoseidonHash,
     bowe_hopwood::{
         BoweHopwoodPedersenCRH, BoweHopwoodPedersenParameters,
     },
-    pedersen::PedersenWindow,
 }, vrf::{
     FieldBasedVrf,
     ecvrf::{
         FieldBasedEcVrf, FieldBasedEcVrfProof,
     },
 }};
+use demo_circuit::{
+    constants::{VRFParams, VRFWindow},
+    domain_separation::{get_personalization, DomainSeparationVersion, HashDomain},
+};
 use rand::rngs::OsRng;
 
-#[derive(Clone)]
-struct TestWindow {}
-impl PedersenWindow for TestWindow {
-    const WINDOW_SIZE: usize = 128;
-    const NUM_WINDOWS: usize = 2;
+use crate::{
+    leader_election::{is_slot_leader, leader_threshold, ACTIVE_SLOT_COEFFICIENT},
+    Error,
+};
+
+// Production instantiation of the VRF, the same used by the sidechains
+pub type GroupHash = BoweHopwoodPedersenCRH<MNT6G1Projective, VRFWindow>;
+pub type GroupHashParameters = BoweHopwoodPedersenParameters<MNT6G1Projective>;
+pub type EcVrfScheme = FieldBasedEcVrf<Fr, MNT6G1Projective, MNT4PoseidonHash, GroupHash>;
+pub type EcVrfProof = FieldBasedEcVrfProof<Fr, MNT6G1Projective>;
+
+/// Returns the group hash parameters of the production VRF. They're expensive to compute: callers
+/// should compute them once and keep them.
+pub fn ouroboros_vrf_parameters() -> GroupHashParameters {
+    let params = VRFParams::new();
+    GroupHashParameters { generators: params.group_hash_generators }
 }
 
-type GroupHash = BoweHopwoodPedersenCRH<MNT6G1Projective, TestWindow>;
-type GroupHashParameters = BoweHopwoodPedersenParameters<MNT6G1Projective>;
-type EcVrfScheme = FieldBasedEcVrf<Fr, MNT6G1Projective, MNT4PoseidonHash, GroupHash>;
-type EcVrfProof = FieldBasedEcVrfProof<Fr, MNT6G1Projective>;
+fn u64_to_field<F: PrimeField>(value: u64) -> F {
+    F::from_repr(F::BigInt::from(value))
+}
 
-pub fn ouroboros_create_proof
-(
-    pp: GroupHashParameters,
-    epoch_randomness: Fr,
-    _slot_number: u32,
-    pk: MNT6G1Projective, //Or MNT6G1Affine and you convert into projective by calling pk.into_projective() inside the function
-    sk: Fs,
-    _forger_stake: u64,
-    _total_forgers_stake: u64
-) -> Option<(EcVrfProof, EcVrfProof)> {
-
-    //Example calling code
+// The personalization of `domain`, separating the two VRF evaluations of a slot so that their outputs
+// are independent. Consensus critical: the version of the personalization is fixed. Being the integer
+// given by at most 16 bytes (tag and version), it's the same in the field of any VRF instantiation.
+fn vrf_personalization<F: PrimeField>(domain: HashDomain) -> Result<F, Error> {
+    let personalization = get_personalization(domain, DomainSeparationVersion::V1)
+        .ok_or("Missing VRF domain personalization")?;
+    let limbs = personalization.into_repr().0;
+    if limbs[2..].iter().any(|&limb| limb != 0) {
+        Err("VRF domain personalization exceeds 128 bits")?
+    }
+    let two_to_32 = u64_to_field::<F>(1 << 32);
+    Ok(u64_to_field::<F>(limbs[1]) * two_to_32 * two_to_32 + u64_to_field::<F>(limbs[0]))
+}
+
+// The VRF is evaluated on (epoch_randomness, slot_number, personalization): a forger gets a fresh,
+// unpredictable lottery ticket for every slot, which it can't grind since its key and the randomness are fixed
+fn vrf_input<F: PrimeField>(epoch_randomness: F, slot_number: u32, domain: HashDomain) -> Result<[F; 3], Error> {
+    Ok([epoch_randomness, u64_to_field(u64::from(slot_number)), vrf_personalization(domain)?])
+}
+
+fn vrf_evaluate<S: FieldBasedVrf>(
+    pp: &S::GHParams,
+    epoch_randomness: S::Data,
+    slot_number: u32,
+    domain: HashDomain,
+    pk: &S::PublicKey,
+    sk: &S::SecretKey,
+) -> Result<(S::Proof, S::Data), Error>
+    where S::Data: PrimeField
+{
     let rng = &mut OsRng;
-    match EcVrfScheme::prove(rng, &pp, &pk, &sk, &[epoch_randomness]) {
-        Ok(proof) => Some((proof.clone(), proof)),
-        _ => None,
+    let input = vrf_input(epoch_randomness, slot_number, domain)?;
+
+    let proof = S::prove(rng, pp, pk, sk, &input)?;
+    let output = S::proof_to_hash(pp, pk, &input, &proof)?;
+    Ok((proof, output))
+}
+
+fn vrf_verify<S: FieldBasedVrf>(
+    pp: &S::GHParams,
+    proof: &S::Proof,
+    epoch_randomness: S::Data,
+    slot_number: u32,
+    domain: HashDomain,
+    pk: &S::PublicKey,
+) -> Result<Option<S::Data>, Error>
+    where S::Data: PrimeField
+{
+    let input = vrf_input(epoch_randomness, slot_number, domain)?;
+    Ok(S::proof_to_hash(pp, pk, &input, proof).ok())
+}
+
+/// VRF proof of the leader election evaluation of a slot.
+pub struct LeaderProof<S: FieldBasedVrf>(pub S::Proof);
+
+impl<S: FieldBasedVrf> Clone for LeaderProof<S> {
+    fn clone(&self) -> Self {
+        LeaderProof(self.0.clone())
     }
 }
 
-pub fn ouroboros_check_proof
-(
-    pp: GroupHashParameters,
-    proof: (EcVrfProof, EcVrfProof),
-    epoch_randomness: Fr,
-    _slot_number: u32,
-    forger_pk: MNT6G1Projective, //Or MNT6G1Affine and you convert into projective by calling pk.into_projective() inside the function
-    _forger_stake: u64,
-    _total_forgers_stake: u64,
-
-) -> Option<(Fr, Fr)> {
-    //Example calling code
-    match (
-        EcVrfScheme::proof_to_hash(&pp, &forger_pk, &[epoch_randomness], &proof.0),
-        EcVrfScheme::proof_to_hash(&pp, &forger_pk, &[epoch_randomness], &proof.1),
-        )
-    {
-        (Ok(o1), Ok(o2)) => Some((o1, o2)),
-        _ => None,
+/// VRF proof of the epoch nonce evaluation of a slot.
+pub struct NonceProof<S: FieldBasedVrf>(pub S::Proof);
+
+impl<S: FieldBasedVrf> Clone for NonceProof<S> {
+    fn clone(&self) -> Self {
+        NonceProof(self.0.clone())
+    }
+}
+
+/// Verified output of the leader election evaluation of a slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeaderOutput<F: PrimeField>(F);
+
+impl<F: PrimeField> LeaderOutput<F> {
+    pub fn value(&self) -> F {
+        self.0
     }
 }
 
-/*
-// NOTE: To simplify I put all the concrete types and didn't templatize anything. Actually this could
-// be easily templatized, and when calling the functions passing values of the concrete types. The
-// templatized version of the above functions looks like this:
+/// Verified output of the epoch nonce evaluation of a slot, i.e. the contribution of the slot
+/// leader to the nonce of the next epochs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceOutput<F: PrimeField>(F);
 
-pub fn ouroboros_create_proof_templatized<S: FieldBasedVrf>
+impl<F: PrimeField> NonceOutput<F> {
+    pub fn value(&self) -> F {
+        self.0
+    }
+}
+
+/// Returns the proofs of the forger being the leader of `slot_number`, or None if it's not.
+pub fn ouroboros_create_proof<S: FieldBasedVrf>
 (
-    _pp: S::GHParams,
-    _epoch_randomness: S::Data,
-    _slot_number: u32,
-    _pk: S::PublicKey,
-    _forger_stake: u64,
-    _total_forgers_stake: u64
-) -> Option<(S::Proof, S::Proof)> {unimplemented!()}
-
-pub fn ouroboros_check_proof_templatized<S: FieldBasedVrf>
+    pp: &S::GHParams,
+    epoch_randomness: S::Data,
+    slot_number: u32,
+    pk: &S::PublicKey,
+    sk: &S::SecretKey,
+    forger_stake: u64,
+    total_forgers_stake: u64
+) -> Result<Option<(LeaderProof<S>, NonceProof<S>)>, Error>
+    where S::Data: PrimeField
+{
+    let (leader_proof, leader_output) = vrf_evaluate::<S>(pp, epoch_randomness, slot_number, HashDomain::VrfLeader, pk, sk)?;
+
+    if !is_slot_leader(&leader_output, forger_stake, total_forgers_stake, &ACTIVE_SLOT_COEFFICIENT)? {
+        return Ok(None);
+    }
+
+    let (nonce_proof, _) = vrf_evaluate::<S>(pp, epoch_randomness, slot_number, HashDomain::VrfNonce, pk, sk)?;
+    Ok(Some((LeaderProof(leader_proof), NonceProof(nonce_proof))))
+}
+
+/// Returns the outputs of both the evaluations if the proofs are valid and prove that the forger
+/// is the leader of `slot_number`, None otherwise. An invalid proof is not an error, while an
+/// invalid stake is.
+pub fn ouroboros_check_proof<S: FieldBasedVrf>
 (
-    _pp: S::GHParams,
-    _proof: (S::Proof, S::Proof),
-    _epoch_randomness: S::Data,
-    _slot_number: u32,
-    _forger_pk: S::PublicKey,
-    _forger_stake: u64,
-    _total_forgers_stake: u64,
-
-) -> Option<(S::Data, S::Data)> {unimplemented!()}
-*/
\ No newline at end of file
+    pp: &S::GHParams,
+    leader_proof: &LeaderProof<S>,
+    nonce_proof: &NonceProof<S>,
+    epoch_randomness: S::Data,
+    slot_number: u32,
+    forger_pk: &S::PublicKey,
+    forger_stake: u64,
+    total_forgers_stake: u64,
+) -> Result<Option<(LeaderOutput<S::Data>, NonceOutput<S::Data>)>, Error>
+    where S::Data: PrimeField
+{
+    //Reject invalid stakes, whatever the proofs
+    leader_threshold(forger_stake, total_forgers_stake, &ACTIVE_SLOT_COEFFICIENT)?;
+
+    let leader_output = match vrf_verify::<S>(pp, &leader_proof.0, epoch_randomness, slot_number, HashDomain::VrfLeader, forger_pk)? {
+        Some(output) => output,
+        None => return Ok(None),
+    };
+    if !is_slot_leader(&leader_output, forger_stake, total_forgers_stake, &ACTIVE_SLOT_COEFFICIENT)? {
+        return Ok(None);
+    }
+
+    let nonce_output = match vrf_verify::<S>(pp, &nonce_proof.0, epoch_randomness, slot_number, HashDomain::VrfNonce, forger_pk)? {
+        Some(output) => output,
+        None => return Ok(None),
+    };
+    Ok(Some((LeaderOutput(leader_output), NonceOutput(nonce_output))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use algebra::{
+        curves::mnt4753::G1Projective as MNT4G1Projective,
+        fields::mnt6753::Fr as MNT6Fr,
+        UniformRand,
+    };
+    use primitives::crh::{FixedLengthCRH, MNT6PoseidonHash, pedersen::PedersenWindow};
+    use rand::thread_rng;
+
+    // A second VRF instantiation, over the other curve of the cycle and with its own generators
+    #[derive(Clone)]
+    struct TestWindow {}
+    impl PedersenWindow for TestWindow {
+        const WINDOW_SIZE: usize = 64;
+        const NUM_WINDOWS: usize = 4;
+    }
+
+    type TestGroupHash = BoweHopwoodPedersenCRH<MNT4G1Projective, TestWindow>;
+    type TestEcVrfScheme = FieldBasedEcVrf<MNT6Fr, MNT4G1Projective, MNT6PoseidonHash, TestGroupHash>;
+
+    fn test_ouroboros_proof<S: FieldBasedVrf>(pp: &S::GHParams)
+        where S::Data: PrimeField
+    {
+        let rng = &mut thread_rng();
+        let (pk, sk) = S::keygen(rng);
+        let epoch_randomness = S::Data::rand(rng);
+        let total_stake = 1_000_000u64;
+
+        //With all the stake, a forger leads 1 slot out of 20 on average: it takes ~200 slots to find 10
+        let mut leader_slots = Vec::new();
+        let mut slot = 0u32;
+        while leader_slots.len() < 10 {
+            if let Some((leader_proof, nonce_proof)) = ouroboros_create_proof::<S>(pp, epoch_randomness, slot, &pk, &sk, total_stake, total_stake).unwrap() {
+                let (leader_output, nonce_output) = ouroboros_check_proof::<S>(
+                    pp, &leader_proof, &nonce_proof, epoch_randomness, slot, &pk, total_stake, total_stake
+                ).unwrap().unwrap();
+
+                //The two evaluations are independent
+                assert_ne!(leader_output.value(), nonce_output.value());
+                leader_slots.push((slot, leader_proof, nonce_proof));
+            }
+            slot += 1;
+        }
+        assert!(leader_slots.len() < slot as usize);
+
+        let (slot, leader_proof, nonce_proof) = &leader_slots[0];
+        let (other_pk, _) = S::keygen(rng);
+        let check = |leader_proof: &LeaderProof<S>, nonce_proof: &NonceProof<S>, epoch_randomness: S::Data, slot: u32, pk: &S::PublicKey, stake: u64, total_stake: u64| {
+            ouroboros_check_proof::<S>(pp, leader_proof, nonce_proof, epoch_randomness, slot, pk, stake, total_stake)
+        };
+
+        //Proofs are bound to the slot, the epoch randomness and the key
+        assert!(check(leader_proof, nonce_proof, epoch_randomness, slot + 1, &pk, total_stake, total_stake).unwrap().is_none());
+        assert!(check(leader_proof, nonce_proof, S::Data::rand(rng), *slot, &pk, total_stake, total_stake).unwrap().is_none());
+        assert!(check(leader_proof, nonce_proof, epoch_randomness, *slot, &other_pk, total_stake, total_stake).unwrap().is_none());
+
+        //Proofs are bound to their evaluation: swapping them fails
+        let swapped_leader_proof = LeaderProof::<S>(nonce_proof.0.clone());
+        let swapped_nonce_proof = NonceProof::<S>(leader_proof.0.clone());
+        assert!(check(&swapped_leader_proof, &swapped_nonce_proof, epoch_randomness, *slot, &pk, total_stake, total_stake).unwrap().is_none());
+        assert!(check(leader_proof, &swapped_nonce_proof, epoch_randomness, *slot, &pk, total_stake, total_stake).unwrap().is_none());
+
+        //A valid nonce proof from another slot is rejected too
+        let (_, _, other_nonce_proof) = &leader_slots[1];
+        assert!(check(leader_proof, other_nonce_proof, epoch_randomness, *slot, &pk, total_stake, total_stake).unwrap().is_none());
+
+        //Without stake, the forger is never the leader
+        assert!(check(leader_proof, nonce_proof, epoch_randomness, *slot, &pk, 0, total_stake).unwrap().is_none());
+        assert!(ouroboros_create_proof::<S>(pp, epoch_randomness, *slot, &pk, &sk, 0, total_stake).unwrap().is_none());
+
+        //Invalid stakes are errors, whatever the proofs
+        assert!(check(leader_proof, nonce_proof, epoch_randomness, *slot, &pk, 1, 0).is_err());
+        assert!(check(leader_proof, nonce_proof, epoch_randomness, slot + 1, &pk, 2, 1).is_err());
+        assert!(ouroboros_create_proof::<S>(pp, epoch_randomness, *slot, &pk, &sk, 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_vrf_input_personalization() {
+        let rng = &mut thread_rng();
+        let epoch_randomness = Fr::rand(rng);
+
+        //The last element of the input is the personalization of the domain, in both the fields
+        for &domain in [HashDomain::VrfLeader, HashDomain::VrfNonce].iter() {
+            let personalization = get_personalization(domain, DomainSeparationVersion::V1).unwrap();
+            let input = vrf_input(epoch_randomness, 7, domain).unwrap();
+            assert_eq!(input, [epoch_randomness, u64_to_field(7), personalization]);
+
+            let test_personalization = vrf_personalization::<MNT6Fr>(domain).unwrap();
+            assert_eq!(test_personalization.into_repr(), personalization.into_repr());
+        }
+        assert_ne!(
+            vrf_personalization::<Fr>(HashDomain::VrfLeader).unwrap(),
+            vrf_personalization::<Fr>(HashDomain::VrfNonce).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ouroboros_proof_production_vrf() {
+        test_ouroboros_proof::<EcVrfScheme>(&ouroboros_vrf_parameters());
+    }
+
+    #[test]
+    fn test_ouroboros_proof_test_vrf() {
+        let pp = TestGroupHash::setup(&mut thread_rng()).unwrap();
+        test_ouroboros_proof::<TestEcVrfScheme>(&pp);
+    }
+}